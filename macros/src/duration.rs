@@ -213,6 +213,6 @@ pub fn duration(item: TokenStream) -> Result<TokenStream> {
 
 	Ok(quote! {{
 		#[allow(clippy::unnecessary_cast)]
-		::std::time::Duration::from_nanos((#nanos) as u64)
+		::core::time::Duration::from_nanos((#nanos) as u64)
 	}})
 }
@@ -195,9 +195,9 @@ impl Variant {
 		let write = if *is_transparent {
 			let field = member_as_ident(&self.fields.members[0].0);
 
-			quote! { ::std::fmt::Display::fmt(#field, #fmt) }
+			quote! { ::core::fmt::Display::fmt(#field, #fmt) }
 		} else {
-			quote! { ::std::write!(#fmt, #display) }
+			quote! { ::core::write!(#fmt, #display) }
 		};
 
 		let matcher = self.matcher();
@@ -216,9 +216,9 @@ impl Variant {
 		let write = if is_transparent {
 			let field = member_as_ident(&self.fields.members[0].0);
 
-			quote! { ::std::fmt::Debug::fmt(#field, #fmt) }
+			quote! { ::core::fmt::Debug::fmt(#field, #fmt) }
 		} else if let Some((debug, _)) = self.debug.as_ref() {
-			quote! { ::std::write!(#fmt, #debug) }
+			quote! { ::core::write!(#fmt, #debug) }
 		} else {
 			let mut debug = Punctuated::<Expr, Token![.]>::new();
 			let ident = self.ident.to_string();
@@ -330,7 +330,7 @@ impl Input {
 
 		if attrs.no_debug.is_none() {
 			input.attrs.push(parse_quote! {
-				#[derive(::std::fmt::Debug)]
+				#[derive(::core::fmt::Debug)]
 			});
 		}
 
@@ -423,7 +423,7 @@ impl Input {
 				}
 
 				quote! {
-					::std::mem::discriminant(self) == ::std::mem::discriminant(other)
+					::core::mem::discriminant(self) == ::core::mem::discriminant(other)
 				}
 			}
 		};
@@ -438,7 +438,7 @@ impl Input {
 				clause = add_bounds(
 					&self.input.generics,
 					where_clause,
-					&[parse_quote! { ::std::fmt::Display }]
+					&[parse_quote! { ::core::fmt::Display }]
 				);
 
 				Some(&clause)
@@ -446,8 +446,8 @@ impl Input {
 
 			let gen_fmt = |trait_name, fmts| {
 				quote! {
-					impl #impl_generics ::std::fmt::#trait_name for #name #type_generics #where_clause {
-						fn fmt(&self, #fmt: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+					impl #impl_generics ::core::fmt::#trait_name for #name #type_generics #where_clause {
+						fn fmt(&self, #fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
 							#[allow(unused_variables)]
 							match self {
 								#fmts
@@ -468,7 +468,7 @@ impl Input {
 
 		for (body, ty) in froms {
 			from_impls.push(quote_spanned! { ty.span() =>
-				impl #impl_generics ::std::convert::From<#ty> for #name #type_generics #where_clause {
+				impl #impl_generics ::core::convert::From<#ty> for #name #type_generics #where_clause {
 					fn from(value: #ty) -> Self {
 						#body
 					}
@@ -482,17 +482,17 @@ impl Input {
 
 			#(#from_impls)*
 
-			impl #impl_generics ::std::cmp::PartialEq for #name #type_generics #where_clause {
+			impl #impl_generics ::core::cmp::PartialEq for #name #type_generics #where_clause {
 				fn eq(&self, other: &Self) -> bool {
 					#eq
 				}
 			}
 
-			impl #impl_generics ::std::error::Error for #name #type_generics #where_clause
+			impl #impl_generics ::core::error::Error for #name #type_generics #where_clause
 			where
-				Self: ::std::fmt::Debug + ::std::fmt::Display
+				Self: ::core::fmt::Debug + ::core::fmt::Display
 			{
-				fn source(&self) -> ::std::option::Option<&(dyn ::std::error::Error + 'static)> {
+				fn source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
 					#[allow(unused_variables)]
 					match self {
 						#(#sources,)*
@@ -503,8 +503,8 @@ impl Input {
 
 			impl #impl_generics ::xx_core::error::internal::ErrorImpl for #name #type_generics #where_clause
 			where
-				Self: ::std::error::Error + ::std::marker::Send
-					+ ::std::marker::Sync + 'static
+				Self: ::core::error::Error + ::core::marker::Send
+					+ ::core::marker::Sync + 'static
 			{
 				fn kind(&self) -> ::xx_core::error::ErrorKind {
 					#[allow(unused_variables)]
@@ -123,7 +123,7 @@ fn transform_func(func: &mut Function<'_>) -> Result<()> {
 				#(#attrs)*
 				let #ident = {
 					const _: () = {
-						let _ = ::std::mem::#color::<()>;
+						let _ = ::core::mem::#color::<()>;
 					};
 
 					let request = #request;
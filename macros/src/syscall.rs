@@ -81,7 +81,7 @@ impl SyscallImpl {
 				pub unsafe fn #func(num: i32, #(#args: usize),*) -> isize {
 					let result;
 
-					::std::arch::asm!(
+					::core::arch::asm!(
 						#instruction,
 						in(#num) num,
 						#(in(#regs) #args,)*
@@ -245,7 +245,7 @@ pub fn syscall_define(attrs: TokenStream, item: TokenStream) -> Result<TokenStre
 			let number = (#number) as i32;
 
 			{
-				use ::std::convert::{From, Into};
+				use ::core::convert::{From, Into};
 				use ::xx_core::os::syscall::{IntoRaw, IntoRawArray, SyscallParameter, syscall_raw};
 
 				let result = unsafe {
@@ -260,7 +260,7 @@ pub fn syscall_define(attrs: TokenStream, item: TokenStream) -> Result<TokenStre
 		}
 
 		#(#attrs)* #vis #sig {
-			use ::std::convert::TryInto;
+			use ::core::convert::TryInto;
 			use ::xx_core::os::syscall::{IntoRaw, IntoRawArray};
 
 			#(#vars)*
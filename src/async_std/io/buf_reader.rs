@@ -4,6 +4,7 @@ use std::ops::Range;
 
 use super::*;
 use crate::impls::UintExt;
+use crate::os::mman::MirrorBuffer;
 
 macro_rules! identity {
 	($buffered:expr) => {
@@ -57,7 +58,7 @@ macro_rules! impl_bufread {
 			}
 
 			async fn fill_buf(&mut self) -> Result<usize> {
-				let read = self.fill_buf_range(0..self.data.len()).await?;
+				let read = self.fill_buf_range(0..self.data.capacity()).await?;
 
 				if read != 0 {
 					self.buffered.start = 0;
@@ -87,8 +88,17 @@ macro_rules! impl_bufread {
 			/// Shift unconsumed bytes to the beginning to make space for calls to
 			/// [`fill`] without discarding any unconsumed data
 			///
+			/// If the buffer is a [`MirrorBuffer`], the spare capacity is always
+			/// contiguous and this never copies any data
+			///
 			/// [`fill`]: BufRead::fill
 			pub fn move_data_to_beginning(&mut self) {
+				if self.data.is_mirrored() {
+					self.data.wrap(&mut $buffered!(self.buffered));
+
+					return;
+				}
+
 				if self.buffered.start == 0 {
 					return;
 				}
@@ -127,6 +137,8 @@ macro_rules! impl_bufread {
 			async fn fill_amount(&mut self, amount: usize) -> Result<usize> {
 				assert!(amount <= self.capacity());
 
+				self.data.wrap(&mut $buffered!(self.buffered));
+
 				let mut start = self.buffered.end;
 
 				/* cannot overflow here due to limits of buf's length */
@@ -137,7 +149,7 @@ macro_rules! impl_bufread {
 					return Ok(0);
 				}
 
-				if end > self.capacity() {
+				if end > self.capacity() && !self.data.is_mirrored() {
 					end = amount;
 
 					if $buffered!(self.buffered).is_empty() {
@@ -167,12 +179,11 @@ macro_rules! impl_bufread {
 			}
 
 			fn capacity(&self) -> usize {
-				self.data.len()
+				self.data.capacity()
 			}
 
 			fn spare_capacity(&self) -> usize {
-				#[allow(clippy::arithmetic_side_effects)]
-				(self.data.len() - self.buffered.end)
+				self.data.spare_range(&self.buffered).len()
 			}
 
 			#[allow(unsafe_code)]
@@ -189,6 +200,12 @@ macro_rules! impl_bufread {
 			}
 
 			fn unconsume(&mut self, count: usize) {
+				/* a mirrored buffer overwrites consumed data as soon as it is filled */
+				assert!(
+					!self.data.is_mirrored() || count <= self.spare_capacity(),
+					"`count` > `self.spare_capacity()`"
+				);
+
 				self.buffered.start = self
 					.buffered
 					.start
@@ -211,9 +228,19 @@ macro_rules! impl_bufread {
 						break;
 					};
 
+					/* a mirrored buffer overwrites the bytes before `start` as it's
+					 * filled, so only the ones that haven't been overwritten yet are
+					 * still buffered
+					 */
+					let min = if self.data.is_mirrored() {
+						self.buffered.end.saturating_sub(self.data.capacity())
+					} else {
+						0
+					};
+
 					/* wrap cannot happen due to limits of buf's len */
 					#[allow(clippy::cast_possible_wrap)]
-					if pos < 0 || pos > self.buffered.end as i64 {
+					if pos < min as i64 || pos > self.buffered.end as i64 {
 						break;
 					}
 
@@ -313,7 +340,7 @@ macro_rules! impl_bufread {
 
 /// The async equivalent of [`std::io::BufReader`]
 pub struct BufReader<R: ?Sized> {
-	data: Box<[u8]>,
+	data: Storage,
 	buffered: Range<usize>,
 	reader: R
 }
@@ -340,7 +367,7 @@ impl<R: ?Sized> BufReader<R> {
 	///
 	/// # Panics
 	/// If `pos > buf.len()`
-	pub fn from_parts(reader: R, mut buf: Vec<u8>, pos: usize) -> Self
+	pub fn from_parts(reader: R, buf: Vec<u8>, pos: usize) -> Self
 	where
 		R: Sized
 	{
@@ -348,15 +375,30 @@ impl<R: ?Sized> BufReader<R> {
 
 		assert!(pos <= len);
 
-		buf.resize(buf.capacity(), 0);
-
 		Self {
 			reader,
-			data: buf.into_boxed_slice(),
+			data: Storage::from_vec(buf),
 			buffered: pos..len
 		}
 	}
 
+	/// Creates a new `BufReader<R>` backed by a [`MirrorBuffer`]
+	///
+	/// Buffered data wraps around the end of the buffer instead of being
+	/// moved to the beginning, so [`fill`] never has to copy any data
+	///
+	/// [`fill`]: BufRead::fill
+	pub fn with_mirror_buffer(reader: R, buf: MirrorBuffer) -> Self
+	where
+		R: Sized
+	{
+		Self {
+			reader,
+			data: Storage::Mirrored(buf),
+			buffered: 0..0
+		}
+	}
+
 	/// Unwraps this `BufReader<R>`, returning the underlying reader
 	///
 	/// Any leftover data in the internal buffer is lost. A subsequent
@@ -386,9 +428,7 @@ impl<R: ?Sized> BufReader<R> {
 	where
 		R: Sized
 	{
-		let mut buf = self.data.into_vec();
-
-		buf.truncate(self.buffered.end);
+		let buf = self.data.into_vec(&self.buffered);
 
 		(self.reader, buf, self.buffered.start)
 	}
@@ -397,7 +437,7 @@ impl<R: ?Sized> BufReader<R> {
 impl_bufread!(BufReader, identity);
 
 pub struct BufReadHalf<'a, R: ?Sized> {
-	data: &'a mut [u8],
+	data: &'a mut Storage,
 	buffered: &'a mut Range<usize>,
 	reader: R
 }
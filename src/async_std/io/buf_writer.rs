@@ -4,10 +4,11 @@ use std::ops::Range;
 
 use super::*;
 use crate::impls::UintExt;
+use crate::os::mman::MirrorBuffer;
 
 /// The async equivalent of [`std::io::BufWriter`]
 pub struct BufWriter<W: ?Sized> {
-	data: Box<[u8]>,
+	data: Storage,
 	buffered: Range<usize>,
 	writer: W
 }
@@ -28,20 +29,26 @@ impl<W> BufWriter<W> {
 	///
 	/// # Panics
 	/// If `pos > buf.len()`
-	pub fn from_parts(writer: W, mut buf: Vec<u8>, pos: usize) -> Self {
+	pub fn from_parts(writer: W, buf: Vec<u8>, pos: usize) -> Self {
 		let len = buf.len();
 
 		assert!(pos <= len);
 
-		buf.resize(buf.capacity(), 0);
-
 		Self {
 			writer,
-			data: buf.into_boxed_slice(),
+			data: Storage::from_vec(buf),
 			buffered: pos..len
 		}
 	}
 
+	/// Creates a new `BufWriter<W>` backed by a [`MirrorBuffer`]
+	///
+	/// Buffered data wraps around the end of the buffer, so space freed by a
+	/// partial flush can be reused without moving any data
+	pub fn with_mirror_buffer(writer: W, buf: MirrorBuffer) -> Self {
+		Self { writer, data: Storage::Mirrored(buf), buffered: 0..0 }
+	}
+
 	/// Unwraps this `BufWriter<W>`, returning the underlying writer
 	///
 	/// Any unflushed data in the internal buffer is lost
@@ -54,9 +61,7 @@ impl<W> BufWriter<W> {
 	/// The `Vec<u8>` contains the buffered data, and the `usize` is the
 	/// position to start flushing from
 	pub fn into_parts(self) -> (W, Vec<u8>, usize) {
-		let mut buf = self.data.into_vec();
-
-		buf.truncate(self.buffered.end);
+		let buf = self.data.into_vec(&self.buffered);
 
 		(self.writer, buf, self.buffered.start)
 	}
@@ -69,14 +74,16 @@ impl<W: Write + ?Sized> BufWriter<W> {
 		self.buffered = 0..0;
 	}
 
-	#[allow(clippy::arithmetic_side_effects)]
-	const fn spare_capacity(&self) -> usize {
-		self.data.len() - self.buffered.end
+	fn spare_capacity(&self) -> usize {
+		self.data.spare_range(&self.buffered).len()
 	}
 
 	/// Reads from `buf` into our internal buffer
 	fn write_buffered(&mut self, buf: &[u8]) -> usize {
-		let read = read_into_slice(&mut self.data[self.buffered.end..], buf);
+		self.data.wrap(&mut self.buffered);
+
+		let spare = self.data.spare_range(&self.buffered);
+		let read = read_into_slice(&mut self.data[spare], buf);
 
 		#[allow(clippy::arithmetic_side_effects)]
 		(self.buffered.end += read);
@@ -125,7 +132,10 @@ impl<W: Write + ?Sized> BufWriter<W> {
 			self.flush().await?;
 		}
 
-		let buf = &mut self.data[self.buffered.end..];
+		self.data.wrap(&mut self.buffered);
+
+		let spare = self.data.spare_range(&self.buffered);
+		let buf = &mut self.data[spare];
 		let read = reader.read(buf).await?;
 
 		#[allow(clippy::arithmetic_side_effects)]
@@ -171,7 +181,7 @@ impl<W: Write + ?Sized> Write for BufWriter<W> {
 
 		self.flush_buf().await?;
 
-		Ok(if buf.len() >= self.data.len() {
+		Ok(if buf.len() >= self.data.capacity() {
 			let wrote = self.writer.write(buf).await?;
 
 			#[cfg(feature = "tracing")]
@@ -262,7 +272,7 @@ impl<W: Write + Seek + ?Sized> Seek for BufWriter<W> {
 	/// If there was an overflow calculating the stream position
 	async fn stream_position(&mut self) -> Result<u64> {
 		let pos = self.writer.stream_position().await?;
-		let buffered = self.data.len();
+		let buffered = self.buffered.len();
 
 		Ok(pos
 			.checked_add(buffered as u64)
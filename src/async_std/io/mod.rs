@@ -21,6 +21,10 @@ pub mod split;
 pub mod typed;
 pub mod write;
 
+mod storage;
+
+use storage::Storage;
+
 #[doc(inline)]
 pub use {buf_reader::*, buf_writer::*, read::*, seek::*, split::*, write::*};
 
@@ -0,0 +1,87 @@
+//! Backing storage for buffered readers and writers
+
+use std::ops::{Deref, DerefMut, Range};
+
+use crate::os::mman::MirrorBuffer;
+
+pub(super) enum Storage {
+	Boxed(Box<[u8]>),
+
+	/// The slice is twice the capacity, so any buffered range starting in the
+	/// first half is contiguous and data never has to be moved
+	Mirrored(MirrorBuffer)
+}
+
+impl Storage {
+	pub(super) fn from_vec(mut buf: Vec<u8>) -> Self {
+		buf.resize(buf.capacity(), 0);
+
+		Self::Boxed(buf.into_boxed_slice())
+	}
+
+	pub(super) const fn capacity(&self) -> usize {
+		match self {
+			Self::Boxed(data) => data.len(),
+			Self::Mirrored(data) => data.capacity()
+		}
+	}
+
+	pub(super) const fn is_mirrored(&self) -> bool {
+		matches!(self, Self::Mirrored(_))
+	}
+
+	/// The range in which data can be appended after `buffered`
+	pub(super) fn spare_range(&self, buffered: &Range<usize>) -> Range<usize> {
+		#[allow(clippy::arithmetic_side_effects)]
+		match self {
+			Self::Boxed(data) => buffered.end..data.len(),
+			Self::Mirrored(data) => buffered.end..buffered.start + data.capacity()
+		}
+	}
+
+	/// Moves `buffered` back into the first half of a mirrored buffer. This
+	/// never copies any data
+	pub(super) fn wrap(&self, buffered: &mut Range<usize>) {
+		let Self::Mirrored(data) = self else {
+			return;
+		};
+
+		if buffered.start >= data.capacity() {
+			#[allow(clippy::arithmetic_side_effects)]
+			(*buffered = buffered.start - data.capacity()..buffered.end - data.capacity());
+		}
+	}
+
+	pub(super) fn into_vec(self, buffered: &Range<usize>) -> Vec<u8> {
+		match self {
+			Self::Boxed(data) => {
+				let mut buf = data.into_vec();
+
+				buf.truncate(buffered.end);
+				buf
+			}
+
+			Self::Mirrored(data) => data.as_slice()[0..buffered.end].to_vec()
+		}
+	}
+}
+
+impl Deref for Storage {
+	type Target = [u8];
+
+	fn deref(&self) -> &[u8] {
+		match self {
+			Self::Boxed(data) => data,
+			Self::Mirrored(data) => data.as_slice()
+		}
+	}
+}
+
+impl DerefMut for Storage {
+	fn deref_mut(&mut self) -> &mut [u8] {
+		match self {
+			Self::Boxed(data) => data,
+			Self::Mirrored(data) => data.as_mut_slice()
+		}
+	}
+}
@@ -242,11 +242,17 @@ impl<T: Clone> Receiver<T> {
 	/// Receive a value, suspending if the channel is currently empty
 	pub async fn recv(&mut self) -> RecvResult<T> {
 		let mut backoff = Backoff::new();
+		let mut suspended = false;
 
 		loop {
 			let result = self.try_recv();
 
 			if !matches!(result, Err(RecvError::Empty)) || is_interrupted().await {
+				/* suspending refills the budget, so only charge when we didn't */
+				if !suspended {
+					consume_budget().await;
+				}
+
 				return result;
 			}
 
@@ -255,6 +261,7 @@ impl<T: Clone> Receiver<T> {
 				let should_block = || self.channel.tail.load(Ordering::SeqCst) == self.pos;
 				let _ = self.channel.rx_waiters.wait(should_block).await;
 
+				suspended = true;
 				backoff.reset();
 			} else {
 				backoff.snooze();
@@ -317,11 +317,17 @@ macro_rules! channel_impl {
 			/// Receive a value, suspending if the channel is currently empty
 			pub async fn recv(&self) -> RecvResult<T> {
 				let mut backoff = Backoff::new();
+				let mut suspended = false;
 
 				loop {
 					let result = self.try_recv();
 
 					if !matches!(result, Err(RecvError::Empty)) || is_interrupted().await {
+						/* suspending refills the budget, so only charge when we didn't */
+						if !suspended {
+							consume_budget().await;
+						}
+
 						return result;
 					}
 
@@ -329,6 +335,7 @@ macro_rules! channel_impl {
 					if backoff.is_completed() || !acquire_budget(backoff.step() as u32 + 1).await {
 						self.channel.recv_wait().await;
 
+						suspended = true;
 						backoff.reset();
 					} else {
 						backoff.snooze();
@@ -438,10 +445,19 @@ macro_rules! channel_impl {
 			/// Send a value, suspending if the channel is currently full
 			pub async fn send(&self, mut value: T) -> SendResult<T> {
 				let mut backoff = Backoff::new();
+				let mut suspended = false;
 
 				loop {
 					match self.try_send(value) {
-						Ok(()) => return Ok(()),
+						Ok(()) => {
+							/* suspending refills the budget, so only charge when we didn't */
+							if !suspended {
+								consume_budget().await;
+							}
+
+							return Ok(());
+						}
+
 						Err(err @ SendError::Closed(_)) => return Err(err),
 						result if is_interrupted().await => return result,
 						Err(SendError::Full(v)) => value = v
@@ -451,6 +467,7 @@ macro_rules! channel_impl {
 					if backoff.is_completed() || !acquire_budget(backoff.step() as u32 + 1).await {
 						self.channel.send_wait().await;
 
+						suspended = true;
 						backoff.reset();
 					} else {
 						backoff.snooze();
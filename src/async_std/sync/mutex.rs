@@ -174,7 +174,15 @@ impl<T: ?Sized> Mutex<T> {
 	/// [`try_lock`]: Self::try_lock
 	/// [`WouldBlock`]: TryLockError::WouldBlock
 	pub async fn lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
-		if !self.try_lock_internal() {
+		/* yield before taking the lock, so that it's never held while yielding */
+		if current_budget().await == 0 {
+			yield_now().await;
+		}
+
+		if self.try_lock_internal() {
+			/* only charge when we didn't suspend, which refills the budget */
+			let _ = acquire_budget(None).await;
+		} else {
 			let locked = self.lock_contended().await;
 
 			if !locked {
@@ -1,5 +1,5 @@
-use std::cell;
-use std::ops::{Deref, DerefMut};
+use core::cell;
+use core::ops::{Deref, DerefMut};
 
 use crate::macros::wrapper_functions;
 use crate::pointer::*;
@@ -52,7 +52,7 @@ impl<T: Copy + PartialEq> PartialEq<T> for Cell<T> {
 }
 
 impl<T: Copy + PartialOrd> PartialOrd<T> for Cell<T> {
-	fn partial_cmp(&self, other: &T) -> Option<std::cmp::Ordering> {
+	fn partial_cmp(&self, other: &T) -> Option<core::cmp::Ordering> {
 		self.get().partial_cmp(other)
 	}
 }
@@ -79,14 +79,14 @@ impl<T: ?Sized> UnsafeCell<T> {
 	}
 
 	/// # Safety
-	/// Caller must enforce aliasing rules. See std::ptr::as_ref
+	/// Caller must enforce aliasing rules. See core::ptr::as_ref
 	pub unsafe fn as_ref<'a>(&self) -> &'a T {
 		/* Safety: guaranteed by caller */
 		unsafe { self.get().as_ref() }
 	}
 
 	/// # Safety
-	/// Caller must enforce aliasing rules. See std::ptr::as_ref
+	/// Caller must enforce aliasing rules. See core::ptr::as_ref
 	pub unsafe fn as_mut<'a>(&self) -> &'a mut T {
 		/* Safety: guaranteed by caller */
 		unsafe { self.get().as_mut() }
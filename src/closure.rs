@@ -1,5 +1,5 @@
-use std::marker::PhantomData;
-use std::mem::transmute;
+use core::marker::PhantomData;
+use core::mem::transmute;
 
 use crate::pointer::*;
 
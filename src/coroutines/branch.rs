@@ -1,6 +1,6 @@
 #![allow(clippy::multiple_unsafe_ops_per_block, unreachable_pub)]
 
-use std::mem::replace;
+use std::mem::{replace, size_of};
 
 use super::*;
 use crate::cell::Cell;
@@ -434,3 +434,291 @@ where
 	/* Safety: branch is pinned. we are blocked until the future completes */
 	block_on(unsafe { Branch::run(ptr!(&mut *branch.pin_local())) }).await
 }
+
+pub struct BranchAllOutput<O>(pub Option<usize>, pub Vec<Option<MaybePanic<O>>>);
+
+impl<O> BranchAllOutput<MaybePanic<O>> {
+	pub fn flatten(self) -> BranchAllOutput<O> {
+		let results = self
+			.1
+			.into_iter()
+			.map(|result| result.map(|result| result.and_then(|result| result)))
+			.collect();
+
+		BranchAllOutput(self.0, results)
+	}
+}
+
+pub struct BranchAll<F: Future, Cancel> {
+	handles: Box<[FutureHandle<F>]>,
+	request: ReqPtr<BranchAllOutput<F::Output>>,
+	should_cancel: Cancel,
+
+	/// The number of futures started and not yet completed
+	pending: usize,
+
+	/// The first future whose result requested a cancel
+	first: Option<usize>,
+	cancelling: bool,
+
+	/// Set while we iterate over the handles, during which completions must
+	/// not complete the request
+	busy: bool
+}
+
+impl<F: Future, C: Fn(&MaybePanic<F::Output>) -> bool> BranchAll<F, C> {
+	/// # Safety
+	/// all futures must have completed or never started
+	unsafe fn output(&mut self) -> BranchAllOutput<F::Output> {
+		let results = self
+			.handles
+			.iter_mut()
+			.map(|handle| {
+				if handle.done() {
+					/* Safety: the future completed */
+					Some(unsafe { handle.result() })
+				} else {
+					None
+				}
+			})
+			.collect();
+
+		BranchAllOutput(self.first, results)
+	}
+
+	/// # Safety
+	/// `this` must be valid, and not busy
+	/// `this` may become dangling after the function call
+	unsafe fn try_complete(this: MutPtr<Self>) -> bool {
+		/* Safety: guaranteed by caller */
+		let this = unsafe { this.as_mut() };
+
+		if this.pending != 0 {
+			return false;
+		}
+
+		/* Safety: all futures completed */
+		let output = unsafe { this.output() };
+
+		/* Safety: complete the future. we must not access `self` after this */
+		unsafe { Request::complete(this.request, output) };
+
+		true
+	}
+
+	/// Cancel every pending future. Completions that occur within are only
+	/// recorded
+	///
+	/// # Safety
+	/// `this` must be valid and busy
+	unsafe fn cancel_pending(this: MutPtr<Self>) -> Result<()> {
+		let mut result = Ok(());
+
+		/* Safety: guaranteed by caller */
+		let len = unsafe { ptr!(this=>handles.len()) };
+
+		/* Safety: guaranteed by caller */
+		unsafe { ptr!(this=>cancelling = true) };
+
+		for index in 0..len {
+			/* Safety: the handles are not resized */
+			let cancel = unsafe { ptr!(this=>handles[index].take_cancel()) };
+
+			let Some(cancel) = cancel else {
+				continue;
+			};
+
+			/* Safety: the future is in progress */
+			let cancel_result = unsafe { run_cancel(cancel) };
+
+			/* we can't do much if the cancel panics */
+			if let Ok(Err(err)) = cancel_result {
+				if result.is_ok() {
+					result = Err(err);
+				}
+			}
+		}
+
+		result
+	}
+
+	/// # Safety
+	/// must only be called when a task completes
+	#[allow(clippy::arithmetic_side_effects)]
+	unsafe fn complete(request: ReqPtr<F::Output>, arg: Ptr<()>, value: F::Output) {
+		let this_ptr = arg.cast::<Self>().cast_mut();
+
+		/* Safety: we have mutable access here */
+		let this = unsafe { this_ptr.as_mut() };
+
+		/* every handle has the request at the same offset */
+		let index = (request.addr() - this.handles.as_ptr().addr()) / size_of::<FutureHandle<F>>();
+
+		/* Safety: the future has completed */
+		let result = unsafe { this.handles[index].complete(Ok(value)) };
+
+		/* Safety: guaranteed by Future's contract */
+		let should_cancel = call_no_unwind(|| (this.should_cancel)(result));
+
+		this.pending -= 1;
+
+		if should_cancel && this.first.is_none() {
+			this.first = Some(index);
+		}
+
+		if this.busy {
+			return;
+		}
+
+		if this.first.is_some() && !this.cancelling && this.pending != 0 {
+			this.busy = true;
+
+			/* Safety: we are busy. we can't do much if the cancel fails */
+			let _ = unsafe { Self::cancel_pending(this_ptr) };
+
+			/* Safety: reborrow has ended */
+			unsafe { ptr!(this_ptr=>busy = false) };
+		}
+
+		/* Safety: not busy */
+		unsafe { Self::try_complete(this_ptr) };
+	}
+
+	pub fn new<I>(futures: I, should_cancel: C) -> Self
+	where
+		I: IntoIterator<Item = F>
+	{
+		let handles = futures
+			.into_iter()
+			/* Safety: complete does not unwind */
+			.map(|future| unsafe { FutureHandle::new(future, Self::complete) })
+			.collect();
+
+		/* request args are assigned once pinned */
+		Self {
+			handles,
+			request: Ptr::null(),
+			should_cancel,
+			pending: 0,
+			first: None,
+			cancelling: false,
+			busy: false
+		}
+	}
+
+	/// # Safety
+	/// See [`Cancel::run`]
+	unsafe fn cancel_all(this: MutPtr<Self>) -> Result<()> {
+		/* Safety: guaranteed by future's contract */
+		unsafe { ptr!(this=>busy = true) };
+
+		/* Safety: we are busy */
+		let result = unsafe { Self::cancel_pending(this) };
+
+		/* Safety: guaranteed by future's contract */
+		unsafe { ptr!(this=>busy = false) };
+
+		/* Safety: not busy. the request may complete here */
+		unsafe { Self::try_complete(this) };
+
+		result
+	}
+
+	/// # Safety
+	/// see `Future::run`
+	/// self must be pinned
+	/// `this` must be a valid pointer
+	#[future]
+	#[allow(clippy::arithmetic_side_effects)]
+	pub unsafe fn run(this: MutPtr<Self>, request: _) -> BranchAllOutput<F::Output> {
+		#[cancel]
+		fn cancel(self: MutPtr<Self>) -> Result<()> {
+			/* Safety: caller must uphold Future's contract */
+			unsafe { Self::cancel_all(self) }
+		}
+
+		/* Safety: guaranteed by caller */
+		let len = unsafe {
+			ptr!(this=>request = request);
+			ptr!(this=>busy = true);
+			ptr!(this=>handles.len())
+		};
+
+		for index in 0..len {
+			/* Safety: guaranteed by caller */
+			unsafe { ptr!(this=>pending += 1) };
+
+			/* Safety: caller must uphold Future's contract */
+			let should_cancel = unsafe {
+				ptr!(this=>handles[index].run()).map(|result| (ptr!(this=>should_cancel))(result))
+			};
+
+			if let Some(should_cancel) = should_cancel {
+				/* Safety: guaranteed by caller */
+				unsafe {
+					ptr!(this=>pending -= 1);
+
+					if should_cancel && ptr!(this=>first).is_none() {
+						ptr!(this=>first = Some(index));
+					}
+				}
+			}
+
+			/* even if this future is still pending, an earlier one may have
+			 * completed while it started and asked to cancel the rest
+			 *
+			 * Safety: guaranteed by caller
+			 */
+			if unsafe { ptr!(this=>first).is_some() } {
+				/* the remaining futures are never started */
+				break;
+			}
+		}
+
+		/* Safety: guaranteed by caller */
+		unsafe {
+			if ptr!(this=>first).is_some() && ptr!(this=>pending) != 0 {
+				/* we can't do much if the cancel fails */
+				let _ = Self::cancel_pending(this);
+			}
+
+			ptr!(this=>busy = false);
+
+			if ptr!(this=>pending) == 0 {
+				return Progress::Done(ptr!(this=>output()));
+			}
+		}
+
+		Progress::Pending(cancel(this))
+	}
+}
+
+impl<F: Future, Cancel> Pin for BranchAll<F, Cancel> {
+	unsafe fn pin(&mut self) {
+		let arg = ptr!(&*self);
+
+		for handle in self.handles.iter_mut() {
+			handle.set_arg(arg.cast());
+		}
+	}
+}
+
+/// Runs all `futures`, and waits for every one of them to finish
+///
+/// Once `should_cancel` returns true for a result, the futures that haven't
+/// started are skipped and the ones in progress are cancelled
+///
+/// # Safety
+/// `should_cancel` must not unwind
+#[asynchronous]
+pub async unsafe fn branch_all<I, F, C>(futures: I, should_cancel: C) -> BranchAllOutput<F::Output>
+where
+	I: IntoIterator<Item = F>,
+	F: Future,
+	C: Fn(&MaybePanic<F::Output>) -> bool
+{
+	let mut branch = BranchAll::new(futures, should_cancel);
+
+	/* Safety: branch is pinned. we are blocked until the future completes */
+	block_on(unsafe { BranchAll::run(ptr!(&mut *branch.pin_local())) }).await
+}
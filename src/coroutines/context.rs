@@ -1,7 +1,9 @@
 #![allow(clippy::multiple_unsafe_ops_per_block)]
 
-use std::any::TypeId;
+use std::any::{Any, TypeId};
 use std::hash::{DefaultHasher, Hash, Hasher};
+use std::mem::replace;
+use std::rc::Rc;
 
 use super::*;
 use crate::cell::*;
@@ -37,11 +39,15 @@ unsafe fn wake(_: ReqPtr<()>, arg: Ptr<()>, _: ()) {
 
 type Canceller = DynFnOnce<'static, (), Result<()>>;
 
+pub(super) type LocalValue = Rc<dyn Any>;
+pub(super) type LocalValues = Vec<(Ptr<()>, LocalValue)>;
+
 struct Data {
 	budget: Cell<u16>,
 	guards: Cell<u32>,
 	interrupted: Cell<bool>,
-	cancel: UnsafeCell<Option<Canceller>>
+	cancel: UnsafeCell<Option<Canceller>>,
+	locals: UnsafeCell<LocalValues>
 }
 
 impl Data {
@@ -53,7 +59,8 @@ impl Data {
 			budget: Cell::new(DEFAULT_BUDGET as u16),
 			guards: Cell::new(0),
 			interrupted: Cell::new(false),
-			cancel: UnsafeCell::new(None)
+			cancel: UnsafeCell::new(None),
+			locals: UnsafeCell::new(Vec::new())
 		}
 	}
 }
@@ -155,6 +162,19 @@ impl Context {
 		}
 	}
 
+	/// Refill the budget, and suspend the worker until the executor resumes it
+	/// behind the workers that yielded before it
+	///
+	/// # Safety
+	/// See [`scoped`]
+	pub(super) unsafe fn yield_now(&self) {
+		#[allow(clippy::cast_possible_truncation)]
+		self.data.budget.set(DEFAULT_BUDGET as u16);
+
+		/* Safety: guaranteed by caller */
+		unsafe { ptr!(self.worker=>yield_now()) };
+	}
+
 	pub(super) fn current_budget(&self) -> u16 {
 		self.data.budget.get()
 	}
@@ -174,12 +194,65 @@ impl Context {
 		self.data.guards == 0 && self.data.interrupted.get()
 	}
 
+	/// Returns true if the worker has an interrupt, including one that is
+	/// pending due to guards
+	pub(super) fn interrupt_pending(&self) -> bool {
+		self.data.interrupted.get()
+	}
+
 	/// Clears any interrupts or pending interrupts (due to guards) on the
 	/// current worker
 	pub(super) fn clear_interrupt(&self) {
 		self.data.interrupted.set(false);
 	}
 
+	#[allow(clippy::mut_from_ref)]
+	fn locals(&self) -> &mut LocalValues {
+		/* Safety: exclusive unsafe cell access */
+		unsafe { self.data.locals.as_mut() }
+	}
+
+	/// Returns the task local value for `key`
+	pub(super) fn local(&self, key: Ptr<()>) -> Option<LocalValue> {
+		self.locals()
+			.iter()
+			.find(|(local, _)| *local == key)
+			.map(|(_, value)| value.clone())
+	}
+
+	/// Sets or removes the task local value for `key`, returning the previous
+	/// value
+	pub(super) fn replace_local(
+		&self, key: Ptr<()>, value: Option<LocalValue>
+	) -> Option<LocalValue> {
+		let locals = self.locals();
+		let index = locals.iter().position(|(local, _)| *local == key);
+
+		match (index, value) {
+			(Some(index), Some(value)) => Some(replace(&mut locals[index].1, value)),
+			(Some(index), None) => Some(locals.swap_remove(index).1),
+			(None, Some(value)) => {
+				locals.push((key, value));
+
+				None
+			}
+
+			(None, None) => None
+		}
+	}
+
+	/// Replaces every task local value, returning the previous values
+	pub(super) fn replace_locals(&self, locals: LocalValues) -> LocalValues {
+		replace(self.locals(), locals)
+	}
+
+	/// Copies the task local values of `parent`, for a task spawned by it
+	pub(super) fn inherit_locals(&self, parent: &Self) {
+		let locals = parent.locals().clone();
+
+		*self.locals() = locals;
+	}
+
 	/// # Safety
 	/// the context must be alive while it's executing
 	/// this function is unsafe so that Context::run doesn't need
@@ -1,14 +1,24 @@
 #![allow(clippy::multiple_unsafe_ops_per_block)]
 
+use std::collections::VecDeque;
+
 use super::*;
-use crate::cell::Cell;
+use crate::cell::{Cell, UnsafeCell};
 
 /// Per thread executor, responsible for running worker threads
 #[cfg_attr(not(any(doc, feature = "xx-doc")), repr(C))]
 pub struct Executor {
 	current: Cell<Ptr<Worker>>,
 	main: Worker,
-	pool: Ptr<Pool>
+	pool: Ptr<Pool>,
+
+	/// Workers that yielded, in the order they are resumed by
+	/// [`Executor::run_ready`]
+	ready: UnsafeCell<VecDeque<Ptr<Worker>>>,
+
+	/// Whether the runtime calls [`Executor::run_ready`]. If not, yielding
+	/// workers keep running
+	drives_ready: bool
 }
 
 impl Executor {
@@ -28,10 +38,19 @@ impl Executor {
 			main: Worker::main(),
 
 			/* current is assigned once pinned */
-			current: Cell::new(Ptr::null())
+			current: Cell::new(Ptr::null()),
+			ready: UnsafeCell::new(VecDeque::new()),
+			drives_ready: false
 		}
 	}
 
+	/// Declare that the runtime calls [`Executor::run_ready`] whenever it would
+	/// otherwise wait for work, so that workers which yield are suspended until
+	/// then. Runtimes that don't opt in never see a worker suspend to yield
+	pub fn set_drives_ready(&mut self, drives_ready: bool) {
+		self.drives_ready = drives_ready;
+	}
+
 	/// # Safety
 	/// `pool` must be either valid for this executor or null
 	pub unsafe fn set_pool(&mut self, pool: Ptr<Pool>) {
@@ -41,16 +60,27 @@ impl Executor {
 	/// # Safety
 	/// Executor must outlive the worker
 	pub unsafe fn new_worker(&self, start: Start) -> Worker {
-		if self.pool.is_null() {
-			/* Safety: guaranteed by caller */
-			unsafe { Worker::new(ptr!(self), start) }
+		/* Safety: guaranteed by caller */
+		unsafe { self.new_worker_with_stack(start, None) }
+	}
+
+	/// Same as [`Executor::new_worker`], with a hint for the size of the
+	/// worker's stack. See [`Pool::new_fiber_with_stack`]
+	///
+	/// # Safety
+	/// Executor must outlive the worker
+	pub unsafe fn new_worker_with_stack(&self, start: Start, stack_size: Option<usize>) -> Worker {
+		let fiber = if self.pool.is_null() {
+			let options = StackOptions { size: stack_size, ..StackOptions::new() };
+
+			Fiber::with_stack_and_start(&options, start)
 		} else {
 			/* Safety: pool must be valid for this executor */
-			let fiber = unsafe { ptr!(self.pool=>new_fiber(start)) };
+			unsafe { ptr!(self.pool=>new_fiber_with_stack(start, stack_size)) }
+		};
 
-			/* Safety: guaranteed by caller */
-			unsafe { Worker::from_fiber(ptr!(self), fiber) }
-		}
+		/* Safety: guaranteed by caller */
+		unsafe { Worker::from_fiber(ptr!(self), fiber) }
 	}
 
 	/// Workers move themselves onto their own stack when
@@ -87,6 +117,55 @@ impl Executor {
 		unsafe { Fiber::switch(worker.fiber(), ptr!(from=>fiber())) };
 	}
 
+	/// Suspend the current `worker`, and queue it to be resumed by
+	/// [`Executor::run_ready`] behind the workers that yielded before it. Does
+	/// nothing unless the runtime [drives the ready queue]
+	///
+	/// # Safety
+	/// same as suspend
+	///
+	/// [drives the ready queue]: Executor::set_drives_ready
+	pub(super) unsafe fn yield_worker(&self, worker: Ptr<Worker>) {
+		if !self.drives_ready {
+			return;
+		}
+
+		/* Safety: exclusive unsafe cell access */
+		unsafe { self.ready.as_mut() }.push_back(worker);
+
+		/* Safety: guaranteed by caller */
+		unsafe { self.suspend(worker) };
+	}
+
+	/// Resume the workers that yielded before this call, in the order they
+	/// yielded. Workers that yield again are resumed by the next call. Returns
+	/// the number of workers resumed
+	///
+	/// Runtimes that opt in with [`Executor::set_drives_ready`] call this
+	/// whenever they would otherwise wait for work
+	///
+	/// # Safety
+	/// The executor must be pinned, and this must be called on the executor's
+	/// thread
+	pub unsafe fn run_ready(&self) -> usize {
+		/* Safety: exclusive unsafe cell access */
+		let count = unsafe { self.ready.as_ref() }.len();
+
+		for _ in 0..count {
+			/* Safety: exclusive unsafe cell access. the borrow ends before the
+			 * worker runs
+			 */
+			let Some(worker) = (unsafe { self.ready.as_mut() }.pop_front()) else {
+				break;
+			};
+
+			/* Safety: the worker is suspended, so it's not on the call stack */
+			unsafe { self.resume(worker) };
+		}
+
+		count
+	}
+
 	/// Switch from whichever `current` worker is running to the new `worker`
 	///
 	/// # Safety
@@ -91,3 +91,80 @@ where
 	/* Safety: this is a join */
 	unsafe { Join::from_branch(branch.flatten()) }
 }
+
+/// Joins every task in `tasks` and waits for all of them to finish,
+/// returning their results in the same order
+///
+/// If a task panics, an attempt to cancel the others is made,
+/// then the panic resumes on the caller
+///
+/// # Safety
+/// The cloned `env` and the tasks must outlive their spawned fiber
+#[asynchronous]
+pub async unsafe fn join_all<E, I, T, O>(env: &E, tasks: I) -> Vec<O>
+where
+	E: Environment,
+	I: IntoIterator<Item = T>,
+	T: for<'ctx> Task<Output<'ctx> = O>
+{
+	/* Safety: guaranteed by caller */
+	let branch = unsafe {
+		branch_all(
+			tasks.into_iter().map(|task| spawn_task_with_env(env, task)),
+			|result| !matches!(result, Ok(Ok(_)))
+		)
+	}
+	.await;
+
+	let BranchAllOutput(first, mut results) = branch.flatten();
+
+	if let Some(result) = first.and_then(|index| results[index].take()) {
+		/* resume the panic that cancelled the other tasks */
+		runtime::join(result);
+	}
+
+	results
+		.into_iter()
+		/* Safety: no task panicked, so every task ran to completion */
+		.map(|result| runtime::join(unsafe { result.expect_unchecked("Branch failed") }))
+		.collect()
+}
+
+/// Joins every task in `tasks` and waits for all of them to finish,
+/// returning their results in the same order
+///
+/// If a task returns an error, the others are cancelled, and the first error
+/// is returned once every task finishes. Panics are handled the same as
+/// [`join_all`]
+///
+/// # Safety
+/// The cloned `env` and the tasks must outlive their spawned fiber
+#[asynchronous]
+pub async unsafe fn try_join_all<E, I, T, O, Er>(env: &E, tasks: I) -> result::Result<Vec<O>, Er>
+where
+	E: Environment,
+	I: IntoIterator<Item = T>,
+	T: for<'ctx> Task<Output<'ctx> = result::Result<O, Er>>
+{
+	/* Safety: guaranteed by caller */
+	let branch = unsafe {
+		branch_all(
+			tasks.into_iter().map(|task| spawn_task_with_env(env, task)),
+			|result| !matches!(result, Ok(Ok(Ok(_))))
+		)
+	}
+	.await;
+
+	let BranchAllOutput(first, mut results) = branch.flatten();
+
+	if let Some(result) = first.and_then(|index| results[index].take()) {
+		/* resume the panic, or return the error that cancelled the other tasks */
+		runtime::join(result)?;
+	}
+
+	results
+		.into_iter()
+		/* Safety: no task failed, so every task ran to completion */
+		.map(|result| runtime::join(unsafe { result.expect_unchecked("Branch failed") }))
+		.collect()
+}
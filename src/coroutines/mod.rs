@@ -17,9 +17,13 @@ pub mod environment;
 pub mod executor;
 pub mod impls;
 pub mod join;
+pub mod multi_thread;
 pub mod ops;
+pub mod scope;
 pub mod select;
 pub mod spawn;
+pub mod task_local;
+pub mod task_set;
 pub mod wake;
 pub mod worker;
 
@@ -27,7 +31,8 @@ pub mod internal;
 
 #[doc(inline)]
 pub use {
-	context::*, environment::*, executor::*, join::*, select::*, spawn::*, wake::*, worker::*
+	context::*, environment::*, executor::*, join::*, scope::*, select::*, spawn::*, task_local::*,
+	task_set::*, wake::*, worker::*
 };
 
 use self::branch::*;
@@ -162,7 +167,8 @@ pub async fn current_budget() -> u32 {
 /// Returns whether or not the budget was successfully acquired.
 ///
 /// Note that this function does not do any suspending itself. It is up to the
-/// caller to suspend if this function returns `false`.
+/// caller to suspend if this function returns `false`, for example with
+/// [`yield_now`].
 #[asynchronous]
 #[allow(clippy::impl_trait_in_params)]
 pub async fn acquire_budget(amount: impl Into<Option<u32>>) -> bool {
@@ -171,6 +177,33 @@ pub async fn acquire_budget(amount: impl Into<Option<u32>>) -> bool {
 	get_context().await.decrease_budget(amount).is_some()
 }
 
+/// Reschedule the current worker behind the other workers that are ready to
+/// run, and refill its budget
+///
+/// The worker is resumed by the next call to [`Executor::run_ready`] from the
+/// runtime. If the runtime doesn't [drive the ready queue], the worker keeps
+/// running with a refilled budget.
+///
+/// [drive the ready queue]: Executor::set_drives_ready
+#[asynchronous]
+pub async fn yield_now() {
+	/* Safety: we are in an async function */
+	unsafe { get_context().await.yield_now() };
+}
+
+/// Consume one unit of budget for work that finished without suspending, and
+/// [`yield_now`] if the budget ran out.
+///
+/// Operations that usually complete immediately, such as receiving from a
+/// non-empty channel, call this so that a busy worker can't starve the other
+/// workers on its thread.
+#[asynchronous]
+pub async fn consume_budget() {
+	if !acquire_budget(None).await {
+		yield_now().await;
+	}
+}
+
 /// An async worker that is being cancelled is in an interrupted state.
 ///
 /// Most I/O and blocking operations like timers, locking a mutex, or receiving
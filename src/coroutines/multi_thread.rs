@@ -0,0 +1,743 @@
+//! A runtime that runs async tasks on several executor threads
+//!
+//! Each thread owns an [`Executor`] and a fiber [`Pool`]. Tasks wait in a
+//! queue on the thread they are placed on until they start. Idle threads
+//! steal tasks spawned with [`Runtime::spawn_anywhere`] that haven't started
+//! from the queues of busy threads, while tasks spawned with
+//! [`Runtime::spawn_on`] always run on their thread. Once a task starts, it
+//! stays on its thread. Tasks that yield are resumed by their thread before
+//! it waits for more work. Woken tasks are resumed through an
+//! [`EventFdWaker`], so the tasks can block on futures that complete from
+//! other threads, including the [`RemoteJoinHandle`] of a task on another
+//! thread
+//!
+//! ```
+//! let runtime = Runtime::new(Options::default())?;
+//! let handle = runtime.spawn_anywhere(compute());
+//!
+//! let result = handle.join_blocking();
+//! ```
+
+#![allow(clippy::multiple_unsafe_ops_per_block)]
+
+use std::collections::VecDeque;
+use std::mem::take;
+use std::sync::atomic::{fence, AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use super::*;
+use crate::os::sched::{available_parallelism, pin_current_thread, sched_getaffinity};
+use crate::sync::{Stealer, WorkQueue};
+
+/// How long an idle executor thread sleeps before checking for work again
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The number of unstarted tasks that each thread keeps where other threads
+/// can steal them. The rest wait in the thread's injection queue
+const QUEUE_CAPACITY: usize = 256;
+
+type Job = Box<dyn FnOnce(&ThreadEnv) + Send>;
+
+struct ThreadShared {
+	waker: EventFdWaker,
+
+	/// Jobs that must run on this thread
+	jobs: Mutex<VecDeque<Job>>,
+
+	/// Tasks placed on this thread, before the thread moves them to its queue
+	injected: Mutex<VecDeque<Job>>,
+	stealer: Stealer<Job>,
+
+	/// The number of tasks on this thread that haven't completed, including
+	/// the ones that haven't started
+	load: AtomicUsize,
+	idle: AtomicBool,
+	shutdown: AtomicBool
+}
+
+impl ThreadShared {
+	fn new(stealer: Stealer<Job>) -> Result<Self> {
+		Ok(Self {
+			waker: EventFdWaker::new()?,
+			jobs: Mutex::new(VecDeque::new()),
+			injected: Mutex::new(VecDeque::new()),
+			stealer,
+			load: AtomicUsize::new(0),
+			idle: AtomicBool::new(false),
+			shutdown: AtomicBool::new(false)
+		})
+	}
+
+	#[allow(clippy::unwrap_used)]
+	fn post(&self, job: Job) {
+		self.jobs.lock().unwrap().push_back(job);
+		self.waker.notify();
+	}
+
+	#[allow(clippy::unwrap_used)]
+	fn pop(&self) -> Option<Job> {
+		self.jobs.lock().unwrap().pop_front()
+	}
+
+	/// Place a task on this thread, where other threads can't steal it
+	fn post_task(&self, task: Job) {
+		self.load.fetch_add(1, Ordering::Relaxed);
+		self.post(task);
+	}
+
+	/// Place a task that hasn't started on this thread, where other threads
+	/// can steal it
+	#[allow(clippy::unwrap_used)]
+	fn inject(&self, task: Job) {
+		self.load.fetch_add(1, Ordering::Relaxed);
+		self.injected.lock().unwrap().push_back(task);
+		self.waker.notify();
+	}
+
+	/// Move injected tasks to `queue`, until it's full
+	#[allow(clippy::unwrap_used)]
+	fn refill(&self, queue: &WorkQueue<Job>) {
+		let mut injected = self.injected.lock().unwrap();
+
+		while let Some(task) = injected.pop_front() {
+			if let Err(task) = queue.push(task) {
+				injected.push_front(task);
+
+				break;
+			}
+		}
+	}
+
+	/// Take a task that hasn't started from another thread
+	fn steal(&self) -> Option<Job> {
+		self.stealer
+			.steal()
+			.or_else(|| self.injected.try_lock().ok()?.pop_front())
+	}
+
+	/// Whether this thread has tasks that haven't started
+	fn has_tasks(&self) -> bool {
+		!self.stealer.is_empty() ||
+			self.injected
+				.try_lock()
+				.is_ok_and(|injected| !injected.is_empty())
+	}
+
+	/// Whether this thread can exit. `load` only counts the tasks placed on
+	/// this thread, so the workers they spawned are counted by `pool`
+	#[allow(clippy::unwrap_used)]
+	fn finished(&self, pool: &Pool) -> bool {
+		self.shutdown.load(Ordering::Acquire) &&
+			self.load.load(Ordering::Acquire) == 0 &&
+			pool.stats().active == 0 &&
+			self.jobs.lock().unwrap().is_empty()
+	}
+}
+
+/// Wake a thread that's waiting for work, so that it can steal a task
+fn wake_idle(threads: &[Arc<ThreadShared>]) {
+	/* pairs with the fence in `run_thread`, so that either the idle thread
+	 * sees the new task, or we see that it's idle
+	 */
+	fence(Ordering::SeqCst);
+
+	let idle = threads
+		.iter()
+		.find(|thread| thread.idle.swap(false, Ordering::Relaxed));
+
+	if let Some(thread) = idle {
+		thread.waker.notify();
+	}
+}
+
+/// The environment of the tasks on an executor thread
+#[repr(C)]
+struct ThreadEnv {
+	context: Context,
+	executor: Ptr<Executor>,
+	thread: Ptr<Arc<ThreadShared>>
+}
+
+impl ThreadEnv {
+	/// # Safety
+	/// `executor` and `thread` must outlive the environment
+	unsafe fn new(executor: Ptr<Executor>, thread: Ptr<Arc<ThreadShared>>) -> Self {
+		/* Safety: guaranteed by caller */
+		let waker = unsafe { ptr!(thread=>waker.waker()) };
+
+		Self {
+			/* Safety: the worker is set when spawned */
+			context: unsafe { Context::new::<Self>(Some(waker)) },
+			executor,
+			thread
+		}
+	}
+
+	/// The thread this environment runs on
+	fn thread(&self) -> &Arc<ThreadShared> {
+		/* Safety: guaranteed by `ThreadEnv::new` */
+		unsafe { self.thread.as_ref() }
+	}
+}
+
+/* Safety: the context is the first field */
+unsafe impl Environment for ThreadEnv {
+	fn context(&self) -> &Context {
+		&self.context
+	}
+
+	fn context_mut(&mut self) -> &mut Context {
+		&mut self.context
+	}
+
+	unsafe fn from_context(context: &Context) -> &Self {
+		/* Safety: guaranteed by caller. the struct is repr(C) */
+		unsafe { ptr!(context).cast::<Self>().as_ref() }
+	}
+
+	unsafe fn clone(&self) -> Self {
+		/* Safety: the executor and thread outlive every task on the thread */
+		unsafe { Self::new(self.executor, self.thread) }
+	}
+
+	fn executor(&self) -> Ptr<Executor> {
+		self.executor
+	}
+}
+
+/// Take the next task for the thread at `index`, stealing one from another
+/// thread if it has none
+fn next_task(threads: &[Arc<ThreadShared>], index: usize, queue: &WorkQueue<Job>) -> Option<Job> {
+	let this = &threads[index];
+
+	this.refill(queue);
+
+	if let Some(task) = queue.pop() {
+		return Some(task);
+	}
+
+	for offset in 1..threads.len() {
+		#[allow(clippy::arithmetic_side_effects)]
+		let victim = &threads[(index + offset) % threads.len()];
+
+		let Some(task) = victim.steal() else {
+			continue;
+		};
+
+		/* the task now counts towards this thread's load */
+		this.load.fetch_add(1, Ordering::Relaxed);
+		victim.load.fetch_sub(1, Ordering::Release);
+
+		return Some(task);
+	}
+
+	None
+}
+
+fn run_thread(
+	threads: &[Arc<ThreadShared>], index: usize, queue: &WorkQueue<Job>, cpu: Option<usize>,
+	pool: Pool, prewarm: usize
+) {
+	if let Some(cpu) = cpu {
+		if let Err(err) = pin_current_thread(cpu) {
+			warn!("== Failed to pin executor thread to cpu {}: {:?}", cpu, err);
+		}
+	}
+
+	if overflow::enabled() {
+		if let Err(err) = overflow::install_thread() {
+			warn!("== Failed to set up stack overflow detection: {:?}", err);
+		}
+	}
+
+	let this = &threads[index];
+
+	pool.prewarm(prewarm, None);
+
+	/* Safety: the pool outlives the executor */
+	let mut executor = unsafe { Executor::new_with_pool(ptr!(&pool)) };
+
+	executor.set_drives_ready(true);
+
+	let executor = executor.pin_box();
+
+	/* Safety: the executor and thread outlive the environment */
+	let env = unsafe { ThreadEnv::new(ptr!(&*executor), ptr!(this)) };
+
+	loop {
+		while let Some(job) = this.pop() {
+			job(&env);
+		}
+
+		/* Safety: we are on the executor thread, outside of any worker */
+		let resumed = unsafe { executor.run_ready() };
+
+		if let Some(task) = next_task(threads, index, queue) {
+			/* let another thread take the rest of our tasks while we're busy */
+			if !queue.is_empty() {
+				wake_idle(threads);
+			}
+
+			task(&env);
+
+			/* Safety: we are on the executor thread, outside of any worker */
+			unsafe { this.waker.drain() };
+
+			continue;
+		}
+
+		if resumed != 0 {
+			/* Safety: we are on the executor thread, outside of any worker */
+			unsafe { this.waker.drain() };
+
+			/* the resumed workers may have yielded again */
+			continue;
+		}
+
+		if this.finished(&pool) {
+			break;
+		}
+
+		this.idle.store(true, Ordering::Relaxed);
+
+		/* pairs with the fence in `wake_idle` */
+		fence(Ordering::SeqCst);
+
+		/* check for tasks placed on other threads before sleeping */
+		if threads.iter().any(|thread| thread.has_tasks()) {
+			this.idle.store(false, Ordering::Relaxed);
+
+			continue;
+		}
+
+		/* Safety: we are on the executor thread, outside of any worker */
+		let _ = unsafe { this.waker.wait(IDLE_TIMEOUT) };
+
+		this.idle.store(false, Ordering::Relaxed);
+	}
+}
+
+enum Waiter<Output> {
+	None,
+	Request(ReqPtr<SpawnResult<Output>>),
+	Thread(thread::Thread)
+}
+
+impl<Output> Default for Waiter<Output> {
+	fn default() -> Self {
+		Self::None
+	}
+}
+
+struct Remote<Output> {
+	/// The thread that started the task
+	owner: Option<Arc<ThreadShared>>,
+
+	/// The context of the task while it's running
+	context: Ptr<Context>,
+
+	/// Set if the task is cancelled before it starts
+	cancelled: bool,
+	result: Option<SpawnResult<Output>>,
+	waiter: Waiter<Output>
+}
+
+struct RemoteState<Output> {
+	remote: Mutex<Remote<Output>>
+}
+
+/* Safety: the pointers are only dereferenced on the thread they belong to */
+unsafe impl<Output: Send> Send for RemoteState<Output> {}
+
+/* Safety: see above */
+unsafe impl<Output: Send> Sync for RemoteState<Output> {}
+
+impl<Output: Send + 'static> RemoteState<Output> {
+	const fn new() -> Self {
+		Self {
+			remote: Mutex::new(Remote {
+				owner: None,
+				context: Ptr::null(),
+				cancelled: false,
+				result: None,
+				waiter: Waiter::None
+			})
+		}
+	}
+
+	#[allow(clippy::unwrap_used)]
+	fn lock(&self) -> std::sync::MutexGuard<'_, Remote<Output>> {
+		self.remote.lock().unwrap()
+	}
+
+	/// Called on the thread that is about to start the task
+	fn place(&self, owner: &Arc<ThreadShared>) {
+		self.lock().owner = Some(owner.clone());
+	}
+
+	/// Returns `true` if the task was cancelled before it started
+	fn start(&self, context: Ptr<Context>) -> bool {
+		let mut remote = self.lock();
+
+		remote.context = context;
+		remote.cancelled
+	}
+
+	fn complete(&self, result: SpawnResult<Output>) {
+		let mut remote = self.lock();
+
+		if let Some(owner) = remote.owner.take() {
+			owner.load.fetch_sub(1, Ordering::Release);
+		}
+
+		remote.context = Ptr::null();
+
+		match take(&mut remote.waiter) {
+			Waiter::Request(request) => {
+				drop(remote);
+
+				/* Safety: the waiter is blocked on a thread safe `RemoteState::join` */
+				unsafe { Request::complete(request, result) };
+			}
+
+			Waiter::Thread(thread) => {
+				remote.result = Some(result);
+
+				drop(remote);
+
+				thread.unpark();
+			}
+
+			Waiter::None => remote.result = Some(result)
+		}
+	}
+
+	fn request_cancel(this: &Arc<Self>) -> Result<()> {
+		let mut remote = this.lock();
+
+		if remote.result.is_some() {
+			return Ok(());
+		}
+
+		let Some(owner) = remote.owner.clone() else {
+			/* the task is interrupted once it starts */
+			remote.cancelled = true;
+
+			return Ok(());
+		};
+
+		drop(remote);
+
+		let state = this.clone();
+
+		owner.post(Box::new(move |_| {
+			let context = state.lock().context;
+
+			if !context.is_null() {
+				/* Safety: we are on the task's thread, and the task is running */
+				let _ = unsafe { Context::interrupt(context) };
+			}
+		}));
+
+		Ok(())
+	}
+
+	/// # Safety
+	/// must be blocked on with [`block_on_thread_safe`]
+	#[future]
+	unsafe fn join(this: &Arc<Self>, request: _) -> SpawnResult<Output> {
+		#[cancel]
+		fn cancel(this: &Arc<Self>) -> Result<()> {
+			RemoteState::request_cancel(this)
+		}
+
+		let mut remote = this.lock();
+
+		if let Some(result) = remote.result.take() {
+			return Progress::Done(result);
+		}
+
+		remote.waiter = Waiter::Request(request);
+
+		Progress::Pending(cancel(this))
+	}
+}
+
+struct RemoteTask<T, Output> {
+	task: T,
+	state: Arc<RemoteState<Output>>
+}
+
+#[asynchronous(task)]
+impl<T, Output> Task for RemoteTask<T, Output>
+where
+	T: for<'ctx> Task<Output<'ctx> = Output>,
+	Output: Send + 'static
+{
+	type Output = ();
+
+	async fn run(self) {
+		let Self { task, state } = self;
+		let context = get_context().await;
+
+		if state.start(ptr!(context)) {
+			/* Safety: the task is running on this thread, and isn't blocked */
+			let _ = unsafe { Context::interrupt(ptr!(context)) };
+		}
+
+		/* Safety: we are in an async function */
+		let result = catch_unwind_safe(|| unsafe { scoped(context, task) });
+
+		state.complete(result);
+	}
+}
+
+/// Create the job that starts `task` on the thread that runs it
+fn remote_job<T, Output>(task: T) -> (Job, RemoteJoinHandle<Output>)
+where
+	T: for<'ctx> Task<Output<'ctx> = Output> + Send + 'static,
+	Output: Send + 'static
+{
+	let state = Arc::new(RemoteState::new());
+	let task = RemoteTask { task, state: state.clone() };
+
+	let job: Job = Box::new(move |env: &ThreadEnv| {
+		task.state.place(env.thread());
+
+		/* Safety: the thread does not exit until every task completes */
+		let _ = unsafe { spawn(env, task) };
+	});
+
+	(job, RemoteJoinHandle { state })
+}
+
+/// A thread safe handle for joining with a task spawned on a [`Runtime`]
+pub struct RemoteJoinHandle<Output> {
+	state: Arc<RemoteState<Output>>
+}
+
+#[asynchronous]
+impl<Output: Send + 'static> RemoteJoinHandle<Output> {
+	#[must_use]
+	pub fn is_done(&self) -> bool {
+		self.state.lock().result.is_some()
+	}
+
+	/// Signals the task to cancel, without waiting for the result
+	pub fn request_cancel(&self) -> Result<()> {
+		RemoteState::request_cancel(&self.state)
+	}
+
+	/// # Panics
+	/// If the current async runtime doesn't support thread safe blocking
+	pub async fn try_join(self) -> SpawnResult<Output> {
+		/* Safety: we block with `block_on_thread_safe` */
+		block_on_thread_safe(unsafe { RemoteState::join(&self.state) }).await
+	}
+
+	/// Signals the task to cancel, waits for, and returns the result
+	pub async fn cancel(self) -> Output {
+		let result = self.request_cancel();
+
+		if let Err(err) = &result {
+			debug!(target: &self, ">> Cancel failed: {:?}", err);
+		}
+
+		self.await
+	}
+
+	/// Blocks the current thread until the task completes. For use outside of
+	/// async tasks
+	pub fn join_blocking(self) -> SpawnResult<Output> {
+		let mut remote = self.state.lock();
+
+		loop {
+			if let Some(result) = remote.result.take() {
+				return result;
+			}
+
+			remote.waiter = Waiter::Thread(thread::current());
+
+			drop(remote);
+
+			thread::park();
+
+			remote = self.state.lock();
+		}
+	}
+}
+
+#[asynchronous(task)]
+impl<Output: Send + 'static> Task for RemoteJoinHandle<Output> {
+	type Output = Output;
+
+	async fn run(self) -> Output {
+		let result = self.try_join().await;
+
+		runtime::join(result)
+	}
+}
+
+/// How [`Runtime::spawn_anywhere`] picks a thread
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Placement {
+	#[default]
+	RoundRobin,
+
+	/// The thread with the fewest unfinished tasks
+	LeastLoaded
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+	/// The number of executor threads. Defaults to the number of CPUs this
+	/// thread is allowed to run on
+	pub threads: Option<usize>,
+
+	/// Pin each executor thread to its own CPU
+	pub pin: bool,
+
+	pub placement: Placement,
+
+	/// How each thread's fiber [`Pool`] allocates and keeps stacks
+	pub pool: PoolOptions,
+
+	/// The number of stacks each thread allocates when it starts
+	pub prewarm: usize
+}
+
+/// A set of executor threads that tasks can be spawned on from any thread
+///
+/// Tasks are placed on a thread when spawned. Tasks spawned with
+/// [`Runtime::spawn_anywhere`] may be stolen by an idle thread until they
+/// start. Dropping the runtime waits for every task to
+/// complete, then stops the threads
+pub struct Runtime {
+	threads: Arc<[Arc<ThreadShared>]>,
+	handles: Vec<thread::JoinHandle<()>>,
+	placement: Placement,
+	next: AtomicUsize
+}
+
+impl Runtime {
+	pub fn new(options: Options) -> Result<Self> {
+		let count = options
+			.threads
+			.or_else(available_parallelism)
+			.unwrap_or(1)
+			.max(1);
+
+		let cpus: Vec<usize> = if options.pin {
+			sched_getaffinity(None)?.iter().collect()
+		} else {
+			Vec::new()
+		};
+
+		let queues: Vec<WorkQueue<Job>> =
+			(0..count).map(|_| WorkQueue::new(QUEUE_CAPACITY)).collect();
+
+		let threads: Vec<_> = queues
+			.iter()
+			.map(|queue| ThreadShared::new(queue.stealer()).map(Arc::new))
+			.collect::<Result<_>>()?;
+
+		let mut this = Self {
+			threads: threads.into(),
+			handles: Vec::with_capacity(count),
+			placement: options.placement,
+			next: AtomicUsize::new(0)
+		};
+
+		for (index, queue) in queues.into_iter().enumerate() {
+			#[allow(clippy::arithmetic_side_effects)]
+			let cpu = (!cpus.is_empty()).then(|| cpus[index % cpus.len()]);
+			let threads = this.threads.clone();
+			let pool = Pool::with_options(options.pool.clone());
+			let prewarm = options.prewarm;
+
+			let handle = thread::Builder::new()
+				.name(format!("xx-executor-{}", index))
+				.spawn(move || run_thread(&threads, index, &queue, cpu, pool, prewarm))?;
+
+			this.handles.push(handle);
+		}
+
+		debug!(target: &this, "++ Created runtime with {} threads", count);
+
+		Ok(this)
+	}
+
+	#[must_use]
+	pub fn threads(&self) -> usize {
+		self.threads.len()
+	}
+
+	/// The number of unfinished tasks on each thread
+	pub fn loads(&self) -> impl Iterator<Item = usize> + '_ {
+		self.threads
+			.iter()
+			.map(|thread| thread.load.load(Ordering::Relaxed))
+	}
+
+	/// Spawn a task on the executor thread at `index`. The task always runs
+	/// on that thread
+	///
+	/// # Panics
+	/// If `index` is out of range
+	pub fn spawn_on<T, Output>(&self, index: usize, task: T) -> RemoteJoinHandle<Output>
+	where
+		T: for<'ctx> Task<Output<'ctx> = Output> + Send + 'static,
+		Output: Send + 'static
+	{
+		let (job, handle) = remote_job(task);
+
+		self.threads[index].post_task(job);
+
+		handle
+	}
+
+	/// Spawn a task on a thread picked by the runtime's [`Placement`]. Another
+	/// thread may steal the task if it's idle before the task starts
+	#[allow(clippy::arithmetic_side_effects)]
+	pub fn spawn_anywhere<T, Output>(&self, task: T) -> RemoteJoinHandle<Output>
+	where
+		T: for<'ctx> Task<Output<'ctx> = Output> + Send + 'static,
+		Output: Send + 'static
+	{
+		let index = match self.placement {
+			Placement::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % self.threads.len(),
+			Placement::LeastLoaded => self
+				.loads()
+				.enumerate()
+				.min_by_key(|(_, load)| *load)
+				.map_or(0, |(index, _)| index)
+		};
+
+		let thread = &self.threads[index];
+		let (job, handle) = remote_job(task);
+
+		thread.inject(job);
+
+		/* the thread may be busy, so let an idle one take the task */
+		if !thread.idle.load(Ordering::Relaxed) {
+			wake_idle(&self.threads);
+		}
+
+		handle
+	}
+}
+
+impl Drop for Runtime {
+	fn drop(&mut self) {
+		for thread in self.threads.iter() {
+			thread.shutdown.store(true, Ordering::Release);
+			thread.waker.notify();
+		}
+
+		for handle in self.handles.drain(..) {
+			let _ = handle.join();
+		}
+	}
+}
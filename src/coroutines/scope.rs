@@ -0,0 +1,356 @@
+#![allow(clippy::multiple_unsafe_ops_per_block)]
+
+use std::any::Any;
+use std::marker::PhantomData;
+use std::panic::resume_unwind;
+use std::rc::Rc;
+
+use super::*;
+use crate::cell::{Cell, UnsafeCell};
+
+type Panic = Box<dyn Any + Send>;
+
+trait ChildPanic {
+	/// Takes the panic from the child, if it panicked and wasn't joined
+	fn take_panic(&self) -> Option<Panic>;
+}
+
+struct ChildResult<Output> {
+	result: UnsafeCell<Option<MaybePanic<Output>>>,
+
+	/// A handle waiting for the result, after the scope took the child to
+	/// join it
+	waiter: Cell<ReqPtr<()>>
+}
+
+impl<Output> ChildResult<Output> {
+	const fn new() -> Self {
+		Self {
+			result: UnsafeCell::new(None),
+			waiter: Cell::new(Ptr::null())
+		}
+	}
+
+	fn set(&self, result: MaybePanic<Output>) {
+		/* Safety: exclusive unsafe cell access */
+		unsafe { *self.result.as_mut() = Some(result) };
+
+		let waiter = self.waiter.replace(Ptr::null());
+
+		if !waiter.is_null() {
+			/* Safety: the waiter is blocked on `ChildResult::wait` */
+			unsafe { Request::complete(waiter, ()) };
+		}
+	}
+
+	fn take(&self) -> Option<MaybePanic<Output>> {
+		/* Safety: exclusive unsafe cell access */
+		unsafe { self.result.as_mut().take() }
+	}
+
+	fn is_some(&self) -> bool {
+		/* Safety: exclusive unsafe cell access */
+		unsafe { self.result.as_ref().is_some() }
+	}
+
+	/// Waits for the result to be set. The scope has already interrupted the
+	/// child, so cancelling the wait does nothing
+	///
+	/// # Safety
+	/// `self` must be alive until the future completes
+	#[future]
+	unsafe fn wait(&self, request: _) {
+		#[cancel]
+		fn cancel(&self) -> Result<()> {
+			Ok(())
+		}
+
+		self.waiter.set(request);
+
+		Progress::Pending(cancel(self))
+	}
+}
+
+impl<Output> ChildPanic for ChildResult<Output> {
+	fn take_panic(&self) -> Option<Panic> {
+		match self.take()? {
+			Ok(output) => {
+				self.set(Ok(output));
+
+				None
+			}
+
+			Err(panic) => Some(panic)
+		}
+	}
+}
+
+struct Child<'scope> {
+	handle: JoinHandle<()>,
+	result: Rc<dyn ChildPanic + 'scope>
+}
+
+/// A scope for spawning tasks that may borrow from the enclosing stack
+///
+/// See [`fn@scope`]
+pub struct Scope<'scope, 'env: 'scope, E: Environment> {
+	env: &'env E,
+
+	/// Children that haven't been joined yet. Boxed so that they don't move
+	/// when a child is spawned while another is being cancelled
+	children: UnsafeCell<Vec<Option<Box<Child<'scope>>>>>,
+
+	/// Children that panicked, in the order that they did
+	panicked: UnsafeCell<Vec<Rc<dyn ChildPanic + 'scope>>>,
+
+	scope: PhantomData<&'scope mut &'scope ()>,
+	env_scope: PhantomData<&'env mut &'env ()>
+}
+
+struct ScopedTask<'scope, 'env, E: Environment, T, Output> {
+	scope: &'scope Scope<'scope, 'env, E>,
+	task: T,
+	result: Rc<ChildResult<Output>>
+}
+
+#[asynchronous(task)]
+impl<'scope, 'env, E, T, Output> Task for ScopedTask<'scope, 'env, E, T, Output>
+where
+	E: Environment,
+	T: for<'ctx> Task<Output<'ctx> = Output>,
+	Output: 'scope
+{
+	type Output = ();
+
+	async fn run(self) {
+		let Self { scope, task, result } = self;
+		let context = get_context().await;
+
+		/* Safety: we are in an async function */
+		let output = catch_unwind_safe(|| unsafe { scoped(context, task) });
+		let panicked = output.is_err();
+
+		result.set(output);
+
+		if panicked {
+			/* Safety: exclusive unsafe cell access */
+			unsafe { scope.panicked.as_mut().push(result) };
+
+			scope.interrupt_children();
+		}
+	}
+}
+
+impl<'scope, 'env, E: Environment> Scope<'scope, 'env, E> {
+	const fn new(env: &'env E) -> Self {
+		Self {
+			env,
+			children: UnsafeCell::new(Vec::new()),
+			panicked: UnsafeCell::new(Vec::new()),
+			scope: PhantomData,
+			env_scope: PhantomData
+		}
+	}
+
+	/// Signals every child to cancel, without waiting for them
+	fn interrupt_children(&self) {
+		let mut index = 0;
+
+		/* cancelling a child may run it, which may spawn more children */
+		loop {
+			/* Safety: exclusive unsafe cell access */
+			let Some(child) = (unsafe { self.children.as_ref().get(index) }) else {
+				break;
+			};
+
+			if let Some(child) = child {
+				let result = child.handle.request_cancel();
+
+				if let Err(err) = &result {
+					debug!(target: self, ">> Cancel failed: {:?}", err);
+				}
+			}
+
+			index = index.wrapping_add(1);
+		}
+	}
+
+	/// Takes any child that hasn't been joined
+	fn take_child(&self) -> Option<Box<Child<'scope>>> {
+		/* Safety: exclusive unsafe cell access */
+		unsafe { self.children.as_mut() }
+			.iter_mut()
+			.find_map(Option::take)
+	}
+
+	/// Returns the first panic of the children in `panicked[start..end]`
+	fn take_panic(&self, start: usize, end: usize) -> Option<Panic> {
+		(start..end).find_map(|index| {
+			/* Safety: exclusive unsafe cell access */
+			let result = unsafe { self.panicked.as_ref()[index].clone() };
+
+			result.take_panic()
+		})
+	}
+
+	/// Interrupts and joins every child
+	async fn join_children(&self) {
+		self.interrupt_children();
+
+		while let Some(child) = self.take_child() {
+			/* children spawned while shutting down are interrupted here */
+			let _ = child.handle.request_cancel();
+
+			/* the task itself never panics */
+			let _ = child.handle.try_join().await;
+		}
+	}
+
+	/// Spawn a new async task, which may borrow anything that outlives the
+	/// scope
+	///
+	/// If the task panics, every other child is interrupted, and the panic is
+	/// propagated by the scope unless the task is joined
+	pub fn spawn<T, Output>(&'scope self, task: T) -> ScopedJoinHandle<'scope, Output>
+	where
+		T: for<'ctx> Task<Output<'ctx> = Output> + 'scope,
+		Output: 'scope
+	{
+		let result = Rc::new(ChildResult::new());
+		let task = ScopedTask { scope: self, task, result: result.clone() };
+
+		/* Safety: the scope joins every child before it returns, and `env`
+		 * outlives the scope
+		 */
+		let handle = unsafe { spawn(self.env, task) };
+
+		/* Safety: exclusive unsafe cell access. the child may have spawned
+		 * children of its own
+		 */
+		let children = unsafe { self.children.as_mut() };
+		let index = children.len();
+
+		children.push(Some(Box::new(Child { handle, result: result.clone() })));
+
+		ScopedJoinHandle { children: &self.children, index, result }
+	}
+}
+
+/// A handle for joining with a task spawned by [`Scope::spawn`]
+pub struct ScopedJoinHandle<'scope, Output> {
+	children: &'scope UnsafeCell<Vec<Option<Box<Child<'scope>>>>>,
+	index: usize,
+	result: Rc<ChildResult<Output>>
+}
+
+#[asynchronous]
+impl<Output> ScopedJoinHandle<'_, Output> {
+	#[must_use]
+	pub fn is_done(&self) -> bool {
+		self.result.is_some()
+	}
+
+	/// Signals the task to cancel, without waiting for the result
+	pub fn request_cancel(&self) -> Result<()> {
+		/* Safety: exclusive unsafe cell access */
+		match unsafe { self.children.as_ref() }[self.index].as_ref() {
+			Some(child) => child.handle.request_cancel(),
+			None => Ok(())
+		}
+	}
+
+	pub async fn try_join(self) -> SpawnResult<Output> {
+		/* Safety: exclusive unsafe cell access */
+		let child = unsafe { self.children.as_mut() }[self.index].take();
+
+		if let Some(child) = child {
+			/* the task itself never panics */
+			let _ = child.handle.try_join().await;
+		} else if !self.result.is_some() {
+			/* the scope is joining the child, wait for it to finish instead */
+			/* Safety: we hold a reference to the result */
+			block_on(unsafe { self.result.wait() }).await;
+		}
+
+		#[allow(clippy::expect_used)]
+		self.result.take().expect("Scoped task has no result")
+	}
+
+	/// Signals the task to cancel, waits for, and returns the result
+	pub async fn cancel(self) -> Output {
+		let result = self.request_cancel();
+
+		if let Err(err) = &result {
+			debug!(target: &self, ">> Cancel failed: {:?}", err);
+		}
+
+		self.await
+	}
+}
+
+#[asynchronous(task)]
+impl<Output> Task for ScopedJoinHandle<'_, Output> {
+	type Output = Output;
+
+	async fn run(self) -> Output {
+		let result = self.try_join().await;
+
+		runtime::join(result)
+	}
+}
+
+/// Creates a scope for spawning tasks that may borrow from the caller
+///
+/// The scope does not return until every task spawned in it has finished.
+/// Once `body` returns or a task panics, every task that hasn't been joined is
+/// interrupted. The body itself is left to run to completion
+///
+/// If the body or any unjoined task panics, the first panic is resumed on the
+/// caller once every task has finished
+///
+/// ```
+/// let mut results = [0; 2];
+/// let (first, second) = results.split_at_mut(1);
+///
+/// let sum = scope(env, |scope: &Scope<'_, '_, _>| async move {
+/// 	scope.spawn(async move { first[0] = compute().await });
+///
+/// 	let handle = scope.spawn(compute());
+///
+/// 	handle.await + compute().await
+/// })
+/// .await;
+/// ```
+#[asynchronous]
+pub async fn scope<'env, E, F, Output>(env: &'env E, body: F) -> Output
+where
+	E: Environment,
+	F: for<'scope> AsyncFnOnce<&'scope Scope<'scope, 'env, E>, Output = Output>
+{
+	let scope = Scope::new(env);
+	let context = get_context().await;
+
+	/* Safety: we are in an async function */
+	let result = catch_unwind_safe(|| unsafe { scoped(context, body.call_once(&scope)) });
+
+	/* Safety: exclusive unsafe cell access */
+	let panicked = unsafe { scope.panicked.as_ref().len() };
+
+	scope.join_children().await;
+
+	/* Safety: exclusive unsafe cell access */
+	let total = unsafe { scope.panicked.as_ref().len() };
+
+	/* panics that happened while the body was running came first */
+	if let Some(panic) = scope.take_panic(0, panicked) {
+		resume_unwind(panic);
+	}
+
+	let output = runtime::join(result);
+
+	if let Some(panic) = scope.take_panic(panicked, total) {
+		resume_unwind(panic);
+	}
+
+	output
+}
@@ -3,6 +3,7 @@
 use std::result;
 
 use super::*;
+use crate::impls::OptionExt;
 
 /// The result of a call to [`fn@select`] or [`select_future`]
 #[derive(Debug)]
@@ -106,3 +107,46 @@ where
 
 	runtime::join(result.flatten())
 }
+
+/// Races every task in `tasks` and waits for one of them to finish,
+/// cancelling the others
+///
+/// Returns the index of the task that completed first, along with its
+/// result
+///
+/// If one of the tasks panics, the panic is resumed on the caller
+///
+/// # Panics
+/// If `tasks` is empty
+///
+/// # Safety
+/// The cloned `env` and the tasks must outlive their spawned fiber
+#[asynchronous]
+#[allow(clippy::expect_used)]
+pub async unsafe fn select_all<E, I, T, O>(env: &E, tasks: I) -> (usize, O)
+where
+	E: Environment,
+	I: IntoIterator<Item = T>,
+	T: for<'ctx> Task<Output<'ctx> = O>
+{
+	/* Safety: guaranteed by caller */
+	let branch = unsafe {
+		branch_all(
+			tasks.into_iter().map(|task| spawn_task_with_env(env, task)),
+			|_| true
+		)
+	}
+	.await;
+
+	let BranchAllOutput(first, mut results) = branch.flatten();
+	let index = first.expect("`select_all` called with no tasks");
+
+	/* Safety: the first task to complete has a result */
+	let output = runtime::join(unsafe { results[index].take().expect_unchecked("Branch failed") });
+
+	for result in results.into_iter().flatten() {
+		runtime::join(result);
+	}
+
+	(index, output)
+}
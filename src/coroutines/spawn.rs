@@ -99,7 +99,7 @@ impl<E: Environment, T: for<'ctx> Task<Output<'ctx> = Output>, Output> SpawnWork
 	/// # Safety
 	/// The `env` and `task` must outlive the spawned fiber
 	#[future]
-	unsafe fn spawn(env: E, task: T, request: _) -> SpawnResult<Output> {
+	unsafe fn spawn(env: E, task: T, stack_size: Option<usize>, request: _) -> SpawnResult<Output> {
 		#[cancel]
 		fn cancel(context: NonNull<Context>) -> Result<()> {
 			/* Safety: guaranteed by Future's contract */
@@ -113,7 +113,7 @@ impl<E: Environment, T: for<'ctx> Task<Output<'ctx> = Output>, Output> SpawnWork
 		let executor = call_no_unwind(|| env.executor());
 
 		/* Safety: guaranteed by caller */
-		let worker = unsafe { ptr!(executor=>new_worker(start)) };
+		let worker = unsafe { ptr!(executor=>new_worker_with_stack(start, stack_size)) };
 
 		spawn.data = SpawnData::Start(env, task, worker, request);
 
@@ -162,17 +162,41 @@ where
 		Ok(())
 	}
 
+	/* Safety: guaranteed by caller */
+	unsafe { spawn_task_with_stack(env, task, None).run(request) }
+}
+
+/// Same as [`spawn_task`], with a hint for the size of the worker's stack
+///
+/// # Safety
+/// The `env` and `task` must outlive the spawned fiber
+#[future]
+pub unsafe fn spawn_task_with_stack<E, T, Output>(
+	env: E, task: T, stack_size: Option<usize>, request: _
+) -> SpawnResult<Output>
+where
+	E: Environment,
+	T: for<'ctx> Task<Output<'ctx> = Output>
+{
+	#[cancel]
+	fn cancel(context: NonNull<Context>) -> Result<()> {
+		/* use this fn to generate the cancel closure type */
+		Ok(())
+	}
+
 	#[cfg(any(doc, feature = "xx-doc"))]
 	unreachable!();
 
 	#[cfg(not(any(doc, feature = "xx-doc")))]
 	/* Safety: guaranteed by caller */
-	(unsafe { SpawnWorker::spawn(env, task).run(request) })
+	(unsafe { SpawnWorker::spawn(env, task, stack_size).run(request) })
 }
 
 /// Utility function that calls the above with the
 /// executor and env passed to this function
 ///
+/// The spawned task inherits the task local values of `env`
+///
 /// # Safety
 /// The cloned `env` and `task` must outlive the spawned fiber
 #[future]
@@ -187,7 +211,34 @@ where
 	}
 
 	/* Safety: guaranteed by caller */
-	unsafe { spawn_task(env.clone(), task).run(request) }
+	unsafe { spawn_task_with_env_and_stack(env, task, None).run(request) }
+}
+
+/// Same as [`spawn_task_with_env`], with a hint for the size of the worker's
+/// stack
+///
+/// # Safety
+/// The cloned `env` and `task` must outlive the spawned fiber
+#[future]
+pub unsafe fn spawn_task_with_env_and_stack<E, T, Output>(
+	env: &E, task: T, stack_size: Option<usize>, request: _
+) -> SpawnResult<Output>
+where
+	E: Environment,
+	T: for<'ctx> Task<Output<'ctx> = Output>
+{
+	#[cancel]
+	fn cancel(context: NonNull<Context>) -> Result<()> {
+		Ok(())
+	}
+
+	/* Safety: guaranteed by caller */
+	let child = unsafe { env.clone() };
+
+	call_no_unwind(|| child.context().inherit_locals(env.context()));
+
+	/* Safety: guaranteed by caller */
+	unsafe { spawn_task_with_stack(child, task, stack_size).run(request) }
 }
 
 struct SpawnHandle<Output> {
@@ -249,7 +300,7 @@ impl<Output> Spawn<Output> {
 
 	/// # Safety
 	/// The cloned `env` and `task` must outlive the spawned fiber
-	unsafe fn run<E, T>(env: &E, task: T) -> JoinHandle<Output>
+	unsafe fn run<E, T>(env: &E, task: T, stack_size: Option<usize>) -> JoinHandle<Output>
 	where
 		E: Environment,
 		T: for<'ctx> Task<Output<'ctx> = Output>
@@ -261,7 +312,9 @@ impl<Output> Spawn<Output> {
 		let handle = unsafe { this.handle.as_mut() };
 
 		/* Safety: guaranteed by caller */
-		match unsafe { spawn_task_with_env(env, task).run(ptr!(&this.request)) } {
+		match unsafe {
+			spawn_task_with_env_and_stack(env, task, stack_size).run(ptr!(&this.request))
+		} {
 			Progress::Done(result) => handle.output = Some(result),
 			Progress::Pending(cancel) => {
 				handle.cancel = Some(cancel);
@@ -386,5 +439,25 @@ where
 
 	#[cfg(not(any(doc, feature = "xx-doc")))]
 	/* Safety: guaranteed by caller */
-	(unsafe { Spawn::run(env, task) })
+	(unsafe { Spawn::run(env, task, None) })
+}
+
+/// Same as [`fn@spawn`], except the task's stack is at least `stack_size`
+/// bytes. The size is rounded up by the executor's [`Pool`], if it has one
+///
+/// # Safety
+/// The cloned `env` and `task` must outlive the spawned fiber
+pub unsafe fn spawn_with_stack<E, T, Output>(
+	env: &E, task: T, stack_size: usize
+) -> JoinHandle<Output>
+where
+	E: Environment,
+	T: for<'ctx> Task<Output<'ctx> = Output>
+{
+	#[cfg(any(doc, feature = "xx-doc"))]
+	unreachable!();
+
+	#[cfg(not(any(doc, feature = "xx-doc")))]
+	/* Safety: guaranteed by caller */
+	(unsafe { Spawn::run(env, task, Some(stack_size)) })
 }
@@ -0,0 +1,172 @@
+//! Values scoped to an async task
+//!
+//! A task local value is set for the duration of a task with
+//! [`LocalKey::scope`], and is visible to every async function called from
+//! it. Tasks spawned with [`fn@spawn`], [`fn@join`], [`fn@select`] and the
+//! like inherit the values of the task that spawned them, unless wrapped in
+//! [`without_task_locals`]
+//!
+//! ```
+//! task_local! {
+//! 	static REQUEST_ID: u64;
+//! }
+//!
+//! REQUEST_ID
+//! 	.scope(7, async {
+//! 		assert_eq!(REQUEST_ID.get().await, 7);
+//! 	})
+//! 	.await;
+//! ```
+
+use std::marker::PhantomData;
+use std::mem::take;
+use std::rc::Rc;
+
+use super::context::{LocalValue, LocalValues};
+use super::*;
+
+/// Declares one or more task local keys of type [`LocalKey`]
+#[macro_export]
+macro_rules! task_local {
+	($($(#[$attr:meta])* $vis:vis static $name:ident: $type:ty);+ $(;)?) => {
+		$(
+			$(#[$attr])*
+			$vis static $name: $crate::coroutines::task_local::LocalKey<$type> =
+				$crate::coroutines::task_local::LocalKey::new();
+		)+
+	};
+}
+
+pub use task_local;
+
+/// A key for a task local value, declared with [`task_local!`]
+pub struct LocalKey<V: 'static> {
+	/// Keys are identified by their address, which must not be shared with
+	/// other statics
+	id: u8,
+	phantom: PhantomData<fn() -> V>
+}
+
+struct Restore<'ctx> {
+	context: &'ctx Context,
+	key: Ptr<()>,
+	previous: Option<LocalValue>
+}
+
+impl Drop for Restore<'_> {
+	fn drop(&mut self) {
+		self.context.replace_local(self.key, self.previous.take());
+	}
+}
+
+struct RestoreAll<'ctx> {
+	context: &'ctx Context,
+	previous: LocalValues
+}
+
+impl Drop for RestoreAll<'_> {
+	fn drop(&mut self) {
+		self.context.replace_locals(take(&mut self.previous));
+	}
+}
+
+impl<V: 'static> LocalKey<V> {
+	#[doc(hidden)]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { id: 0, phantom: PhantomData }
+	}
+
+	fn key(&'static self) -> Ptr<()> {
+		ptr!(&self.id).cast()
+	}
+}
+
+#[asynchronous]
+impl<V: 'static> LocalKey<V> {
+	/// Sets the value for this key while `task` runs, restoring the previous
+	/// value afterwards
+	pub async fn scope<T, Output>(&'static self, value: V, task: T) -> Output
+	where
+		T: for<'ctx> Task<Output<'ctx> = Output>
+	{
+		let context = get_context().await;
+		let key = self.key();
+
+		let _restore = Restore {
+			context,
+			key,
+			previous: context.replace_local(key, Some(Rc::new(value)))
+		};
+
+		task.await
+	}
+
+	/// Calls `func` with the value for this key, or returns `None` if it is
+	/// not set
+	pub async fn try_with<F, Output>(&'static self, func: F) -> Option<Output>
+	where
+		F: FnOnce(&V) -> Output
+	{
+		let value = get_context().await.local(self.key())?;
+
+		value.downcast_ref().map(func)
+	}
+
+	/// Calls `func` with the value for this key
+	///
+	/// # Panics
+	/// If the value is not set
+	pub async fn with<F, Output>(&'static self, func: F) -> Output
+	where
+		F: FnOnce(&V) -> Output
+	{
+		#[allow(clippy::expect_used)]
+		self.try_with(func).await.expect("Task local value not set")
+	}
+
+	/// Returns a copy of the value for this key
+	///
+	/// # Panics
+	/// If the value is not set
+	pub async fn get(&'static self) -> V
+	where
+		V: Clone
+	{
+		self.with(V::clone).await
+	}
+}
+
+/// A task that runs without any task local values, created by
+/// [`without_task_locals`]
+pub struct WithoutTaskLocals<T> {
+	task: T
+}
+
+#[asynchronous(task)]
+impl<T, Output> Task for WithoutTaskLocals<T>
+where
+	T: for<'ctx> Task<Output<'ctx> = Output>
+{
+	type Output = Output;
+
+	async fn run(self) -> Output {
+		let context = get_context().await;
+
+		/* the task may be awaited inline, so the caller's values are put back
+		 * once it finishes
+		 */
+		let _restore = RestoreAll {
+			context,
+			previous: context.replace_locals(Vec::new())
+		};
+
+		self.task.await
+	}
+}
+
+/// Prevents a spawned task from inheriting the task local values of the
+/// task that spawned it
+pub const fn without_task_locals<T>(task: T) -> WithoutTaskLocals<T> {
+	WithoutTaskLocals { task }
+}
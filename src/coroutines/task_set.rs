@@ -0,0 +1,280 @@
+#![allow(clippy::multiple_unsafe_ops_per_block)]
+
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+
+use super::*;
+use crate::cell::{Cell, UnsafeCell};
+
+struct Shared<Output> {
+	/// Results of tasks that completed and haven't been joined, in the order
+	/// that they completed
+	completed: UnsafeCell<VecDeque<SpawnResult<Output>>>,
+
+	/// Handles of tasks that are still running. A task that completes while
+	/// it's being spawned never gets a handle
+	running: UnsafeCell<HashMap<u64, Option<Rc<JoinHandle<()>>>>>,
+
+	waiter: Cell<ReqPtr<()>>,
+
+	/// The context to interrupt once every task completes, set during a
+	/// shutdown
+	drained: Cell<Ptr<Context>>,
+
+	/// Set if the set interrupted the `drained` context, so that only that
+	/// interrupt is cleared
+	interrupted: Cell<bool>,
+
+	/// Set once the `TaskSet` is dropped, after which results are discarded
+	detached: Cell<bool>
+}
+
+impl<Output> Shared<Output> {
+	fn new() -> Self {
+		Self {
+			completed: UnsafeCell::new(VecDeque::new()),
+			running: UnsafeCell::new(HashMap::new()),
+			waiter: Cell::new(Ptr::null()),
+			drained: Cell::new(Ptr::null()),
+			interrupted: Cell::new(false),
+			detached: Cell::new(false)
+		}
+	}
+
+	#[allow(clippy::mut_from_ref)]
+	fn completed(&self) -> &mut VecDeque<SpawnResult<Output>> {
+		/* Safety: exclusive unsafe cell access */
+		unsafe { self.completed.as_mut() }
+	}
+
+	#[allow(clippy::mut_from_ref)]
+	fn running(&self) -> &mut HashMap<u64, Option<Rc<JoinHandle<()>>>> {
+		/* Safety: exclusive unsafe cell access */
+		unsafe { self.running.as_mut() }
+	}
+
+	fn complete(&self, id: u64, result: SpawnResult<Output>) {
+		self.running().remove(&id);
+
+		if !self.detached.get() {
+			self.completed().push_back(result);
+		}
+
+		let waiter = self.waiter.replace(Ptr::null());
+
+		if !waiter.is_null() {
+			/* Safety: the waiter is blocked on `Shared::wait` */
+			unsafe { Request::complete(waiter, ()) };
+		} else if self.running().is_empty() {
+			let drained = self.drained.replace(Ptr::null());
+
+			/* Safety: the context is blocked on the shutdown deadline. an
+			 * interrupt that it already has is left for the caller
+			 */
+			if !drained.is_null() && !unsafe { drained.as_ref() }.interrupt_pending() {
+				/* Safety: see above */
+				let result = unsafe { Context::interrupt(drained) };
+
+				self.interrupted.set(result.is_ok());
+			}
+		}
+	}
+
+	/// Signals every running task to cancel
+	fn cancel_all(&self) -> Result<()> {
+		/* cancelling a task may complete it, which removes it from `running` */
+		let handles: Vec<_> = self.running().values().flatten().cloned().collect();
+		let mut result = Ok(());
+
+		for handle in handles {
+			let cancel_result = handle.request_cancel();
+
+			if let Err(err) = cancel_result {
+				debug!(target: self, ">> Cancel failed: {:?}", err);
+
+				if result.is_ok() {
+					result = Err(err);
+				}
+			}
+		}
+
+		result
+	}
+
+	/// Waits for a task to complete. Cancelling the wait cancels every task
+	///
+	/// # Safety
+	/// `self` must be alive until the future completes
+	#[future]
+	unsafe fn wait(&self, request: _) {
+		#[cancel]
+		fn cancel(&self) -> Result<()> {
+			self.cancel_all()
+		}
+
+		self.waiter.set(request);
+
+		Progress::Pending(cancel(self))
+	}
+}
+
+struct SetTask<T, Output> {
+	task: T,
+	shared: Rc<Shared<Output>>,
+	id: u64
+}
+
+#[asynchronous(task)]
+impl<T, Output> Task for SetTask<T, Output>
+where
+	T: for<'ctx> Task<Output<'ctx> = Output>
+{
+	type Output = ();
+
+	async fn run(self) {
+		let Self { task, shared, id } = self;
+		let context = get_context().await;
+
+		/* Safety: we are in an async function */
+		let result = catch_unwind_safe(|| unsafe { scoped(context, task) });
+
+		shared.complete(id, result);
+	}
+}
+
+/// A set of spawned tasks, which can be joined in the order that they complete
+///
+/// Completed tasks release their worker immediately, and only their result is
+/// kept until it's joined. Dropping the set detaches every task that's still
+/// running
+pub struct TaskSet<Output> {
+	shared: Rc<Shared<Output>>,
+	next_id: u64
+}
+
+#[asynchronous]
+impl<Output> TaskSet<Output> {
+	#[must_use]
+	pub fn new() -> Self {
+		Self { shared: Rc::new(Shared::new()), next_id: 0 }
+	}
+
+	/// Spawn a new async task into the set
+	///
+	/// # Safety
+	/// The cloned `env` and `task` must outlive the spawned fiber
+	pub unsafe fn spawn<E, T>(&mut self, env: &E, task: T)
+	where
+		E: Environment,
+		T: for<'ctx> Task<Output<'ctx> = Output>
+	{
+		let id = self.next_id;
+
+		self.next_id = self.next_id.wrapping_add(1);
+		self.shared.running().insert(id, None);
+
+		let task = SetTask { task, shared: self.shared.clone(), id };
+
+		/* Safety: guaranteed by caller */
+		let handle = unsafe { spawn(env, task) };
+
+		/* if the task already completed, the handle is no longer needed */
+		if let Some(entry) = self.shared.running().get_mut(&id) {
+			*entry = Some(Rc::new(handle));
+		}
+	}
+
+	/// The number of tasks in the set, including the ones that completed but
+	/// haven't been joined
+	#[must_use]
+	#[allow(clippy::arithmetic_side_effects)]
+	pub fn len(&self) -> usize {
+		self.shared.running().len() + self.shared.completed().len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Signals every running task to cancel, without waiting for them
+	pub fn cancel_all(&self) -> Result<()> {
+		self.shared.cancel_all()
+	}
+
+	/// Waits for the next task to complete and returns its result, or `None`
+	/// if the set is empty
+	///
+	/// If the caller is interrupted, every running task is cancelled, and
+	/// this continues to wait for the next result
+	pub async fn try_join_next(&mut self) -> Option<SpawnResult<Output>> {
+		loop {
+			if let Some(result) = self.shared.completed().pop_front() {
+				return Some(result);
+			}
+
+			if self.shared.running().is_empty() {
+				return None;
+			}
+
+			/* Safety: the shared state outlives the wait */
+			block_on(unsafe { self.shared.wait() }).await;
+		}
+	}
+
+	/// See [`TaskSet::try_join_next`]
+	///
+	/// If the task panicked, the panic is resumed on the caller
+	pub async fn join_next(&mut self) -> Option<Output> {
+		self.try_join_next().await.map(runtime::join)
+	}
+
+	/// Waits for every task to complete until `deadline` finishes, then
+	/// cancels the remaining tasks and waits for them
+	///
+	/// Returns the number of tasks that were cancelled. The results of every
+	/// task are discarded
+	pub async fn shutdown<T, O>(&mut self, deadline: T) -> usize
+	where
+		T: for<'ctx> Task<Output<'ctx> = O>
+	{
+		if !self.shared.running().is_empty() {
+			self.shared.drained.set(ptr!(get_context().await));
+
+			/* interrupted once every task completes */
+			let _ = deadline.await;
+
+			self.shared.drained.set(Ptr::null());
+
+			if self.shared.interrupted.replace(false) {
+				clear_interrupt().await;
+			}
+		}
+
+		let cancelled = self.shared.running().len();
+
+		let _ = self.cancel_all();
+
+		while let Some(result) = self.try_join_next().await {
+			if result.is_err() {
+				warn!(target: &*self, "== Task panicked during shutdown");
+			}
+		}
+
+		cancelled
+	}
+}
+
+impl<Output> Default for TaskSet<Output> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<Output> Drop for TaskSet<Output> {
+	fn drop(&mut self) {
+		self.shared.detached.set(true);
+
+		self.shared.completed().clear();
+	}
+}
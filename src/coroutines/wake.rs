@@ -1,4 +1,11 @@
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
 use super::*;
+use crate::os::eventfd::{CreateFlag, EventFd};
+use crate::os::fd::BorrowedFd;
+use crate::os::poll::PollFlag;
+use crate::sync::atomic::AtomicMutPtr;
 
 #[derive(Clone, Copy)]
 pub struct WakerVTable {
@@ -45,3 +52,159 @@ impl Waker {
 		unsafe { (self.vtable.wake)(self.ptr, request) }
 	}
 }
+
+struct Node {
+	request: ReqPtr<()>,
+	next: MutPtr<Node>
+}
+
+/// A [`Waker`] that queues woken requests in a lock-free list, and signals
+/// an [`EventFd`] once each time the list becomes non-empty
+///
+/// The executor thread waits for [`fd`] to become readable, for example with
+/// epoll or io_uring, then calls [`drain`] to resume the woken tasks. Simple
+/// executors can use [`wait`], which does both
+///
+/// [`fd`]: EventFdWaker::fd
+/// [`drain`]: EventFdWaker::drain
+/// [`wait`]: EventFdWaker::wait
+pub struct EventFdWaker {
+	queue: AtomicMutPtr<Node>,
+	event: EventFd
+}
+
+impl EventFdWaker {
+	pub fn new() -> OsResult<Self> {
+		let event = EventFd::new(CreateFlag::NonBlock | CreateFlag::CloseOnExec)?;
+
+		Ok(Self { queue: AtomicMutPtr::new(MutPtr::null()), event })
+	}
+
+	/// The eventfd, which is readable when there are tasks to resume
+	#[must_use]
+	pub fn fd(&self) -> BorrowedFd<'_> {
+		self.event.fd()
+	}
+
+	/// Wake the executor thread without resuming any task, for example when
+	/// there is new work for it
+	pub fn notify(&self) {
+		let _ = self.event.write(1);
+	}
+
+	/// # Safety
+	/// `self` must not be moved, and must not be dropped until no other thread
+	/// can be using the returned waker
+	#[must_use]
+	pub unsafe fn waker(&self) -> Waker {
+		/* Safety: neither function unwinds, and `wake` is thread safe */
+		static VTABLE: WakerVTable =
+			unsafe { WakerVTable::new(EventFdWaker::prepare, EventFdWaker::wake) };
+
+		Waker::new(ptr!(self).cast(), &VTABLE)
+	}
+
+	/// # Safety
+	/// See [`WakerVTable::new`]
+	const unsafe fn prepare(_: Ptr<()>) {}
+
+	/// # Safety
+	/// See [`WakerVTable::new`]
+	unsafe fn wake(this: Ptr<()>, request: ReqPtr<()>) {
+		/* Safety: guaranteed by `EventFdWaker::waker` */
+		let this = unsafe { this.cast::<Self>().as_ref() };
+		let node = MutNonNull::from_box(Box::new(Node { request, next: MutPtr::null() }));
+		let mut head = this.queue.load(Ordering::Relaxed);
+
+		loop {
+			/* Safety: the node is not shared until the exchange succeeds */
+			unsafe { ptr!(node=>next) = head };
+
+			match this.queue.compare_exchange_weak(
+				head,
+				node.into(),
+				Ordering::Release,
+				Ordering::Relaxed
+			) {
+				Ok(_) => break,
+				Err(current) => head = current
+			}
+		}
+
+		/* the executor takes the whole list at once, so only the first wake of
+		 * each batch needs to signal
+		 */
+		if head.is_null() {
+			let _ = this.event.write(1);
+		}
+	}
+
+	/// Resume the tasks woken since the last call, in the order they were
+	/// woken. Returns the number of tasks resumed
+	///
+	/// # Safety
+	/// Must be called on the executor's thread, outside of any worker
+	#[allow(clippy::multiple_unsafe_ops_per_block)]
+	pub unsafe fn drain(&self) -> usize {
+		/* clear the signal before taking the list. a wake that races with this
+		 * either has its node taken here, or signals again
+		 */
+		let _ = self.event.read();
+
+		let mut head = self.queue.swap(MutPtr::null(), Ordering::Acquire);
+		let mut list = MutPtr::null();
+
+		/* the list is newest first */
+		while !head.is_null() {
+			/* Safety: we own the nodes */
+			unsafe {
+				let next = ptr!(head=>next);
+
+				ptr!(head=>next) = list;
+				list = head;
+				head = next;
+			}
+		}
+
+		let mut count = 0usize;
+
+		while !list.is_null() {
+			/* Safety: we own the nodes, which were allocated by `wake` */
+			let node = unsafe { MutNonNull::new_unchecked(list).into_box() };
+
+			list = node.next;
+			count = count.wrapping_add(1);
+
+			/* Safety: guaranteed by caller */
+			unsafe { Request::complete(node.request, ()) };
+		}
+
+		count
+	}
+
+	/// Wait up to `timeout` for a task to be woken, then resume the woken
+	/// tasks. Returns the number of tasks resumed
+	///
+	/// # Safety
+	/// See [`EventFdWaker::drain`]
+	pub unsafe fn wait(&self, timeout: Duration) -> OsResult<usize> {
+		self.event.poll(PollFlag::In.into(), timeout)?;
+
+		/* Safety: guaranteed by caller */
+		Ok(unsafe { self.drain() })
+	}
+}
+
+impl Drop for EventFdWaker {
+	fn drop(&mut self) {
+		let mut head = *self.queue.get_mut();
+
+		/* the requests were never resumed, but the nodes are still ours */
+		while !head.is_null() {
+			/* Safety: we own the nodes, which were allocated by `wake` */
+			let node = unsafe { MutNonNull::new_unchecked(head).into_box() };
+
+			head = node.next;
+		}
+	}
+}
@@ -77,6 +77,13 @@ impl Worker {
 		unsafe { ptr!(self.executor=>suspend(ptr!(self))) };
 	}
 
+	/// # Safety
+	/// see `Executor::yield_worker`
+	pub(super) unsafe fn yield_now(&self) {
+		/* Safety: guaranteed by caller */
+		unsafe { ptr!(self.executor=>yield_worker(ptr!(self))) };
+	}
+
 	/// # Safety
 	/// see `Executor::exit`
 	pub(super) unsafe fn exit(self) {
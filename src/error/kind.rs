@@ -211,6 +211,7 @@ impl From<OsError> for ErrorKind {
 	}
 }
 
+#[cfg(feature = "std")]
 impl From<io::ErrorKind> for ErrorKind {
 	fn from(value: io::ErrorKind) -> Self {
 		use io::ErrorKind::*;
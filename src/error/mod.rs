@@ -1,9 +1,13 @@
-use std::backtrace::Backtrace;
-use std::ffi::{FromBytesWithNulError, FromVecWithNulError, NulError};
-use std::fmt::{self, Debug, Display, Formatter};
-use std::str::Utf8Error;
-use std::string::FromUtf8Error;
-use std::{error, io, result};
+use alloc::boxed::Box;
+use alloc::ffi::{FromVecWithNulError, NulError};
+use alloc::fmt::format;
+use alloc::string::FromUtf8Error;
+use core::ffi::FromBytesWithNulError;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::str::Utf8Error;
+use core::{error, result};
+#[cfg(feature = "std")]
+use std::io;
 
 use crate::macros::sealed_trait;
 #[cfg(feature = "os")]
@@ -23,6 +27,21 @@ pub use repr::SimpleMessage;
 
 pub use crate::macros::errors;
 
+#[cfg(feature = "std")]
+pub use std::backtrace::Backtrace;
+
+/// Backtraces are never captured without the `std` feature
+#[cfg(not(feature = "std"))]
+#[derive(Debug)]
+pub enum Backtrace {}
+
+#[cfg(not(feature = "std"))]
+impl Display for Backtrace {
+	fn fmt(&self, _: &mut Formatter<'_>) -> fmt::Result {
+		match *self {}
+	}
+}
+
 pub type Result<T> = result::Result<T, Error>;
 pub type OsResult<T> = result::Result<T, OsError>;
 
@@ -228,7 +247,7 @@ impl From<fmt::Arguments<'_>> for Error {
 	fn from(value: fmt::Arguments<'_>) -> Self {
 		match value.as_str() {
 			Some(str) => Self::message(str),
-			None => Self::message(fmt::format(value))
+			None => Self::message(format(value))
 		}
 	}
 }
@@ -293,6 +312,7 @@ impl PartialEq<ErrorKind> for Error {
 	}
 }
 
+#[cfg(feature = "std")]
 impl ErrorImpl for io::Error {
 	fn into_error(self) -> Error
 	where
@@ -326,6 +346,7 @@ impl ErrorImpl for io::Error {
 	}
 }
 
+#[cfg(feature = "std")]
 impl From<Error> for io::Error {
 	fn from(value: Error) -> Self {
 		if let Some(os) = value.os_error() {
@@ -362,7 +383,7 @@ macro_rules! fmt_error {
 	};
 
 	($str:literal @ $kind:expr) => {
-		<$crate::error::Error as ::std::convert::From<
+		<$crate::error::Error as ::core::convert::From<
 			&'static $crate::error::SimpleMessage
 		>>::from(
 			&$crate::error::SimpleMessage {
@@ -373,10 +394,10 @@ macro_rules! fmt_error {
 	};
 
 	($($arg:tt)*) => {
-		<$crate::error::Error as ::std::convert::From<
-			::std::fmt::Arguments<'_>
+		<$crate::error::Error as ::core::convert::From<
+			::core::fmt::Arguments<'_>
 		>>::from(
-			::std::format_args!($($arg)*)
+			::core::format_args!($($arg)*)
 		)
 	}
 }
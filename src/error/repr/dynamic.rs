@@ -128,7 +128,10 @@ impl DynError<()> {
 	}
 }
 
+#[cfg(feature = "std")]
 pub fn capture_backtrace() -> Option<Backtrace> {
+	use std::backtrace::BacktraceStatus;
+
 	let mut backtrace = None;
 
 	if backtrace.insert(Backtrace::capture()).status() != BacktraceStatus::Captured {
@@ -138,6 +141,11 @@ pub fn capture_backtrace() -> Option<Backtrace> {
 	backtrace
 }
 
+#[cfg(not(feature = "std"))]
+pub const fn capture_backtrace() -> Option<Backtrace> {
+	None
+}
+
 impl crate::error::Error {
 	/// # Safety
 	/// See [`DynError::downcast_ptr`]
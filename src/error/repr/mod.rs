@@ -1,11 +1,11 @@
 #![allow(unreachable_pub, clippy::multiple_unsafe_ops_per_block)]
 
-use std::any::TypeId;
-use std::backtrace::{Backtrace, BacktraceStatus};
-use std::error::Error;
-use std::fmt::{self, Debug, Display, Formatter};
-use std::marker::PhantomData;
-use std::mem::{forget, transmute, ManuallyDrop, MaybeUninit};
+use alloc::boxed::Box;
+use core::any::TypeId;
+use core::error::Error;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::marker::PhantomData;
+use core::mem::{forget, transmute, ManuallyDrop, MaybeUninit};
 
 use crate::macros::const_assert;
 use crate::pointer::*;
@@ -19,7 +19,7 @@ use self::custom::*;
 use self::dynamic::*;
 use super::internal::*;
 use super::private::*;
-use super::{BoxedError, Context, ErrorKind, OsError};
+use super::{Backtrace, BoxedError, Context, ErrorKind, OsError};
 
 pub trait CompactErrorKind: Send + Sync + 'static {
 	fn kind<E>(&self, error: &E) -> ErrorKind
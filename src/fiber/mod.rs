@@ -1,8 +1,9 @@
 #![allow(unreachable_pub, clippy::multiple_unsafe_ops_per_block)]
 
-use std::arch::global_asm;
-use std::mem::{zeroed, ManuallyDrop};
+use core::arch::global_asm;
+use core::mem::{size_of, zeroed, ManuallyDrop};
 
+use crate::error::OsResult;
 use crate::macros::{assert_unsafe_precondition, import_sysdeps};
 use crate::opt::hint::unreachable_unchecked;
 use crate::os::mman::*;
@@ -11,6 +12,7 @@ use crate::os::unistd::*;
 use crate::os::RawBuf;
 use crate::pointer::*;
 
+pub mod overflow;
 pub mod pool;
 
 #[doc(inline)]
@@ -114,70 +116,185 @@ unsafe extern "C" fn exit_fiber_to_pool(arg: Ptr<()>) {
 	/* Safety: ownership of the fiber is passed to us */
 	let mut fiber = unsafe { ManuallyDrop::take(&mut arg.0) };
 
-	/* Safety: guaranteed by caller */
-	unsafe {
-		fiber.clear_stack();
+	/* Safety: guaranteed by caller. the pool clears the stack */
+	unsafe { ptr!(arg.1=>exit_fiber(fiber)) };
+}
+
+/// How the stack of a fiber is allocated
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StackOptions {
+	/// The size of the stack, including its guard page. Rounded up to a whole
+	/// number of pages. Defaults to the stack size limit of the process
+	pub size: Option<usize>,
+
+	/// Don't reserve swap space for the stack. See [`Flag::NoReserve`]
+	pub no_reserve: bool,
+
+	/// Ask for the stack to be backed by transparent huge pages
+	pub huge_pages: bool,
+
+	/// Fill the stack with a canary pattern, so that
+	/// [`Fiber::stack_high_water_mark`] can measure how much of it was used.
+	/// This touches every page of the stack, and stacks are refilled instead of
+	/// freed by [`Fiber::clear_stack`], so a stack kept for reuse stays
+	/// resident in full
+	pub canary: bool
+}
+
+impl StackOptions {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			size: None,
+			no_reserve: false,
+			huge_pages: false,
+			canary: false
+		}
+	}
+}
+
+const CANARY: u8 = 0xa5;
+const CANARY_WORD: u64 = u64::from_ne_bytes([CANARY; 8]);
+
+#[allow(clippy::expect_used)]
+fn page_size() -> usize {
+	get_system_configuration(SystemConfiguration::Pagesize)
+		.expect("Failed to get page size")
+		.unwrap_or(0)
+		.try_into()
+		.expect("Valid page size")
+}
+
+/// The stack size used when none is specified, which is the stack size limit
+/// of the process
+///
+/// # Panics
+/// If the limit can't be read
+#[allow(clippy::expect_used)]
+#[must_use]
+pub fn default_stack_size() -> usize {
+	get_limit(Resource::Stack)
+		.expect("Failed to get stack size")
+		.try_into()
+		.expect("Valid stack size")
+}
+
+/// The size of the stack that is allocated when `size` bytes are requested,
+/// which leaves at least one usable page above the guard page
+///
+/// # Panics
+/// If the size is too big
+#[allow(clippy::expect_used)]
+#[must_use]
+pub fn round_stack_size(size: usize) -> usize {
+	let page_size = page_size();
+
+	assert!(page_size > 0);
+
+	size.max(page_size.saturating_mul(2))
+		.checked_next_multiple_of(page_size)
+		.expect("Stack size too big")
+}
+
+/// Map a stack with a guard page at the bottom, as described by `options`
+fn map_stack(options: &StackOptions) -> OsResult<Map<'static>> {
+	let stack_size = round_stack_size(options.size.unwrap_or_else(default_stack_size));
+	let page_size = page_size();
+	let mut flags = Flag::Anonymous | Flag::Stack;
+
+	if options.no_reserve {
+		flags |= Flag::NoReserve;
+	}
 
-		ptr!(arg.1=>exit_fiber(fiber));
+	let stack = Builder::new(Type::Private, stack_size)
+		.protect(Protection::Read | Protection::Write)
+		.flag(flags)
+		.map()?;
+
+	if options.huge_pages {
+		/* Safety: the stack isn't in use. this is only a hint */
+		let _ = unsafe { stack.advise(Advice::HugePage) };
+	}
+
+	/* Safety: map the bottom `page_size` bytes as a guard page */
+	unsafe {
+		mprotect(
+			RawBuf::from_parts(stack.as_ptr().cast_const(), page_size),
+			Default::default()
+		)?;
 	}
+
+	Ok(stack)
 }
 
 #[cfg_attr(not(any(doc, feature = "xx-doc")), repr(C))]
 pub struct Fiber {
 	context: Context,
-	stack: Map<'static>
+
+	/// The record of the stack's guard page, if overflow detection is enabled.
+	/// Declared before the stack, so that it's removed before the stack is
+	/// unmapped
+	guard: Option<overflow::Guard>,
+	stack: Map<'static>,
+
+	/// Where the canary starts, if the stack is filled with one
+	canary: Option<usize>
 }
 
 impl Fiber {
 	#[must_use]
 	pub fn main() -> Self {
-		Self { context: Context::default(), stack: Map::new() }
+		Self {
+			context: Context::default(),
+			stack: Map::new(),
+			guard: None,
+			canary: None
+		}
 	}
 
-	#[allow(clippy::new_without_default, clippy::expect_used)]
+	#[allow(clippy::new_without_default)]
 	#[must_use]
 	/// # Panics
 	/// If the stack allocation fails
 	pub fn new() -> Self {
-		let stack_size = get_limit(Resource::Stack)
-			.expect("Failed to get stack size")
-			.try_into()
-			.expect("Valid stack size");
-		let page_size = get_system_configuration(SystemConfiguration::Pagesize)
-			.expect("Failed to get page size")
-			.unwrap_or(0)
-			.try_into()
-			.expect("Valid page size");
-
-		assert!(page_size > 0 && stack_size > 0 && stack_size > page_size);
-
-		let stack = Builder::new(Type::Private, stack_size)
-			.protect(Protection::Read | Protection::Write)
-			.flag(Flag::Anonymous | Flag::Stack)
-			.map()
-			.expect("Failed to allocate stack for fiber");
-
-		/* Safety: map the bottom `page_size` bytes as a guard page */
-		unsafe {
-			mprotect(
-				RawBuf::from_parts(stack.as_ptr().cast_const(), page_size),
-				Default::default()
-			)
-			.expect("Failed to set permissions for guard page");
-		}
+		Self::with_stack(&StackOptions::new())
+	}
 
-		Self {
+	/// # Panics
+	/// If the stack allocation fails
+	#[allow(clippy::expect_used)]
+	#[must_use]
+	pub fn with_stack(options: &StackOptions) -> Self {
+		let stack = map_stack(options).expect("Failed to allocate stack for fiber");
+		let guard = overflow::register(stack.as_ptr().addr(), stack.len());
+		let canary = options.canary.then(page_size);
+
+		let this = Self {
 			/* fiber context. stores the current stack and instruction pointer registers,
 			 * and any that cannot be corrupted by inline asm
 			 */
 			context: Context::default(),
-			stack
+			stack,
+			guard,
+			canary
+		};
+
+		if let Some(start) = this.canary {
+			/* Safety: the stack isn't in use */
+			unsafe { this.fill_canary(start) };
 		}
+
+		this
 	}
 
 	#[must_use]
 	pub fn new_with_start(start: Start) -> Self {
-		let mut this = Self::new();
+		Self::with_stack_and_start(&StackOptions::new(), start)
+	}
+
+	#[must_use]
+	pub fn with_stack_and_start(options: &StackOptions, start: Start) -> Self {
+		let mut this = Self::with_stack(options);
 
 		/* Safety: the fiber was never started */
 		unsafe { this.set_start(start) };
@@ -185,6 +302,61 @@ impl Fiber {
 		this
 	}
 
+	/// The size of the fiber's stack, including its guard page
+	#[must_use]
+	pub const fn stack_size(&self) -> usize {
+		self.stack.len()
+	}
+
+	/// Fill the top of the stack, starting at `start` bytes from the bottom,
+	/// with the canary
+	///
+	/// # Safety
+	/// that part of the stack must not be in use
+	unsafe fn fill_canary(&self, start: usize) {
+		let len = self.stack.len().saturating_sub(start);
+
+		/* Safety: guaranteed by caller. in bounds of the stack */
+		unsafe {
+			self.stack
+				.as_ptr()
+				.cast::<u8>()
+				.add(self.stack.len().wrapping_sub(len))
+				.write_bytes(CANARY, len);
+		}
+	}
+
+	/// The most stack the fiber used, in bytes, since it was created or since
+	/// its stack was last cleared. Only stacks filled with a canary can be
+	/// measured, see [`StackOptions::canary`]
+	///
+	/// The usage can be underestimated if the deepest part of the stack was
+	/// written with the canary pattern
+	#[must_use]
+	#[allow(clippy::arithmetic_side_effects)]
+	pub fn stack_high_water_mark(&self) -> Option<usize> {
+		let start = self.canary?;
+		let bottom = self.stack.as_ptr().addr() + start;
+		let words = (self.stack.len() - start) / size_of::<u64>();
+		let mut unused = 0;
+
+		while unused < words {
+			let word = Ptr::<u64>::from_addr(bottom + unused * size_of::<u64>());
+
+			/* Safety: in bounds of the stack, which is aligned to a page. the
+			 * canary is only compared, so a racing write from the fiber
+			 * itself is harmless
+			 */
+			if unsafe { word.as_ptr().read_volatile() } != CANARY_WORD {
+				break;
+			}
+
+			unused += 1;
+		}
+
+		Some((words - unused) * size_of::<u64>())
+	}
+
 	/// Set the entry point of the fiber
 	///
 	/// # Safety
@@ -222,11 +394,28 @@ impl Fiber {
 		unsafe { switch(ptr!(&mut this=>context), ptr!(&mut to=>context)) };
 	}
 
+	/// Release the memory of the stack. A stack filled with a canary is
+	/// refilled instead, so that the next worker is measured as well, and its
+	/// memory is never released
+	///
+	/// Returns whether the memory was released
+	///
 	/// # Safety
 	/// fiber must not be running
-	pub unsafe fn clear_stack(&mut self) {
+	pub unsafe fn clear_stack(&mut self) -> bool {
+		if let Some(used) = self.stack_high_water_mark() {
+			/* freed pages would lose the canary, so the next worker couldn't
+			 * be measured
+			 *
+			 * Safety: fiber isn't running
+			 */
+			unsafe { self.fill_canary(self.stack.len().wrapping_sub(used)) };
+
+			return false;
+		}
+
 		/* Safety: fiber isn't running */
-		let _ = unsafe { self.stack.advise(Advice::Free) };
+		unsafe { self.stack.advise(Advice::Free) }.is_ok()
 	}
 
 	/// Same as switch, except drops the `self` fiber
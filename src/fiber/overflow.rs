@@ -0,0 +1,344 @@
+//! Stack overflow detection for fibers
+//!
+//! Overflowing the stack of a fiber hits its guard page, which raises
+//! `SIGSEGV`. Once [`install`] is called, the guard pages of fibers created
+//! afterwards are recorded, and a fault in one of them reports which worker
+//! overflowed, its stack size and a backtrace, then aborts. The handler is
+//! registered with the [`signal_handler`] registry, which passes other faults
+//! on to the action that was installed before
+//!
+//! [`signal_handler`]: crate::os::signal_handler
+//!
+//! The handler runs on an alternate signal stack, as the overflowed stack has
+//! no room left. [`install`] sets one up for the calling thread, and every
+//! other thread that runs fibers must call [`install_thread`]
+//!
+//! ```
+//! overflow::install()?;
+//!
+//! spawn(move || {
+//! 	overflow::install_thread()?;
+//!
+//! 	/* run fibers */
+//! });
+//! ```
+
+use alloc::boxed::Box;
+use core::ptr::null_mut;
+use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+#[cfg(feature = "log")]
+use std::cell::RefCell;
+#[cfg(feature = "log")]
+use std::sync::{Mutex, PoisonError};
+
+use super::*;
+#[cfg(feature = "log")]
+use crate::log::{print_fatal, print_forced_backtrace};
+#[cfg(feature = "log")]
+use crate::os::signal::*;
+#[cfg(feature = "log")]
+use crate::os::signal_handler::register;
+
+const SLOTS_PER_CHUNK: usize = 256;
+
+/// The size of the alternate signal stack. Capturing a backtrace takes a lot
+/// more than the minimum signal stack size
+#[cfg(feature = "log")]
+const SIGNAL_STACK_SIZE: usize = 256 * 1024;
+
+/// A recorded stack
+struct Slot {
+	/// The start of the stack, where its guard page is, or zero if the slot
+	/// is free
+	stack: AtomicUsize,
+	size: AtomicUsize
+}
+
+impl Slot {
+	const fn new() -> Self {
+		Self {
+			stack: AtomicUsize::new(0),
+			size: AtomicUsize::new(0)
+		}
+	}
+}
+
+struct Chunk {
+	slots: [Slot; SLOTS_PER_CHUNK],
+
+	/// The number of claimed slots, so that full chunks can be skipped
+	used: AtomicUsize,
+	next: *mut Chunk
+}
+
+/* Safety: `next` is only written before the chunk is shared */
+unsafe impl Send for Chunk {}
+
+/* Safety: see above */
+unsafe impl Sync for Chunk {}
+
+impl Chunk {
+	const fn new() -> Self {
+		Self {
+			slots: [const { Slot::new() }; SLOTS_PER_CHUNK],
+			used: AtomicUsize::new(0),
+			next: null_mut()
+		}
+	}
+
+	fn claim(&'static self, stack: usize, size: usize) -> Option<Guard> {
+		if self.used.load(Ordering::Relaxed) >= SLOTS_PER_CHUNK {
+			return None;
+		}
+
+		for (index, slot) in self.slots.iter().enumerate() {
+			if slot
+				.stack
+				.compare_exchange(0, stack, Ordering::Acquire, Ordering::Relaxed)
+				.is_err()
+			{
+				continue;
+			}
+
+			slot.size.store(size, Ordering::Release);
+			self.used.fetch_add(1, Ordering::Relaxed);
+
+			return Some(Guard { chunk: self, index });
+		}
+
+		None
+	}
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Chunks are never freed, so that the handler never sees one freed while it
+/// runs
+static CHUNKS: AtomicPtr<Chunk> = AtomicPtr::new(null_mut());
+
+/// The size of a guard page, set before the handler is installed
+#[cfg(feature = "log")]
+static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// Records the guard page of a fiber's stack until dropped
+pub(super) struct Guard {
+	chunk: &'static Chunk,
+	index: usize
+}
+
+impl Guard {
+	fn slot(&self) -> &Slot {
+		/* Safety: the index was claimed in this chunk */
+		unsafe { self.chunk.slots.get_unchecked(self.index) }
+	}
+}
+
+impl Drop for Guard {
+	fn drop(&mut self) {
+		let slot = self.slot();
+
+		slot.size.store(0, Ordering::Relaxed);
+		slot.stack.store(0, Ordering::Release);
+
+		self.chunk.used.fetch_sub(1, Ordering::Relaxed);
+	}
+}
+
+/// Record a stack of `size` bytes at `stack`, if detection is enabled
+pub(super) fn register(stack: usize, size: usize) -> Option<Guard> {
+	if !ENABLED.load(Ordering::Relaxed) {
+		return None;
+	}
+
+	let mut head = CHUNKS.load(Ordering::Acquire);
+	let mut chunk = head;
+
+	while !chunk.is_null() {
+		/* Safety: chunks are never freed */
+		let current = unsafe { &*chunk };
+
+		if let Some(guard) = current.claim(stack, size) {
+			return Some(guard);
+		}
+
+		chunk = current.next;
+	}
+
+	let chunk = Box::into_raw(Box::new(Chunk::new()));
+
+	loop {
+		/* Safety: the chunk is not shared until the exchange succeeds */
+		unsafe { (*chunk).next = head };
+
+		match CHUNKS.compare_exchange_weak(head, chunk, Ordering::AcqRel, Ordering::Acquire) {
+			Ok(_) => break,
+			Err(current) => head = current
+		}
+	}
+
+	/* Safety: chunks are never freed */
+	unsafe { &*chunk }.claim(stack, size)
+}
+
+/// Find the recorded stack whose guard page contains `addr`
+#[cfg(feature = "log")]
+fn find(addr: usize) -> Option<(usize, usize)> {
+	let page_size = PAGE_SIZE.load(Ordering::Relaxed);
+	let mut chunk = CHUNKS.load(Ordering::Acquire);
+
+	while !chunk.is_null() {
+		/* Safety: chunks are never freed */
+		let current = unsafe { &*chunk };
+
+		for slot in &current.slots {
+			let stack = slot.stack.load(Ordering::Acquire);
+
+			if stack != 0 && addr.wrapping_sub(stack) < page_size {
+				return Some((stack, slot.size.load(Ordering::Acquire)));
+			}
+		}
+
+		chunk = current.next;
+	}
+
+	None
+}
+
+/// Report the overflow and abort. This isn't async-signal-safe, but the
+/// process is about to abort anyway
+#[cfg(feature = "log")]
+#[cold]
+#[inline(never)]
+fn report(stack: usize, size: usize, addr: usize) -> ! {
+	let thread = std::thread::current();
+
+	print_fatal(format_args!(
+		"Worker on thread '{}' ({:?}) overflowed its stack of {} bytes at {:#x}..{:#x} (fault at \
+		 {:#x})",
+		thread.name().unwrap_or("<unnamed>"),
+		thread.id(),
+		size,
+		stack,
+		stack.wrapping_add(size),
+		addr
+	));
+
+	print_forced_backtrace();
+	print_fatal(format_args!("Stack overflow, aborting"));
+
+	std::process::abort();
+}
+
+/// Called for every `SIGSEGV`. Faults outside of a guard page are passed on
+/// to the previous action by the registry
+#[cfg(feature = "log")]
+fn check(info: &SigInfo) {
+	/* Safety: the kernel passes a siginfo that describes a fault */
+	let addr = unsafe { info.fields.fault.addr }.addr();
+
+	if let Some((stack, size)) = find(addr) {
+		report(stack, size, addr);
+	}
+}
+
+#[cfg(feature = "log")]
+fn install_handler() -> OsResult<()> {
+	static INSTALLED: Mutex<bool> = Mutex::new(false);
+
+	let mut installed = INSTALLED.lock().unwrap_or_else(PoisonError::into_inner);
+
+	if *installed {
+		return Ok(());
+	}
+
+	PAGE_SIZE.store(page_size(), Ordering::Relaxed);
+
+	/* Safety: `check` only reads atomics, unless it's about to abort */
+	unsafe { register(Signal::SegmentationViolation as i32, check) }?.forget();
+
+	*installed = true;
+
+	Ok(())
+}
+
+/// The alternate signal stack of a thread, disabled and freed when the
+/// thread exits
+#[cfg(feature = "log")]
+struct AltStack {
+	_stack: Map<'static>
+}
+
+#[cfg(feature = "log")]
+impl Drop for AltStack {
+	fn drop(&mut self) {
+		let disable = SignalStack {
+			flags: SignalStackFlag::Disable as u32,
+			..Default::default()
+		};
+
+		/* Safety: disabling the stack before it's freed */
+		let _ = unsafe { signal_stack(Some(&disable), None) };
+	}
+}
+
+#[cfg(feature = "log")]
+thread_local! {
+	static ALT_STACK: RefCell<Option<AltStack>> = const { RefCell::new(None) };
+}
+
+/// Set up an alternate signal stack for the current thread, so that stack
+/// overflows of the fibers that run on it can be reported. Does nothing if
+/// it's already set up
+///
+/// # Errors
+/// If allocating or setting the stack fails
+#[cfg(feature = "log")]
+pub fn install_thread() -> OsResult<()> {
+	ALT_STACK.with_borrow_mut(|alt_stack| {
+		if alt_stack.is_some() {
+			return Ok(());
+		}
+
+		let stack = map_stack(&StackOptions {
+			size: Some(SIGNAL_STACK_SIZE),
+			no_reserve: true,
+			..StackOptions::new()
+		})?;
+
+		let page_size = page_size();
+		let options = SignalStack {
+			/* Safety: the stack is larger than its guard page */
+			sp: unsafe { stack.as_ptr().cast::<u8>().add(page_size) }.cast(),
+			flags: 0,
+			size: stack.len().saturating_sub(page_size)
+		};
+
+		/* Safety: the stack is kept until the thread exits */
+		unsafe { signal_stack(Some(&options), None)? };
+
+		*alt_stack = Some(AltStack { _stack: stack });
+
+		Ok(())
+	})
+}
+
+/// Enable stack overflow detection for fibers created from now on, and set up
+/// an alternate signal stack for the current thread
+///
+/// # Errors
+/// If installing the handler or the alternate signal stack fails
+#[cfg(feature = "log")]
+pub fn install() -> OsResult<()> {
+	install_thread()?;
+	install_handler()?;
+
+	ENABLED.store(true, Ordering::Relaxed);
+
+	Ok(())
+}
+
+/// Whether stack overflow detection is enabled
+#[must_use]
+pub fn enabled() -> bool {
+	ENABLED.load(Ordering::Relaxed)
+}
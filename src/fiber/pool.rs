@@ -1,48 +1,196 @@
+use alloc::vec::Vec;
+use core::ops::DerefMut;
+#[cfg(feature = "std")]
 use std::sync::Mutex;
 
 use crate::fiber::*;
 use crate::impls::OptionExt;
+#[cfg(not(feature = "std"))]
+use crate::sync::SpinMutex as Mutex;
+#[cfg(feature = "log")]
 use crate::trace;
 
+/// How a [`Pool`] allocates and keeps stacks
+#[derive(Clone, Debug, Default)]
+pub struct PoolOptions {
+	/// How stacks are allocated. `stack.size` is the size of the stacks of
+	/// workers that are spawned without a size hint
+	pub stack: StackOptions,
+
+	/// Sizes that stack size hints are rounded up to, so that stacks can be
+	/// reused between workers with different hints. Hints larger than every
+	/// class get a stack of their own size, which is never kept
+	pub size_classes: Vec<usize>,
+
+	/// The most stacks to keep for reuse. Defaults to 20% of the running
+	/// workers, plus 16
+	pub max_retained: Option<usize>,
+
+	/// The most bytes of stack to keep for reuse
+	pub max_retained_bytes: Option<usize>
+}
+
+impl PoolOptions {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			stack: StackOptions::new(),
+			size_classes: Vec::new(),
+			max_retained: None,
+			max_retained_bytes: None
+		}
+	}
+}
+
+/// The number of buckets in [`PoolStats::usage`]
+pub const USAGE_BUCKETS: usize = usize::BITS as usize + 1;
+
+/// A snapshot of a [`Pool`]'s stacks, and of the stack usage of the workers
+/// that exited to it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolStats {
+	/// Workers that are running
+	pub active: u64,
+
+	/// Stacks kept for reuse
+	pub retained: usize,
+	pub retained_bytes: usize,
+
+	/// The number of exited workers whose stack usage was measured. Only stacks
+	/// filled with a canary are measured, see [`StackOptions::canary`]
+	pub measured: u64,
+
+	/// The deepest stack usage measured, in bytes
+	pub high_water_mark: usize,
+
+	/// The number of measured workers by stack usage. Bucket `i` counts the
+	/// workers that used less than `2^i` bytes, and at least `2^(i - 1)`
+	pub usage: [u64; USAGE_BUCKETS]
+}
+
 struct Data {
-	pool: Vec<Fiber>,
-	active: u64
+	/// Kept stacks, grouped by size. Only stacks of the default size and the
+	/// size classes are kept
+	stacks: Vec<(usize, Vec<Fiber>)>,
+
+	/// The size of stacks without a size hint, once the sizes are known
+	default_size: Option<usize>,
+	active: u64,
+	retained: usize,
+	retained_bytes: usize,
+	measured: u64,
+	high_water_mark: usize,
+	usage: [u64; USAGE_BUCKETS]
 }
 
 impl Data {
 	const fn new() -> Self {
-		Self { pool: Vec::new(), active: 0 }
+		Self {
+			stacks: Vec::new(),
+			default_size: None,
+			active: 0,
+			retained: 0,
+			retained_bytes: 0,
+			measured: 0,
+			high_water_mark: 0,
+			usage: [0; USAGE_BUCKETS]
+		}
+	}
+
+	fn record_usage(&mut self, used: usize) {
+		let bucket = usize::BITS.wrapping_sub(used.leading_zeros()) as usize;
+
+		self.measured = self.measured.saturating_add(1);
+		self.high_water_mark = self.high_water_mark.max(used);
+
+		if let Some(count) = self.usage.get_mut(bucket) {
+			*count = count.saturating_add(1);
+		}
+	}
+
+	#[allow(clippy::arithmetic_side_effects)]
+	fn take(&mut self, size: usize) -> Option<Fiber> {
+		let (_, stacks) = self.stacks.iter_mut().find(|(class, _)| *class == size)?;
+		let fiber = stacks.pop()?;
+
+		self.retained -= 1;
+		self.retained_bytes -= size;
+
+		Some(fiber)
+	}
+
+	/// Keep `fiber` for reuse, or return it if stacks of its size aren't kept
+	#[allow(clippy::arithmetic_side_effects)]
+	fn retain(&mut self, fiber: Fiber) -> Result<(), Fiber> {
+		let size = fiber.stack_size();
+		let Some((_, stacks)) = self.stacks.iter_mut().find(|(class, _)| *class == size) else {
+			return Err(fiber);
+		};
+
+		if stacks.try_reserve(1).is_err() {
+			return Err(fiber);
+		}
+
+		stacks.push(fiber);
+
+		self.retained += 1;
+		self.retained_bytes += size;
+
+		Ok(())
 	}
 }
 
 pub struct Pool {
+	options: PoolOptions,
 	data: Mutex<Data>
 }
 
 impl Pool {
 	#[must_use]
 	pub const fn new() -> Self {
-		Self { data: Mutex::new(Data::new()) }
+		Self::with_options(PoolOptions::new())
+	}
+
+	#[must_use]
+	pub const fn with_options(options: PoolOptions) -> Self {
+		Self { options, data: Mutex::new(Data::new()) }
+	}
+
+	#[must_use]
+	pub const fn options(&self) -> &PoolOptions {
+		&self.options
 	}
 
 	/// # Panics
 	/// if creating a fiber fails
 	#[must_use]
 	pub fn new_fiber(&self, start: Start) -> Fiber {
-		let fiber = {
-			/* we never panic with the lock */
-			#[allow(clippy::unwrap_used)]
-			let mut data = self.data.lock().unwrap();
+		self.new_fiber_with_stack(start, None)
+	}
+
+	/// Same as [`Pool::new_fiber`], except the stack is at least `stack_size`
+	/// bytes, rounded up to a size class
+	///
+	/// # Panics
+	/// if creating a fiber fails
+	#[must_use]
+	pub fn new_fiber_with_stack(&self, start: Start, stack_size: Option<usize>) -> Fiber {
+		let (size, fiber) = {
+			let mut data = self.lock();
 
 			data.active = data
 				.active
 				.checked_add(1)
 				.expect_nounwind("Fatal error: fiber count overflow");
-			data.pool.pop()
+
+			let size = self.stack_size(&mut data, stack_size);
+
+			(size, data.take(size))
 		};
 
 		match fiber {
 			Some(mut fiber) => {
+				#[cfg(feature = "log")]
 				trace!(target: self, "== Reusing stack for worker");
 
 				/* Safety: fiber was exited to us */
@@ -52,11 +200,125 @@ impl Pool {
 			}
 
 			None => {
-				trace!(target: self, "++ Creating stack for worker");
+				#[cfg(feature = "log")]
+				trace!(target: self, "++ Creating stack of {} bytes for worker", size);
+
+				Fiber::with_stack_and_start(&self.stack_options(size), start)
+			}
+		}
+	}
+
+	/// Allocate up to `count` stacks of at least `stack_size` bytes and keep
+	/// them for reuse, so that spawning workers later doesn't have to. Returns
+	/// the number of stacks allocated, which is fewer than `count` if the
+	/// pool's limits are reached
+	///
+	/// # Panics
+	/// if creating a fiber fails
+	pub fn prewarm(&self, count: usize, stack_size: Option<usize>) -> usize {
+		let size = self.stack_size(&mut self.lock(), stack_size);
+		let mut allocated = 0usize;
+
+		while allocated < count && self.has_room(&self.lock(), size, usize::MAX) {
+			let fiber = Fiber::with_stack(&self.stack_options(size));
+
+			if self.lock().retain(fiber).is_err() {
+				break;
+			}
+
+			allocated = allocated.wrapping_add(1);
+		}
+
+		#[cfg(feature = "log")]
+		trace!(target: self, "++ Allocated {} stacks of {} bytes", allocated, size);
+
+		allocated
+	}
+
+	#[must_use]
+	pub fn stats(&self) -> PoolStats {
+		let data = self.lock();
+
+		PoolStats {
+			active: data.active,
+			retained: data.retained,
+			retained_bytes: data.retained_bytes,
+			measured: data.measured,
+			high_water_mark: data.high_water_mark,
+			usage: data.usage
+		}
+	}
+
+	fn lock(&self) -> impl DerefMut<Target = Data> + '_ {
+		/* we never panic with the lock */
+		#[cfg(feature = "std")]
+		#[allow(clippy::unwrap_used)]
+		let data = self.data.lock().unwrap();
+
+		#[cfg(not(feature = "std"))]
+		let data = self.data.lock();
+
+		data
+	}
+
+	/// Resolve the sizes of the stacks that are kept
+	fn init_sizes(&self, data: &mut Data) -> usize {
+		if let Some(size) = data.default_size {
+			return size;
+		}
+
+		let default_size =
+			round_stack_size(self.options.stack.size.unwrap_or_else(default_stack_size));
+		let classes = self
+			.options
+			.size_classes
+			.iter()
+			.map(|class| round_stack_size(*class));
 
-				Fiber::new_with_start(start)
+		for size in [default_size].into_iter().chain(classes) {
+			if !data.stacks.iter().any(|(class, _)| *class == size) {
+				data.stacks.push((size, Vec::new()));
 			}
 		}
+
+		data.default_size = Some(default_size);
+		default_size
+	}
+
+	/// The size of the stack to allocate for a hint of `stack_size`
+	fn stack_size(&self, data: &mut Data, stack_size: Option<usize>) -> usize {
+		let default_size = self.init_sizes(data);
+
+		let Some(stack_size) = stack_size else {
+			return default_size;
+		};
+
+		let class = self
+			.options
+			.size_classes
+			.iter()
+			.copied()
+			.filter(|class| *class >= stack_size)
+			.min()
+			.unwrap_or(stack_size);
+
+		round_stack_size(class)
+	}
+
+	fn stack_options(&self, size: usize) -> StackOptions {
+		StackOptions { size: Some(size), ..self.options.stack }
+	}
+
+	/// Whether the limits allow another stack of `size` bytes to be kept, with
+	/// at most `ideal` stacks kept when the pool has no limit of its own
+	fn has_room(&self, data: &Data, size: usize, ideal: usize) -> bool {
+		let max = self.options.max_retained.unwrap_or(ideal);
+		let max_bytes = self.options.max_retained_bytes.unwrap_or(usize::MAX);
+
+		data.retained < max &&
+			data.retained_bytes
+				.checked_add(size)
+				.is_some_and(|bytes| bytes <= max_bytes)
 	}
 
 	const fn calculate_ideal(count: u64) -> u64 {
@@ -66,29 +328,67 @@ impl Pool {
 		(count * RATIO / 100 + 16)
 	}
 
+	/// Clear the stack of `fiber` and keep it for reuse, or drop it if the pool
+	/// is full. The stack usage of the worker is recorded if it can be measured
+	///
 	/// # Safety
 	/// fiber must be exited
 	///
 	/// This function never panics
 	#[allow(clippy::missing_panics_doc)]
-	pub unsafe fn exit_fiber(&self, fiber: Fiber) {
-		/* we never panic with the lock */
-		#[allow(clippy::unwrap_used)]
-		let mut data = self.data.lock().unwrap();
+	pub unsafe fn exit_fiber(&self, mut fiber: Fiber) {
+		let used = fiber.stack_high_water_mark();
+		let mut data = self.lock();
+
+		if let Some(used) = used {
+			data.record_usage(used);
+		}
 
 		data.active = data
 			.active
 			.checked_sub(1)
 			.expect_nounwind("Fatal error: fiber count overflow");
 
-		let ideal = Self::calculate_ideal(data.active);
+		let ideal = Self::calculate_ideal(data.active)
+			.try_into()
+			.unwrap_or(usize::MAX);
+
+		let keep = self.has_room(&data, fiber.stack_size(), ideal);
 
-		if ideal > data.pool.len() as u64 && data.pool.try_reserve(1).is_ok() {
-			trace!(target: self, "== Preserving worker stack");
+		/* clear or unmap the stack without holding the lock */
+		drop(data);
 
-			data.pool.push(fiber);
+		let result = if keep {
+			/* only a stack that is kept needs clearing
+			 *
+			 * Safety: guaranteed by caller
+			 */
+			unsafe { fiber.clear_stack() };
+
+			let mut data = self.lock();
+
+			/* another stack may have taken the room in the meantime */
+			if self.has_room(&data, fiber.stack_size(), ideal) {
+				data.retain(fiber)
+			} else {
+				Err(fiber)
+			}
 		} else {
-			trace!(target: self, "-- Dropping worker stack");
+			Err(fiber)
+		};
+
+		match result {
+			Ok(()) => {
+				#[cfg(feature = "log")]
+				trace!(target: self, "== Preserving worker stack");
+			}
+
+			Err(fiber) => {
+				#[cfg(feature = "log")]
+				trace!(target: self, "-- Dropping worker stack");
+
+				drop(fiber);
+			}
 		}
 	}
 }
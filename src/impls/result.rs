@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use core::fmt::Debug;
 
 use crate::macros::{panic_nounwind, sealed_trait, unreachable_unchecked};
 
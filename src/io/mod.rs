@@ -1,8 +1,9 @@
 #![allow(clippy::transmute_ptr_to_ptr)]
 
+use core::mem::{transmute, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+#[cfg(feature = "std")]
 use std::io::{IoSlice, Result, Write};
-use std::mem::{transmute, MaybeUninit};
-use std::ops::{Deref, DerefMut};
 
 use crate::pointer::*;
 
@@ -97,6 +98,7 @@ impl<const SIZE: usize> DerefMut for UninitBuf<SIZE> {
 	}
 }
 
+#[cfg(feature = "std")]
 impl<const SIZE: usize> Write for UninitBuf<SIZE> {
 	fn write(&mut self, buf: &[u8]) -> Result<usize> {
 		Ok(self.extend_from_slice(buf))
@@ -1,3 +1,5 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
 #[cfg(feature = "async_std")]
 pub mod async_std;
 #[cfg(feature = "cell")]
@@ -28,6 +30,8 @@ pub mod opt;
 pub mod os;
 #[cfg(feature = "pointer")]
 pub mod pointer;
+#[cfg(feature = "random")]
+pub mod random;
 #[cfg(feature = "runtime")]
 pub mod runtime;
 #[cfg(feature = "sync")]
@@ -35,6 +39,7 @@ pub mod sync;
 #[cfg(feature = "threadpool")]
 pub mod threadpool;
 
+extern crate alloc;
 extern crate self as xx_core;
 
 #[cfg(feature = "ctor")]
@@ -100,3 +100,9 @@ pub(super) fn print_backtrace(thread_name: &str) {
 
 	log!(target: thread_name, Level::Error, "{:?}", backtrace);
 }
+
+pub(super) fn print_forced_backtrace(thread_name: &str) {
+	let backtrace = Backtrace::force_capture();
+
+	log!(target: thread_name, Level::Error, "{:?}", backtrace);
+}
@@ -24,6 +24,13 @@ pub fn print_backtrace() {
 	internal::print_backtrace(thread_name);
 }
 
+/// Print a backtrace, even if backtraces aren't enabled by the environment
+pub fn print_forced_backtrace() {
+	get_thread_name!(thread_name);
+
+	internal::print_forced_backtrace(thread_name);
+}
+
 pub fn print_fatal(fmt: Arguments<'_>) {
 	get_thread_name!(thread_name);
 
@@ -1,7 +1,7 @@
 #[macro_export]
 macro_rules! panic_nounwind {
 	($($arg: tt)*) => {
-		$crate::runtime::panic_nounwind(::std::format_args!($($arg)*))
+		$crate::runtime::panic_nounwind(::core::format_args!($($arg)*))
 	}
 }
 
@@ -16,12 +16,12 @@ macro_rules! unreachable_unchecked {
 		#[cfg(debug_assertions)]
 		$crate::macros::panic_nounwind!(
 			"Entered unreachable code: {}",
-			::std::format_args!($($arg)*)
+			::core::format_args!($($arg)*)
 		);
 
 		#[cfg(not(debug_assertions))]
 		let _ = || {
-			::std::format_args!($($arg)*);
+			::core::format_args!($($arg)*);
 		};
 
 		#[cfg(not(debug_assertions))]
@@ -36,7 +36,7 @@ macro_rules! assert_unsafe_precondition {
 	($condition:expr) => {
 		$crate::macros::assert_unsafe_precondition!(
 			$condition,
-			::std::stringify!($condition)
+			::core::stringify!($condition)
 		)
 	};
 
@@ -47,7 +47,7 @@ macro_rules! assert_unsafe_precondition {
 
 			$crate::macros::panic_nounwind!(
 				"Unsafe precondition(s) violated: {}",
-				::std::format_args!($($arg)*)
+				::core::format_args!($($arg)*)
 			);
 		}
 
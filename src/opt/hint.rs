@@ -1,4 +1,4 @@
-use std::hint;
+use core::hint;
 
 #[inline(always)]
 #[cold]
@@ -27,7 +27,7 @@ pub const fn unlikely(cond: bool) -> bool {
 }
 
 /// # Safety
-/// See [`std::hint::unreachable_unchecked`]
+/// See [`core::hint::unreachable_unchecked`]
 #[inline(always)]
 pub const unsafe fn unreachable_unchecked() -> ! {
 	/* Safety: guaranteed by caller */
@@ -35,7 +35,7 @@ pub const unsafe fn unreachable_unchecked() -> ! {
 }
 
 /// # Safety
-/// See [`std::intrinsics::assume`]
+/// See [`core::intrinsics::assume`]
 #[inline(always)]
 pub const unsafe fn assume(condition: bool) {
 	if !condition {
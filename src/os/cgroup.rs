@@ -0,0 +1,128 @@
+//! Readers for the cgroup v2 limits of the calling process
+
+use std::fs;
+use std::path::PathBuf;
+
+use super::error::OsError;
+use super::*;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// The CPU bandwidth limit from `cpu.max`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CpuLimit {
+	/// The run time allowed per period, in microseconds, or `None` if
+	/// unlimited
+	pub quota: Option<u64>,
+
+	/// The length of a period, in microseconds
+	pub period: u64
+}
+
+impl CpuLimit {
+	/// The number of CPUs worth of time allowed, or `None` if unlimited
+	#[must_use]
+	#[allow(clippy::cast_precision_loss)]
+	pub fn cpus(&self) -> Option<f64> {
+		let quota = self.quota?;
+
+		(self.period != 0).then(|| quota as f64 / self.period as f64)
+	}
+}
+
+/// A cgroup v2 directory
+#[derive(Clone, Debug)]
+pub struct Cgroup {
+	path: PathBuf
+}
+
+fn parse_max(value: &str) -> Result<Option<u64>> {
+	match value.trim() {
+		"max" => Ok(None),
+		value => value
+			.parse()
+			.map(Some)
+			.map_err(|_| OsError::Inval.into())
+	}
+}
+
+impl Cgroup {
+	/// The cgroup of the calling process, from `/proc/self/cgroup`
+	///
+	/// Returns `OsError::NoEnt` if the process is not in a cgroup v2
+	/// hierarchy
+	pub fn current() -> Result<Self> {
+		let cgroups = fs::read_to_string("/proc/self/cgroup").map_err(Error::new)?;
+
+		/* the unified hierarchy has the entry `0::/path` */
+		let path = cgroups
+			.lines()
+			.find_map(|line| line.strip_prefix("0::"))
+			.ok_or(OsError::NoEnt)?;
+
+		Ok(Self::from_path(
+			PathBuf::from(CGROUP_ROOT).join(path.trim_start_matches('/'))
+		))
+	}
+
+	#[must_use]
+	pub const fn from_path(path: PathBuf) -> Self {
+		Self { path }
+	}
+
+	#[must_use]
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Read the control file `name`
+	pub fn read(&self, name: &str) -> Result<String> {
+		fs::read_to_string(self.path.join(name)).map_err(Error::new)
+	}
+
+	fn read_value(&self, name: &str) -> Result<u64> {
+		self.read(name)?
+			.trim()
+			.parse()
+			.map_err(|_| OsError::Inval.into())
+	}
+
+	/// The hard memory limit in bytes, or `None` if unlimited
+	pub fn memory_max(&self) -> Result<Option<u64>> {
+		parse_max(&self.read("memory.max")?)
+	}
+
+	/// The memory usage at which reclaim is throttled, or `None` if unlimited
+	pub fn memory_high(&self) -> Result<Option<u64>> {
+		parse_max(&self.read("memory.high")?)
+	}
+
+	/// The memory currently used by the cgroup, in bytes
+	pub fn memory_current(&self) -> Result<u64> {
+		self.read_value("memory.current")
+	}
+
+	pub fn cpu_max(&self) -> Result<CpuLimit> {
+		let value = self.read("cpu.max")?;
+		let mut fields = value.split_whitespace();
+		let quota = parse_max(fields.next().ok_or(OsError::Inval)?)?;
+		let period = match fields.next() {
+			Some(period) => period.parse().map_err(|_| OsError::Inval)?,
+			None => 100_000
+		};
+
+		Ok(CpuLimit { quota, period })
+	}
+
+	/// The total CPU time used by the cgroup
+	pub fn cpu_usage(&self) -> Result<Duration> {
+		let stat = self.read("cpu.stat")?;
+		let usage = stat
+			.lines()
+			.find_map(|line| line.strip_prefix("usage_usec "))
+			.and_then(|usage| usage.trim().parse().ok())
+			.ok_or(OsError::Inval)?;
+
+		Ok(Duration::from_micros(usage))
+	}
+}
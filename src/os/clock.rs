@@ -0,0 +1,445 @@
+//! Typed time measurements and a mockable [`Clock`]
+
+use core::cmp;
+use core::fmt::{self, Debug, Formatter};
+use core::hash::{Hash, Hasher};
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+#[cfg(feature = "std")]
+use std::time::SystemTime as StdSystemTime;
+
+use super::time::{nanotime, time, ClockId, TimeSpec};
+use super::*;
+use crate::impls::ResultExt;
+use crate::macros::sealed_trait;
+use crate::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+sealed_trait!();
+
+/// A clock that never goes backwards, and can be measured with an [`Instant`]
+pub trait MonotonicClockId: Sealed {
+	const ID: ClockId;
+}
+
+/// The monotonic clock, which does not advance while the system is suspended
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Monotonic;
+
+/// The boot time clock, which also advances while the system is suspended
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct BootTime;
+
+impl Sealed for Monotonic {}
+
+impl MonotonicClockId for Monotonic {
+	const ID: ClockId = ClockId::Monotonic;
+}
+
+impl Sealed for BootTime {}
+
+impl MonotonicClockId for BootTime {
+	const ID: ClockId = ClockId::BootTime;
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn duration_as_nanos(duration: Duration) -> Option<u64> {
+	let nanos = duration.as_nanos();
+
+	if nanos > u64::MAX as u128 {
+		None
+	} else {
+		Some(nanos as u64)
+	}
+}
+
+/// A measurement of a monotonically nondecreasing clock, with nanosecond
+/// precision
+///
+/// Instants of different clocks cannot be compared, which is enforced by the
+/// type parameter `C`
+pub struct Instant<C = Monotonic> {
+	nanos: u64,
+	phantom: PhantomData<C>
+}
+
+/// An [`Instant`] of the [`BootTime`] clock
+pub type BootInstant = Instant<BootTime>;
+
+impl<C: MonotonicClockId> Instant<C> {
+	/// # Panics
+	/// If the clock cannot be read, which never happens on a supported
+	/// kernel
+	#[must_use]
+	pub fn now() -> Self {
+		Self::from_nanos(nanotime(C::ID).expect_nounwind("Failed to get the time"))
+	}
+
+	/// The amount of time elapsed since this instant
+	#[must_use]
+	pub fn elapsed(&self) -> Duration {
+		Self::now().saturating_duration_since(*self)
+	}
+}
+
+impl<C> Instant<C> {
+	/// Creates an instant from the raw value of the clock, in nanoseconds
+	#[must_use]
+	pub const fn from_nanos(nanos: u64) -> Self {
+		Self { nanos, phantom: PhantomData }
+	}
+
+	/// The raw value of the clock, in nanoseconds
+	#[must_use]
+	pub const fn as_nanos(&self) -> u64 {
+		self.nanos
+	}
+
+	#[must_use]
+	pub const fn checked_add(&self, duration: Duration) -> Option<Self> {
+		let Some(nanos) = duration_as_nanos(duration) else {
+			return None;
+		};
+
+		match self.nanos.checked_add(nanos) {
+			Some(nanos) => Some(Self::from_nanos(nanos)),
+			None => None
+		}
+	}
+
+	#[must_use]
+	pub const fn checked_sub(&self, duration: Duration) -> Option<Self> {
+		let Some(nanos) = duration_as_nanos(duration) else {
+			return None;
+		};
+
+		match self.nanos.checked_sub(nanos) {
+			Some(nanos) => Some(Self::from_nanos(nanos)),
+			None => None
+		}
+	}
+
+	/// Same as [`Instant::checked_add`], except clamps to the maximum
+	/// instant on overflow. Useful for computing deadlines from timeouts
+	/// that may be infinite
+	#[must_use]
+	pub const fn saturating_add(&self, duration: Duration) -> Self {
+		match self.checked_add(duration) {
+			Some(instant) => instant,
+			None => Self::from_nanos(u64::MAX)
+		}
+	}
+
+	/// The amount of time elapsed from `earlier` to `self`, or `None` if
+	/// `earlier` is later than `self`
+	#[must_use]
+	pub const fn checked_duration_since(&self, earlier: Self) -> Option<Duration> {
+		match self.nanos.checked_sub(earlier.nanos) {
+			Some(nanos) => Some(Duration::from_nanos(nanos)),
+			None => None
+		}
+	}
+
+	/// The amount of time elapsed from `earlier` to `self`, or zero if
+	/// `earlier` is later than `self`
+	#[must_use]
+	pub const fn saturating_duration_since(&self, earlier: Self) -> Duration {
+		Duration::from_nanos(self.nanos.saturating_sub(earlier.nanos))
+	}
+
+	/// The amount of time remaining until `deadline`, or zero if the deadline
+	/// has passed
+	#[must_use]
+	pub const fn duration_until(&self, deadline: Self) -> Duration {
+		deadline.saturating_duration_since(*self)
+	}
+
+	#[must_use]
+	pub const fn as_timespec(&self) -> TimeSpec {
+		TimeSpec::from_nanos(self.nanos)
+	}
+}
+
+impl<C> Clone for Instant<C> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<C> Copy for Instant<C> {}
+
+impl<C> PartialEq for Instant<C> {
+	fn eq(&self, other: &Self) -> bool {
+		self.nanos == other.nanos
+	}
+}
+
+impl<C> Eq for Instant<C> {}
+
+impl<C> PartialOrd for Instant<C> {
+	fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<C> Ord for Instant<C> {
+	fn cmp(&self, other: &Self) -> cmp::Ordering {
+		self.nanos.cmp(&other.nanos)
+	}
+}
+
+impl<C> Hash for Instant<C> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.nanos.hash(state);
+	}
+}
+
+impl<C> Debug for Instant<C> {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+		fmt.debug_tuple("Instant")
+			.field(&Duration::from_nanos(self.nanos))
+			.finish()
+	}
+}
+
+impl<C> Add<Duration> for Instant<C> {
+	type Output = Self;
+
+	/// # Panics
+	/// If the result overflows
+	#[allow(clippy::expect_used)]
+	fn add(self, rhs: Duration) -> Self {
+		self.checked_add(rhs)
+			.expect("Overflow when adding duration to instant")
+	}
+}
+
+impl<C> AddAssign<Duration> for Instant<C> {
+	fn add_assign(&mut self, rhs: Duration) {
+		*self = *self + rhs;
+	}
+}
+
+impl<C> Sub<Duration> for Instant<C> {
+	type Output = Self;
+
+	/// # Panics
+	/// If the result overflows
+	#[allow(clippy::expect_used)]
+	fn sub(self, rhs: Duration) -> Self {
+		self.checked_sub(rhs)
+			.expect("Overflow when subtracting duration from instant")
+	}
+}
+
+impl<C> SubAssign<Duration> for Instant<C> {
+	fn sub_assign(&mut self, rhs: Duration) {
+		*self = *self - rhs;
+	}
+}
+
+impl<C> Sub for Instant<C> {
+	type Output = Duration;
+
+	/// Same as [`Instant::saturating_duration_since`]
+	fn sub(self, rhs: Self) -> Duration {
+		self.saturating_duration_since(rhs)
+	}
+}
+
+/// A measurement of the wall clock, with nanosecond precision
+///
+/// The wall clock can jump forwards or backwards, so use [`Instant`] to
+/// measure elapsed time
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct SystemTime {
+	/* nanoseconds since the unix epoch, which covers the years 1678 to 2262 */
+	nanos: i64
+}
+
+impl SystemTime {
+	pub const UNIX_EPOCH: Self = Self { nanos: 0 };
+
+	/// # Panics
+	/// If the clock cannot be read, or the time is out of range
+	#[must_use]
+	#[allow(clippy::expect_used)]
+	pub fn now() -> Self {
+		let ts = time(ClockId::RealTime).expect_nounwind("Failed to get the time");
+
+		Self::from_timespec(ts).expect("System time out of range")
+	}
+
+	#[must_use]
+	pub fn from_timespec(ts: TimeSpec) -> Option<Self> {
+		let nanos = ts.sec.checked_mul(1_000_000_000)?.checked_add(ts.nanos)?;
+
+		Some(Self { nanos })
+	}
+
+	/// Creates a time from the number of nanoseconds since the unix epoch
+	#[must_use]
+	pub const fn from_unix_nanos(nanos: i64) -> Self {
+		Self { nanos }
+	}
+
+	/// The number of nanoseconds since the unix epoch, which is negative
+	/// for times before the epoch
+	#[must_use]
+	pub const fn as_unix_nanos(&self) -> i64 {
+		self.nanos
+	}
+
+	#[must_use]
+	pub fn checked_add(&self, duration: Duration) -> Option<Self> {
+		let nanos: i64 = duration.as_nanos().try_into().ok()?;
+
+		Some(Self { nanos: self.nanos.checked_add(nanos)? })
+	}
+
+	#[must_use]
+	pub fn checked_sub(&self, duration: Duration) -> Option<Self> {
+		let nanos: i64 = duration.as_nanos().try_into().ok()?;
+
+		Some(Self { nanos: self.nanos.checked_sub(nanos)? })
+	}
+
+	/// The amount of time elapsed from `earlier` to `self`, or `None` if
+	/// `earlier` is later than `self`
+	#[must_use]
+	pub fn checked_duration_since(&self, earlier: Self) -> Option<Duration> {
+		let nanos = self.nanos.checked_sub(earlier.nanos)?;
+
+		Some(Duration::from_nanos(nanos.try_into().ok()?))
+	}
+
+	/// The amount of time elapsed since the unix epoch, or `None` if this
+	/// time is before the epoch
+	#[must_use]
+	pub fn duration_since_epoch(&self) -> Option<Duration> {
+		self.checked_duration_since(Self::UNIX_EPOCH)
+	}
+}
+
+#[cfg(feature = "std")]
+impl From<SystemTime> for StdSystemTime {
+	fn from(value: SystemTime) -> Self {
+		let duration = Duration::from_nanos(value.nanos.unsigned_abs());
+
+		if value.nanos >= 0 {
+			Self::UNIX_EPOCH + duration
+		} else {
+			Self::UNIX_EPOCH - duration
+		}
+	}
+}
+
+/// A source of time
+///
+/// Code that measures time or computes deadlines should take a `Clock`
+/// instead of reading the time directly, so that it can be tested
+/// deterministically with a [`ManualClock`]
+pub trait Clock {
+	/// The current monotonic time
+	fn now(&self) -> Instant;
+
+	/// The current wall clock time
+	fn system_time(&self) -> SystemTime;
+}
+
+impl<T: Clock + ?Sized> Clock for &T {
+	fn now(&self) -> Instant {
+		T::now(self)
+	}
+
+	fn system_time(&self) -> SystemTime {
+		T::system_time(self)
+	}
+}
+
+/// The clock provided by the operating system
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+	fn now(&self) -> Instant {
+		Instant::now()
+	}
+
+	fn system_time(&self) -> SystemTime {
+		SystemTime::now()
+	}
+}
+
+/// A clock that only advances when told to
+///
+/// The monotonic and wall clock times advance together. The wall clock can
+/// also be set independently, to simulate clock adjustments
+#[derive(Debug)]
+pub struct ManualClock {
+	nanos: AtomicU64,
+	system_offset: AtomicI64
+}
+
+impl ManualClock {
+	/// Creates a clock starting at `start`, whose wall clock starts at `system`
+	#[must_use]
+	#[allow(clippy::cast_possible_wrap)]
+	pub const fn new(start: Instant, system: SystemTime) -> Self {
+		Self {
+			nanos: AtomicU64::new(start.nanos),
+			system_offset: AtomicI64::new(system.nanos.wrapping_sub(start.nanos as i64))
+		}
+	}
+
+	/// Advance the clock by `duration`
+	///
+	/// # Panics
+	/// If the clock overflows
+	#[allow(clippy::expect_used)]
+	pub fn advance(&self, duration: Duration) {
+		let nanos = u64::try_from(duration.as_nanos()).ok();
+
+		self.nanos
+			.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |now| {
+				now.checked_add(nanos?)
+			})
+			.expect("Clock overflow");
+	}
+
+	/// Set the monotonic time, which may not go backwards
+	///
+	/// # Panics
+	/// If `instant` is earlier than the current time
+	pub fn set(&self, instant: Instant) {
+		let previous = self.nanos.fetch_max(instant.nanos, Ordering::Relaxed);
+
+		assert!(previous <= instant.nanos, "Monotonic clock cannot go backwards");
+	}
+
+	/// Set the wall clock time, without affecting the monotonic time
+	#[allow(clippy::cast_possible_wrap)]
+	pub fn set_system_time(&self, time: SystemTime) {
+		let now = self.nanos.load(Ordering::Relaxed);
+
+		self.system_offset
+			.store(time.nanos.wrapping_sub(now as i64), Ordering::Relaxed);
+	}
+}
+
+impl Default for ManualClock {
+	fn default() -> Self {
+		Self::new(Instant::from_nanos(0), SystemTime::UNIX_EPOCH)
+	}
+}
+
+impl Clock for ManualClock {
+	fn now(&self) -> Instant {
+		Instant::from_nanos(self.nanos.load(Ordering::Relaxed))
+	}
+
+	#[allow(clippy::cast_possible_wrap)]
+	fn system_time(&self) -> SystemTime {
+		let now = self.nanos.load(Ordering::Relaxed) as i64;
+
+		SystemTime::from_unix_nanos(now.wrapping_add(self.system_offset.load(Ordering::Relaxed)))
+	}
+}
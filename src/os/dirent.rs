@@ -1,3 +1,6 @@
+use alloc::boxed::Box;
+use alloc::vec;
+
 use super::error::*;
 use super::fcntl::OpenFlag;
 use super::stat::{Statx, *};
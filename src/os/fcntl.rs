@@ -102,3 +102,36 @@ define_enum! {
 impl AtFlag {
 	pub const RemoveDir: Self = Self::EAccess;
 }
+
+define_enum! {
+	#[repr(u32)]
+	pub enum FcntlCommand {
+		DupFd              = 0,
+		GetFd              = 1,
+		SetFd              = 2,
+		GetFl              = 3,
+		SetFl              = 4,
+		GetLock            = 5,
+		SetLock            = 6,
+		SetLockWait        = 7,
+		SetOwner           = 8,
+		GetOwner           = 9,
+		SetSignal          = 10,
+		GetSignal          = 11,
+		DupFdCloseOnExec   = 1030,
+		SetPipeSize        = 1031,
+		GetPipeSize        = 1032,
+
+		/// Add seals to a file that supports sealing
+		AddSeals           = 1033,
+
+		/// Get the set of seals on a file
+		GetSeals           = 1034
+	}
+}
+
+/// # Safety
+/// `arg` must be valid for the command. commands that take a pointer
+/// must point to valid memory of the correct type
+#[syscall_define(Fcntl)]
+pub unsafe fn fcntl(fd: BorrowedFd<'_>, cmd: FcntlCommand, arg: usize) -> OsResult<i32>;
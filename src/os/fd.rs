@@ -0,0 +1,153 @@
+//! File descriptor types
+//!
+//! These are the types from `std::os::fd` with the `std` feature. Without
+//! it, equivalent definitions are provided, with [`OwnedFd`] closing itself
+//! through the `close` system call
+
+#[cfg(feature = "std")]
+pub use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+
+#[cfg(not(feature = "std"))]
+pub use self::no_std::*;
+
+#[cfg(not(feature = "std"))]
+mod no_std {
+	use core::fmt::{self, Debug, Formatter};
+	use core::marker::PhantomData;
+	use core::mem::forget;
+
+	use super::super::syscall::{syscall1, SyscallNumber};
+
+	pub type RawFd = i32;
+
+	pub trait AsRawFd {
+		fn as_raw_fd(&self) -> RawFd;
+	}
+
+	pub trait FromRawFd {
+		/// # Safety
+		/// `fd` must be open, and owned by the caller
+		unsafe fn from_raw_fd(fd: RawFd) -> Self;
+	}
+
+	pub trait IntoRawFd {
+		fn into_raw_fd(self) -> RawFd;
+	}
+
+	pub trait AsFd {
+		fn as_fd(&self) -> BorrowedFd<'_>;
+	}
+
+	/// An owned file descriptor, closed on drop
+	#[repr(transparent)]
+	pub struct OwnedFd {
+		fd: RawFd
+	}
+
+	/// A borrowed file descriptor
+	#[repr(transparent)]
+	#[derive(Clone, Copy)]
+	pub struct BorrowedFd<'fd> {
+		fd: RawFd,
+		phantom: PhantomData<&'fd OwnedFd>
+	}
+
+	impl BorrowedFd<'_> {
+		/// # Safety
+		/// `fd` must remain open for the returned lifetime
+		#[must_use]
+		pub const unsafe fn borrow_raw(fd: RawFd) -> Self {
+			Self { fd, phantom: PhantomData }
+		}
+	}
+
+	impl Drop for OwnedFd {
+		#[allow(clippy::cast_sign_loss)]
+		fn drop(&mut self) {
+			/* Safety: we own the fd. errors are ignored, as in std */
+			let _ = unsafe { syscall1(SyscallNumber::Close as i32, self.fd as usize) };
+		}
+	}
+
+	impl AsRawFd for RawFd {
+		fn as_raw_fd(&self) -> RawFd {
+			*self
+		}
+	}
+
+	impl AsRawFd for OwnedFd {
+		fn as_raw_fd(&self) -> RawFd {
+			self.fd
+		}
+	}
+
+	impl AsRawFd for BorrowedFd<'_> {
+		fn as_raw_fd(&self) -> RawFd {
+			self.fd
+		}
+	}
+
+	impl FromRawFd for RawFd {
+		unsafe fn from_raw_fd(fd: RawFd) -> Self {
+			fd
+		}
+	}
+
+	impl FromRawFd for OwnedFd {
+		unsafe fn from_raw_fd(fd: RawFd) -> Self {
+			Self { fd }
+		}
+	}
+
+	impl IntoRawFd for RawFd {
+		fn into_raw_fd(self) -> RawFd {
+			self
+		}
+	}
+
+	impl IntoRawFd for OwnedFd {
+		fn into_raw_fd(self) -> RawFd {
+			let fd = self.fd;
+
+			forget(self);
+			fd
+		}
+	}
+
+	impl AsFd for OwnedFd {
+		fn as_fd(&self) -> BorrowedFd<'_> {
+			/* Safety: the fd is open for as long as we are borrowed */
+			unsafe { BorrowedFd::borrow_raw(self.fd) }
+		}
+	}
+
+	impl AsFd for BorrowedFd<'_> {
+		fn as_fd(&self) -> BorrowedFd<'_> {
+			*self
+		}
+	}
+
+	impl<T: AsFd + ?Sized> AsFd for &T {
+		fn as_fd(&self) -> BorrowedFd<'_> {
+			T::as_fd(self)
+		}
+	}
+
+	impl<T: AsFd + ?Sized> AsFd for &mut T {
+		fn as_fd(&self) -> BorrowedFd<'_> {
+			T::as_fd(self)
+		}
+	}
+
+	impl Debug for OwnedFd {
+		fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+			fmt.debug_struct("OwnedFd").field("fd", &self.fd).finish()
+		}
+	}
+
+	impl Debug for BorrowedFd<'_> {
+		fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+			fmt.debug_struct("BorrowedFd").field("fd", &self.fd).finish()
+		}
+	}
+}
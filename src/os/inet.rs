@@ -1,4 +1,4 @@
-use std::net::{IpAddr, SocketAddr, SocketAddrV6};
+use core::net::{IpAddr, SocketAddr, SocketAddrV6};
 
 use super::socket::AddressFamily;
 use super::*;
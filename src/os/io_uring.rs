@@ -1,3 +1,6 @@
+use alloc::format;
+use alloc::string::String;
+
 use super::error::OsError;
 use super::signal::{SignalMask, SIGSET_SIZE};
 use super::time::TimeSpec;
@@ -1,6 +1,7 @@
+use core::ops::{Deref, DerefMut};
+use core::slice;
+#[cfg(feature = "std")]
 use std::io::{IoSlice, IoSliceMut};
-use std::ops::{Deref, DerefMut};
-use std::slice;
 
 use super::*;
 
@@ -41,6 +42,7 @@ impl DerefMut for IoVecMut<'_> {
 	}
 }
 
+#[cfg(feature = "std")]
 impl<'buf> IoVec<'buf> {
 	#[must_use]
 	pub fn from_io_slices<'slices>(slices: &'slices [IoSlice<'buf>]) -> &'slices [Self] {
@@ -50,6 +52,7 @@ impl<'buf> IoVec<'buf> {
 	}
 }
 
+#[cfg(feature = "std")]
 impl<'buf> IoVecMut<'buf> {
 	#[must_use]
 	pub fn from_io_slices_mut<'slices>(
@@ -0,0 +1,365 @@
+//! Landlock unprivileged sandboxing
+//!
+//! A [`Ruleset`] lists the kinds of access to restrict, and the exceptions
+//! that remain allowed. Once restricted, the thread and its future children
+//! can only access files beneath the allowed paths and ports, with the
+//! allowed rights.
+//!
+//! Each kernel release supports a Landlock ABI version. Access rights that
+//! the running kernel does not know about are dropped, so that the same
+//! ruleset enforces as much as possible everywhere.
+
+use alloc::vec::Vec;
+
+use super::error::OsError;
+use super::fcntl::OpenFlag;
+use super::prctl::set_no_new_privs;
+use super::stat::{statx_fd, FileType, Statx, StatxMask};
+use super::unistd::open;
+use super::*;
+
+define_enum! {
+	#[bitflags]
+	#[repr(u64)]
+	pub enum AccessFs {
+		Execute    = 1 << 0,
+		WriteFile  = 1 << 1,
+		ReadFile   = 1 << 2,
+		ReadDir    = 1 << 3,
+		RemoveDir  = 1 << 4,
+		RemoveFile = 1 << 5,
+		MakeChar   = 1 << 6,
+		MakeDir    = 1 << 7,
+		MakeReg    = 1 << 8,
+		MakeSock   = 1 << 9,
+		MakeFifo   = 1 << 10,
+		MakeBlock  = 1 << 11,
+		MakeSym    = 1 << 12,
+
+		/// Link or rename a file from or to a different directory. ABI 2
+		Refer      = 1 << 13,
+
+		/// Truncate a file. ABI 3
+		Truncate   = 1 << 14,
+
+		/// Call `ioctl` on a character or block device. ABI 5
+		IoctlDev   = 1 << 15
+	}
+}
+
+define_enum! {
+	#[bitflags]
+	#[repr(u64)]
+	pub enum AccessNet {
+		/// Bind a TCP socket to a local port. ABI 4
+		BindTcp    = 1 << 0,
+
+		/// Connect a TCP socket to a remote port. ABI 4
+		ConnectTcp = 1 << 1
+	}
+}
+
+define_enum! {
+	#[repr(u32)]
+	pub enum RuleType {
+		PathBeneath = 1,
+		NetPort     = 2
+	}
+}
+
+define_enum! {
+	#[bitflags]
+	#[repr(u32)]
+	pub enum CreateRulesetFlag {
+		/// Return the highest supported ABI version instead of a ruleset
+		Version = 1 << 0
+	}
+}
+
+define_struct! {
+	pub struct RulesetAttr {
+		pub handled_access_fs: u64,
+		pub handled_access_net: u64
+	}
+}
+
+define_struct! {
+	#[repr(packed)]
+	pub struct PathBeneathAttr {
+		pub allowed_access: u64,
+		pub parent_fd: i32
+	}
+}
+
+define_struct! {
+	pub struct NetPortAttr {
+		pub allowed_access: u64,
+		pub port: u64
+	}
+}
+
+pub mod raw {
+	use super::*;
+
+	/// # Safety
+	/// `attr` must be null or point to `size` valid bytes
+	#[syscall_define(LandlockCreateRuleset)]
+	pub unsafe fn landlock_create_ruleset(
+		attr: Ptr<RulesetAttr>, size: usize, flags: BitFlags<CreateRulesetFlag>
+	) -> OsResult<i32>;
+
+	/// # Safety
+	/// `rule_attr` must point to the attribute struct for `rule_type`
+	#[syscall_define(LandlockAddRule)]
+	pub unsafe fn landlock_add_rule(
+		ruleset_fd: BorrowedFd<'_>, rule_type: RuleType, rule_attr: Ptr<()>, flags: u32
+	) -> OsResult<()>;
+}
+
+/// Create a new ruleset. `size` is the number of bytes of `attr` the kernel
+/// should read, as older kernels reject unknown fields
+///
+/// # Panics
+/// If `size` is larger than `RulesetAttr`
+pub fn landlock_create_ruleset(attr: &RulesetAttr, size: usize) -> OsResult<OwnedFd> {
+	assert!(size <= size_of::<RulesetAttr>());
+
+	/* Safety: `attr` is at least `size` bytes */
+	let fd = unsafe { raw::landlock_create_ruleset(ptr!(attr), size, BitFlags::default()) }?;
+
+	/* Safety: the kernel returned a new file descriptor */
+	Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+pub fn landlock_add_path_beneath(
+	ruleset_fd: BorrowedFd<'_>, attr: &PathBeneathAttr
+) -> OsResult<()> {
+	/* Safety: the attribute matches the rule type */
+	unsafe { raw::landlock_add_rule(ruleset_fd, RuleType::PathBeneath, ptr!(attr).cast(), 0) }
+}
+
+pub fn landlock_add_net_port(ruleset_fd: BorrowedFd<'_>, attr: &NetPortAttr) -> OsResult<()> {
+	/* Safety: the attribute matches the rule type */
+	unsafe { raw::landlock_add_rule(ruleset_fd, RuleType::NetPort, ptr!(attr).cast(), 0) }
+}
+
+/// Restrict the calling thread with the ruleset. The thread must have
+/// `no_new_privs` set, or `CAP_SYS_ADMIN`
+#[syscall_define(LandlockRestrictSelf)]
+pub fn landlock_restrict_self(ruleset_fd: BorrowedFd<'_>, flags: u32) -> OsResult<()>;
+
+/// Returns the highest Landlock ABI version supported by the kernel, or `0`
+/// if Landlock is unsupported or disabled
+#[must_use]
+#[allow(clippy::cast_sign_loss)]
+pub fn abi_version() -> u32 {
+	/* Safety: a null attribute is valid with the version flag */
+	let version =
+		unsafe { raw::landlock_create_ruleset(Ptr::null(), 0, CreateRulesetFlag::Version.into()) };
+
+	version.map_or(0, |version| version as u32)
+}
+
+/// The filesystem rights known to ABI `version`
+#[must_use]
+pub fn supported_access_fs(version: u32) -> BitFlags<AccessFs> {
+	let mut access = BitFlags::<AccessFs>::all();
+
+	if version < 5 {
+		access.remove(AccessFs::IoctlDev);
+	}
+
+	if version < 3 {
+		access.remove(AccessFs::Truncate);
+	}
+
+	if version < 2 {
+		access.remove(AccessFs::Refer);
+	}
+
+	if version < 1 {
+		access = BitFlags::default();
+	}
+
+	access
+}
+
+/// The network rights known to ABI `version`
+#[must_use]
+pub fn supported_access_net(version: u32) -> BitFlags<AccessNet> {
+	if version < 4 {
+		BitFlags::default()
+	} else {
+		BitFlags::all()
+	}
+}
+
+/// Rights that apply to a file rather than a directory
+#[must_use]
+pub fn file_access() -> BitFlags<AccessFs> {
+	make_bitflags!(AccessFs::{ Execute | WriteFile | ReadFile | Truncate | IoctlDev })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RestrictionStatus {
+	/// Every requested restriction is enforced
+	FullyEnforced,
+
+	/// The kernel supports a lower ABI, and some rights are unrestricted
+	PartiallyEnforced,
+
+	/// Landlock is unsupported or disabled
+	NotEnforced
+}
+
+#[derive(Debug)]
+enum Rule {
+	PathBeneath(OwnedFd, BitFlags<AccessFs>),
+	NetPort(u16, BitFlags<AccessNet>)
+}
+
+/// A builder for a Landlock ruleset
+///
+/// Rights that are handled are denied, unless allowed by a rule
+#[derive(Debug, Default)]
+pub struct Ruleset {
+	handled_fs: BitFlags<AccessFs>,
+	handled_net: BitFlags<AccessNet>,
+	rules: Vec<Rule>
+}
+
+#[allow(clippy::impl_trait_in_params)]
+impl Ruleset {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	#[must_use]
+	pub fn handle_fs(mut self, access: impl Into<BitFlags<AccessFs>>) -> Self {
+		self.handled_fs |= access.into();
+		self
+	}
+
+	#[must_use]
+	pub fn handle_net(mut self, access: impl Into<BitFlags<AccessNet>>) -> Self {
+		self.handled_net |= access.into();
+		self
+	}
+
+	/// Allow `access` to the file or directory `path`, and everything beneath
+	/// it
+	pub fn path_beneath(
+		mut self, path: impl AsRef<Path>, access: impl Into<BitFlags<AccessFs>>
+	) -> Result<Self> {
+		let flags = make_bitflags!(OpenFlag::{ Path | CloseOnExec });
+		let fd = with_path_as_cstr(path, |path| open(path, flags.bits(), 0).map_err(Into::into))?;
+
+		self.rules.push(Rule::PathBeneath(fd, access.into()));
+
+		Ok(self)
+	}
+
+	/// Like [`Ruleset::path_beneath`], for an already opened file
+	#[must_use]
+	pub fn path_beneath_fd(mut self, fd: OwnedFd, access: impl Into<BitFlags<AccessFs>>) -> Self {
+		self.rules.push(Rule::PathBeneath(fd, access.into()));
+		self
+	}
+
+	/// Allow `access` to the TCP port `port`
+	#[must_use]
+	pub fn net_port(mut self, port: u16, access: impl Into<BitFlags<AccessNet>>) -> Self {
+		self.rules.push(Rule::NetPort(port, access.into()));
+		self
+	}
+
+	/// Restrict the calling thread with the ruleset, dropping any rights the
+	/// kernel does not support
+	///
+	/// Sets `no_new_privs`, so that no capabilities are required
+	pub fn restrict_self(self) -> Result<RestrictionStatus> {
+		let version = match abi_version() {
+			0 => return Ok(RestrictionStatus::NotEnforced),
+			version => version
+		};
+
+		let handled_fs = self.handled_fs & supported_access_fs(version);
+		let handled_net = self.handled_net & supported_access_net(version);
+
+		let mut status = if handled_fs == self.handled_fs && handled_net == self.handled_net {
+			RestrictionStatus::FullyEnforced
+		} else {
+			RestrictionStatus::PartiallyEnforced
+		};
+
+		if handled_fs.is_empty() && handled_net.is_empty() {
+			return Ok(RestrictionStatus::NotEnforced);
+		}
+
+		let attr = RulesetAttr {
+			handled_access_fs: handled_fs.bits(),
+			handled_access_net: handled_net.bits()
+		};
+
+		/* kernels without network rules reject the larger struct */
+		let size = if version < 4 {
+			size_of::<u64>()
+		} else {
+			size_of::<RulesetAttr>()
+		};
+
+		let ruleset = landlock_create_ruleset(&attr, size)?;
+
+		for rule in &self.rules {
+			match rule {
+				Rule::PathBeneath(fd, access) => {
+					let mut access = *access & handled_fs;
+					let mut statx = Statx::default();
+
+					statx_fd(fd.as_fd(), 0, StatxMask::Type as u32, &mut statx)?;
+
+					if statx.file_type() != Some(FileType::Directory) {
+						access &= file_access();
+					}
+
+					if access.is_empty() {
+						continue;
+					}
+
+					let attr = PathBeneathAttr {
+						allowed_access: access.bits(),
+						parent_fd: fd.as_raw_fd()
+					};
+
+					landlock_add_path_beneath(ruleset.as_fd(), &attr)?;
+				}
+
+				Rule::NetPort(port, access) => {
+					let access = *access & handled_net;
+
+					if access.is_empty() {
+						continue;
+					}
+
+					let attr = NetPortAttr {
+						allowed_access: access.bits(),
+						port: (*port).into()
+					};
+
+					landlock_add_net_port(ruleset.as_fd(), &attr)?;
+				}
+			}
+		}
+
+		set_no_new_privs()?;
+
+		match landlock_restrict_self(ruleset.as_fd(), 0) {
+			Ok(()) => (),
+			Err(OsError::OpNotSupp) => status = RestrictionStatus::NotEnforced,
+			Err(err) => return Err(err.into())
+		}
+
+		Ok(status)
+	}
+}
@@ -0,0 +1,180 @@
+use super::error::OsError;
+use super::fcntl::*;
+use super::mman::*;
+use super::stat::*;
+use super::unistd::ftruncate;
+use super::*;
+
+define_enum! {
+	#[bitflags]
+	#[repr(u32)]
+	pub enum MemfdFlag {
+		/// Enable the close-on-exec flag for the new file descriptor.
+		CloseOnExec  = 1 << 0,
+
+		/// Allow sealing operations on this file.
+		AllowSealing = 1 << 1,
+
+		/// Create the file in the hugetlbfs filesystem using huge pages.
+		HugeTLB      = 1 << 2,
+
+		/// Create the file without executable permissions, and seal
+		/// it against becoming executable.
+		NoExecSeal   = 1 << 3,
+
+		/// Create the file with executable permissions.
+		Exec         = 1 << 4
+	}
+}
+
+define_enum! {
+	#[repr(u32)]
+	pub enum HugePageSize {
+		Size64KiB  = 16 << 26,
+		Size512KiB = 19 << 26,
+		Size1MiB   = 20 << 26,
+		Size2MiB   = 21 << 26,
+		Size8MiB   = 23 << 26,
+		Size16MiB  = 24 << 26,
+		Size32MiB  = 25 << 26,
+		Size256MiB = 28 << 26,
+		Size512MiB = 29 << 26,
+		Size1GiB   = 30 << 26,
+		Size2GiB   = 31 << 26,
+		Size16GiB  = 34 << 26
+	}
+}
+
+define_enum! {
+	#[bitflags]
+	#[repr(u32)]
+	pub enum Seal {
+		/// Prevent further seals from being added.
+		Seal        = 1 << 0,
+
+		/// Prevent the file from shrinking.
+		Shrink      = 1 << 1,
+
+		/// Prevent the file from growing.
+		Grow        = 1 << 2,
+
+		/// Prevent writes to the file, including through shared writable
+		/// mappings. Fails if any such mapping exists.
+		Write       = 1 << 3,
+
+		/// Prevent future writes, while allowing existing shared writable
+		/// mappings to continue writing.
+		FutureWrite = 1 << 4,
+
+		/// Prevent the executable permission bits from being changed.
+		Exec        = 1 << 5
+	}
+}
+
+pub mod raw {
+	use super::*;
+
+	#[syscall_define(MemfdCreate)]
+	pub fn memfd_create(name: &CStr, flags: u32) -> OsResult<OwnedFd>;
+}
+
+pub fn memfd_create(
+	name: &CStr, flags: BitFlags<MemfdFlag>, huge_page_size: Option<HugePageSize>
+) -> OsResult<OwnedFd> {
+	let mut flags = flags.bits();
+
+	if let Some(size) = huge_page_size {
+		flags |= MemfdFlag::HugeTLB as u32 | size as u32;
+	}
+
+	raw::memfd_create(name, flags)
+}
+
+pub fn add_seals(fd: BorrowedFd<'_>, seals: BitFlags<Seal>) -> OsResult<()> {
+	/* Safety: F_ADD_SEALS takes an integer argument */
+	unsafe { fcntl(fd, FcntlCommand::AddSeals, seals.bits() as usize) }.map(|_| ())
+}
+
+pub fn get_seals(fd: BorrowedFd<'_>) -> OsResult<BitFlags<Seal>> {
+	/* Safety: F_GET_SEALS takes no argument */
+	let seals = unsafe { fcntl(fd, FcntlCommand::GetSeals, 0) }?;
+
+	/* the seals are flags, so never negative */
+	#[allow(clippy::cast_sign_loss)]
+	let seals = seals as u32;
+
+	Ok(BitFlags::from_bits_truncate(seals))
+}
+
+/// Map the entire file read-only and shared
+///
+/// The file must be sealed against shrinking and writing, so that the
+/// contents of the mapping can never change, and accessing it can never
+/// fault with a `SIGBUS`. Returns `OsError::Inval` if the seals are missing
+pub fn map_sealed(fd: BorrowedFd<'_>) -> OsResult<Map<'static>> {
+	let seals = get_seals(fd)?;
+
+	if !seals.contains(Seal::Shrink | Seal::Write) {
+		return Err(OsError::Inval);
+	}
+
+	let mut stat = Statx::default();
+
+	statx_fd(fd, 0, StatxMask::Size as u32, &mut stat)?;
+
+	let len = stat.size.try_into().map_err(|_| OsError::Overflow)?;
+
+	Builder::new(Type::Shared, len)
+		.protect(Protection::Read)
+		.fd(fd)
+		.map()
+}
+
+pub struct MemFd(OwnedFd);
+
+impl MemFd {
+	pub fn new(
+		name: &CStr, flags: BitFlags<MemfdFlag>, huge_page_size: Option<HugePageSize>
+	) -> OsResult<Self> {
+		memfd_create(name, flags, huge_page_size).map(Self)
+	}
+
+	pub fn set_len(&self, len: u64) -> OsResult<()> {
+		let len = len.try_into().map_err(|_| OsError::FBig)?;
+
+		ftruncate(self.fd(), len)
+	}
+
+	pub fn add_seals(&self, seals: BitFlags<Seal>) -> OsResult<()> {
+		add_seals(self.fd(), seals)
+	}
+
+	pub fn seals(&self) -> OsResult<BitFlags<Seal>> {
+		get_seals(self.fd())
+	}
+
+	/// Seal the file against any further modification, then map it
+	/// read-only. See [`map_sealed`]
+	pub fn seal_and_map(&self) -> OsResult<Map<'static>> {
+		self.add_seals(Seal::Seal | Seal::Shrink | Seal::Grow | Seal::Write)?;
+
+		map_sealed(self.fd())
+	}
+
+	#[must_use]
+	pub fn fd(&self) -> BorrowedFd<'_> {
+		self.0.as_fd()
+	}
+}
+
+impl From<MemFd> for OwnedFd {
+	fn from(value: MemFd) -> Self {
+		value.0
+	}
+}
+
+impl From<OwnedFd> for MemFd {
+	fn from(value: OwnedFd) -> Self {
+		Self(value)
+	}
+}
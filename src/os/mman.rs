@@ -1,3 +1,8 @@
+use core::slice;
+
+use super::error::OsError;
+use super::memfd::{memfd_create, MemfdFlag};
+use super::unistd::{ftruncate, get_system_configuration, SystemConfiguration};
 use super::*;
 use crate::impls::ResultExt;
 
@@ -158,6 +163,12 @@ impl<'fd> Builder<'fd> {
 		}
 	}
 
+	#[must_use]
+	pub const fn address(mut self, addr: Ptr<()>) -> Self {
+		self.addr = addr;
+		self
+	}
+
 	#[must_use]
 	pub fn protect<F>(mut self, protection: F) -> Self
 	where
@@ -349,3 +360,94 @@ impl Drop for Map<'_> {
 		unsafe { munmap(self.section()) }.expect_nounwind("Failed to unmap memory");
 	}
 }
+
+/// A buffer whose pages are mapped twice, back to back, so that the byte at
+/// `offset` and the byte at `offset + capacity` are the same byte
+///
+/// Any range of up to `capacity` bytes that starts in the first half is
+/// contiguous in memory, even if it wraps around the end of the buffer. This
+/// makes it suitable as the backing storage for ring buffers
+pub struct MirrorBuffer {
+	map: Map<'static>,
+	capacity: usize
+}
+
+impl MirrorBuffer {
+	/// Create a new mirrored buffer. The capacity is rounded up to a multiple
+	/// of the page size
+	pub fn new(capacity: usize) -> OsResult<Self> {
+		let page_size = get_system_configuration(SystemConfiguration::Pagesize)?
+			.and_then(|size| usize::try_from(size).ok())
+			.filter(|size| *size > 0)
+			.ok_or(OsError::Inval)?;
+		let capacity = capacity
+			.max(1)
+			.checked_next_multiple_of(page_size)
+			.ok_or(OsError::NoMem)?;
+		let total = capacity.checked_mul(2).ok_or(OsError::NoMem)?;
+		let length = capacity.try_into().map_err(|_| OsError::NoMem)?;
+
+		let file = memfd_create(c"mirror_buffer", MemfdFlag::CloseOnExec.into(), None)?;
+
+		ftruncate(file.as_fd(), length)?;
+
+		/* reserve the address space for both halves. the reservation is
+		 * inaccessible until the file is mapped over it
+		 */
+		let map = Builder::new(Type::Private, total)
+			.flag(Flag::Anonymous | Flag::NoReserve)
+			.map()?;
+
+		for offset in [0, capacity] {
+			/* Safety: `offset` is within the reservation */
+			let addr = unsafe { map.as_ptr().cast::<u8>().add(offset) };
+
+			/* the mappings replace the reservation, and are unmapped
+			 * when the reservation is dropped
+			 */
+			Builder::new(Type::Shared, capacity)
+				.address(addr.cast_const().cast())
+				.protect(Protection::Read | Protection::Write)
+				.flag(Flag::Fixed)
+				.fd(file.as_fd())
+				.map_raw()?;
+		}
+
+		Ok(Self { map, capacity })
+	}
+
+	/// The size of the buffer, which is half of the mapping
+	#[must_use]
+	pub const fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	#[must_use]
+	pub const fn as_ptr(&self) -> MutPtr<u8> {
+		self.map.as_ptr().cast()
+	}
+
+	/// The entire mapping, which is `2 * capacity` bytes long
+	///
+	/// Note that writes to one half are visible through the other half
+	#[must_use]
+	pub fn as_slice(&self) -> &[u8] {
+		/* Safety: the mapping is valid and readable for its entire length */
+		unsafe { slice::from_raw_parts(self.as_ptr().as_ptr(), self.map.len()) }
+	}
+
+	/// See [`MirrorBuffer::as_slice`]
+	#[must_use]
+	pub fn as_mut_slice(&mut self) -> &mut [u8] {
+		/* Safety: the mapping is valid and writable for its entire length, and we
+		 * have exclusive access to it
+		 */
+		unsafe { slice::from_raw_parts_mut(self.as_ptr().as_mut_ptr(), self.map.len()) }
+	}
+}
+
+/* Safety: this is an owned type */
+unsafe impl Send for MirrorBuffer {}
+
+/* Safety: shared access only allows reads */
+unsafe impl Sync for MirrorBuffer {}
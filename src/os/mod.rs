@@ -1,41 +1,58 @@
-use std::ffi::{CStr, CString};
-use std::marker::PhantomData;
-use std::mem::{size_of, size_of_val, transmute};
-use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
-use std::path::Path;
-use std::time::Duration;
+use alloc::ffi::CString;
+use core::ffi::CStr;
+use core::marker::PhantomData;
+use core::mem::{size_of, size_of_val, transmute};
+use core::time::Duration;
 
 use enumflags2::{bitflags, make_bitflags, BitFlag, BitFlags};
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 
+use self::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use self::path::Path;
 use self::syscall::*;
 use crate::error::*;
 use crate::io::UninitBuf;
 use crate::macros::syscall_define;
 use crate::pointer::*;
 
+#[cfg(feature = "std")]
+pub mod cgroup;
+pub mod clock;
 pub mod dirent;
 pub mod epoll;
 pub mod error;
 pub mod eventfd;
 pub mod fcntl;
+pub mod fd;
 pub mod futex;
 pub mod inet;
 pub mod io_uring;
 pub mod iovec;
+pub mod landlock;
+pub mod memfd;
 pub mod mman;
 pub mod openat;
 pub mod openat2;
+pub mod path;
 pub mod poll;
+pub mod prctl;
+pub mod random;
 pub mod resource;
+pub mod sched;
+pub mod seccomp;
 pub mod signal;
+pub mod signal_handler;
 pub mod socket;
 pub mod stat;
 pub mod syscall;
 pub mod tcp;
 pub mod time;
+#[cfg(feature = "std")]
+pub mod topology;
 pub mod unistd;
+pub mod vdso;
+pub mod xattr;
 
 pub const INVALID_FD: RawFd = -1;
 
@@ -209,10 +226,10 @@ macro_rules! define_struct {
 		{ $($rest)* }
 
 		#[allow(deprecated)]
-		impl $(<$generic>)? ::std::default::Default for $name $(<$generic>)? {
+		impl $(<$generic>)? ::core::default::Default for $name $(<$generic>)? {
 			fn default() -> Self {
 				/* Safety: repr(C) */
-				unsafe { ::std::mem::zeroed() }
+				unsafe { ::core::mem::zeroed() }
 			}
 		}
 	};
@@ -233,24 +250,24 @@ macro_rules! define_union {
 		$vis union $name $($rest)*
 
 		#[allow(deprecated)]
-		impl ::std::default::Default for $name {
+		impl ::core::default::Default for $name {
 			fn default() -> Self {
 				/* Safety: repr(C) */
-				unsafe { ::std::mem::zeroed() }
+				unsafe { ::core::mem::zeroed() }
 			}
 		}
 
 		#[allow(deprecated)]
-		impl ::std::cmp::PartialEq for $name {
+		impl ::core::cmp::PartialEq for $name {
 			fn eq(&self, other: &Self) -> bool {
-				::std::ptr::eq(self, other)
+				::core::ptr::eq(self, other)
 			}
 		}
 
 		#[allow(deprecated)]
-		impl ::std::fmt::Debug for $name {
-			fn fmt(&self, fmt: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
-				fmt.debug_struct(::std::stringify!($name)).finish()
+		impl ::core::fmt::Debug for $name {
+			fn fmt(&self, fmt: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+				fmt.debug_struct(::core::stringify!($name)).finish()
 			}
 		}
 	}
@@ -276,7 +293,7 @@ where
 {
 	const MAX_STACK_ALLOCATION: usize = 384;
 
-	let bytes = path.as_ref().as_os_str().as_encoded_bytes();
+	let bytes = path::as_bytes(path.as_ref());
 
 	if bytes.len() >= MAX_STACK_ALLOCATION {
 		allocate_cstr(bytes, func)
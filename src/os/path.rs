@@ -0,0 +1,71 @@
+//! The path type accepted by functions in this module
+//!
+//! This is `std::path::Path` with the `std` feature. Without it, [`Path`] is
+//! a plain byte string
+
+#[cfg(feature = "std")]
+pub use std::path::Path;
+
+/// A borrowed path, which is not required to be valid UTF-8
+#[cfg(not(feature = "std"))]
+#[repr(transparent)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Path([u8]);
+
+#[cfg(not(feature = "std"))]
+impl Path {
+	#[must_use]
+	pub fn new<P: AsRef<[u8]> + ?Sized>(path: &P) -> &Self {
+		let bytes: *const [u8] = path.as_ref();
+
+		/* Safety: repr(transparent) */
+		unsafe { &*(bytes as *const Self) }
+	}
+
+	#[must_use]
+	pub const fn as_bytes(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+#[cfg(not(feature = "std"))]
+impl AsRef<Self> for Path {
+	fn as_ref(&self) -> &Self {
+		self
+	}
+}
+
+#[cfg(not(feature = "std"))]
+impl AsRef<Path> for str {
+	fn as_ref(&self) -> &Path {
+		Path::new(self)
+	}
+}
+
+#[cfg(not(feature = "std"))]
+impl AsRef<Path> for [u8] {
+	fn as_ref(&self) -> &Path {
+		Path::new(self)
+	}
+}
+
+#[cfg(not(feature = "std"))]
+impl AsRef<Path> for alloc::string::String {
+	fn as_ref(&self) -> &Path {
+		Path::new(self)
+	}
+}
+
+/// The bytes of `path`, without a nul terminator
+#[cfg(feature = "std")]
+#[must_use]
+pub fn as_bytes(path: &Path) -> &[u8] {
+	path.as_os_str().as_encoded_bytes()
+}
+
+/// The bytes of `path`, without a nul terminator
+#[cfg(not(feature = "std"))]
+#[must_use]
+pub const fn as_bytes(path: &Path) -> &[u8] {
+	path.as_bytes()
+}
@@ -0,0 +1,61 @@
+use super::*;
+
+define_enum! {
+	#[repr(u32)]
+	pub enum PrctlOption {
+		SetParentDeathSignal = 1,
+		GetParentDeathSignal = 2,
+		GetDumpable          = 3,
+		SetDumpable          = 4,
+		GetKeepCapabilities  = 7,
+		SetKeepCapabilities  = 8,
+		SetName              = 15,
+		GetName              = 16,
+		GetSeccomp           = 21,
+		SetSeccomp           = 22,
+		SetTimerSlack        = 29,
+		GetTimerSlack        = 30,
+		SetChildSubreaper    = 36,
+		GetChildSubreaper    = 37,
+
+		/// Once set, `execve` promises not to grant privileges that could not
+		/// have been granted without the call. Required to install a seccomp
+		/// filter without `CAP_SYS_ADMIN`. Cannot be unset.
+		SetNoNewPrivs        = 38,
+		GetNoNewPrivs        = 39,
+
+		/// Copy the auxiliary vector into a buffer. Since Linux 6.4
+		GetAuxv              = 0x4155_5856
+	}
+}
+
+/// # Safety
+/// the arguments must be valid for `option`. options that take a pointer
+/// must point to valid memory of the correct type
+#[syscall_define(Prctl)]
+pub unsafe fn prctl(
+	option: PrctlOption, arg2: usize, arg3: usize, arg4: usize, arg5: usize
+) -> OsResult<i32>;
+
+pub fn set_no_new_privs() -> OsResult<()> {
+	/* Safety: this option takes integer arguments */
+	unsafe { prctl(PrctlOption::SetNoNewPrivs, 1, 0, 0, 0) }.map(|_| ())
+}
+
+pub fn get_no_new_privs() -> OsResult<bool> {
+	/* Safety: this option takes integer arguments */
+	unsafe { prctl(PrctlOption::GetNoNewPrivs, 0, 0, 0, 0) }.map(|value| value != 0)
+}
+
+/// Set the timer slack of the current thread, in nanoseconds. Zero resets it
+/// to the thread's default
+pub fn set_timer_slack(nanos: usize) -> OsResult<()> {
+	/* Safety: this option takes integer arguments */
+	unsafe { prctl(PrctlOption::SetTimerSlack, nanos, 0, 0, 0) }.map(|_| ())
+}
+
+/// Get the timer slack of the current thread, in nanoseconds
+pub fn get_timer_slack() -> OsResult<i32> {
+	/* Safety: this option takes no arguments */
+	unsafe { prctl(PrctlOption::GetTimerSlack, 0, 0, 0, 0) }
+}
@@ -0,0 +1,55 @@
+use super::error::OsError;
+use super::*;
+
+define_enum! {
+	#[bitflags]
+	#[repr(u32)]
+	pub enum RandomFlag {
+		/// Return `OsError::Again` instead of blocking if the entropy pool
+		/// has not yet been initialized.
+		NonBlock = 1 << 0,
+
+		/// Draw from the random source instead of the urandom source. Only
+		/// useful on kernels older than 5.6.
+		Random   = 1 << 1,
+
+		/// Never block, and return possibly insecure random bytes if the
+		/// entropy pool has not yet been initialized. Requires kernel 5.6.
+		Insecure = 1 << 2
+	}
+}
+
+/// Fills some of `buf` with random bytes, returning the number of bytes
+/// written
+#[syscall_define(Getrandom)]
+pub fn getrandom(#[array] buf: MutRawBuf<'_>, flags: BitFlags<RandomFlag>) -> OsResult<usize>;
+
+/// Fills all of `buf` with random bytes, retrying interrupted and partial
+/// reads
+///
+/// Returns `OsError::Again` if `RandomFlag::NonBlock` is set and the entropy
+/// pool has not yet been initialized
+#[allow(clippy::arithmetic_side_effects)]
+pub fn fill_random(mut buf: &mut [u8], flags: BitFlags<RandomFlag>) -> OsResult<()> {
+	while !buf.is_empty() {
+		match getrandom(MutRawBuf::from(&mut *buf), flags) {
+			Ok(0) => return Err(OsError::Io),
+			Ok(read) => buf = &mut buf[read.min(buf.len())..],
+			Err(OsError::Intr) => (),
+			Err(err) => return Err(err)
+		}
+	}
+
+	Ok(())
+}
+
+/// Returns random bytes without ever blocking
+///
+/// Prefers the secure source, and falls back to possibly insecure bytes if
+/// the entropy pool has not yet been initialized
+pub fn random_nonblocking(buf: &mut [u8]) -> OsResult<()> {
+	match fill_random(buf, RandomFlag::NonBlock.into()) {
+		Err(OsError::Again) => fill_random(buf, RandomFlag::Insecure.into()),
+		result => result
+	}
+}
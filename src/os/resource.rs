@@ -1,3 +1,5 @@
+use core::ops::Sub;
+
 use super::time::TimeVal;
 use super::*;
 
@@ -121,6 +123,57 @@ define_struct! {
 	}
 }
 
+impl Usage {
+	#[must_use]
+	pub fn user_time(&self) -> Duration {
+		self.user_time.as_duration()
+	}
+
+	#[must_use]
+	pub fn system_time(&self) -> Duration {
+		self.sys_time.as_duration()
+	}
+
+	/// The total time spent on the CPU, in user and kernel mode
+	#[must_use]
+	pub fn cpu_time(&self) -> Duration {
+		self.user_time().saturating_add(self.system_time())
+	}
+}
+
+/// The usage between two snapshots. `max_rss` is a high water mark rather
+/// than a counter, so the value from the later snapshot is kept
+impl Sub for Usage {
+	type Output = Self;
+
+	fn sub(self, rhs: Self) -> Self {
+		Self {
+			user_time: self.user_time.saturating_sub(&rhs.user_time),
+			sys_time: self.sys_time.saturating_sub(&rhs.sys_time),
+			max_rss: self.max_rss,
+			text_rss: self.text_rss.saturating_sub(rhs.text_rss),
+			data_rss: self.data_rss.saturating_sub(rhs.data_rss),
+			stack_rss: self.stack_rss.saturating_sub(rhs.stack_rss),
+			minor_flt: self.minor_flt.saturating_sub(rhs.minor_flt),
+			major_fault: self.major_fault.saturating_sub(rhs.major_fault),
+			swaps: self.swaps.saturating_sub(rhs.swaps),
+			input_block: self.input_block.saturating_sub(rhs.input_block),
+			output_block: self.output_block.saturating_sub(rhs.output_block),
+			ipc_msgs_sent: self.ipc_msgs_sent.saturating_sub(rhs.ipc_msgs_sent),
+			ipc_msgs_recvd: self.ipc_msgs_recvd.saturating_sub(rhs.ipc_msgs_recvd),
+			signals_delivered: self
+				.signals_delivered
+				.saturating_sub(rhs.signals_delivered),
+			voluntary_context_switches: self
+				.voluntary_context_switches
+				.saturating_sub(rhs.voluntary_context_switches),
+			involuntary_context_switches: self
+				.involuntary_context_switches
+				.saturating_sub(rhs.involuntary_context_switches)
+		}
+	}
+}
+
 pub mod raw {
 	use super::*;
 
@@ -163,6 +216,32 @@ pub fn p_rlimit(
 	Ok(limit)
 }
 
+/// Get the limit of another process. A `pid` of `None` is the calling
+/// process
+pub fn get_process_limit(pid: Option<i32>, resource: Resource) -> OsResult<Limit> {
+	p_rlimit(pid, resource, None)
+}
+
+/// Set the limit of another process, returning the previous limit. Requires
+/// `CAP_SYS_RESOURCE` unless the process has the same credentials
+pub fn set_process_limit(pid: Option<i32>, resource: Resource, limit: &Limit) -> OsResult<Limit> {
+	p_rlimit(pid, resource, Some(limit))
+}
+
+/// Raise the soft limit of `resource` to its hard limit, returning the new
+/// limit. Commonly used at startup for `Resource::NoFile`
+pub fn raise_limit_to_max(resource: Resource) -> OsResult<Limit> {
+	let mut limit = get_rlimit(resource)?;
+
+	if limit.current != limit.maximum {
+		limit.current = limit.maximum;
+
+		set_rlimit(resource, &limit)?;
+	}
+
+	Ok(limit)
+}
+
 pub fn get_limit(resource: Resource) -> OsResult<u64> {
 	Ok(get_rlimit(resource)?.current)
 }
@@ -0,0 +1,296 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::unistd::{get_system_configuration, SystemConfiguration};
+use super::*;
+
+/// The number of CPUs in the kernel's default `cpu_set_t`. A [`CpuSet`]
+/// grows past it as needed
+pub const CPU_SETSIZE: usize = 1024;
+
+const BITS: usize = u64::BITS as usize;
+
+/// A set of CPUs, in the kernel's `cpu_set_t` format. The set grows to hold
+/// any CPU number
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct CpuSet {
+	/// Never ends with a zero word, so that equal sets compare equal
+	bits: Vec<u64>
+}
+
+impl CpuSet {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { bits: Vec::new() }
+	}
+
+	fn trim(&mut self) {
+		while self.bits.last() == Some(&0) {
+			self.bits.pop();
+		}
+	}
+
+	#[allow(clippy::arithmetic_side_effects)]
+	pub fn insert(&mut self, cpu: usize) {
+		let word = cpu / BITS;
+
+		if word >= self.bits.len() {
+			self.bits.resize(word + 1, 0);
+		}
+
+		self.bits[word] |= 1 << (cpu % BITS);
+	}
+
+	#[allow(clippy::arithmetic_side_effects)]
+	pub fn remove(&mut self, cpu: usize) {
+		if let Some(word) = self.bits.get_mut(cpu / BITS) {
+			*word &= !(1 << (cpu % BITS));
+
+			self.trim();
+		}
+	}
+
+	#[must_use]
+	#[allow(clippy::arithmetic_side_effects)]
+	pub fn contains(&self, cpu: usize) -> bool {
+		self.bits
+			.get(cpu / BITS)
+			.is_some_and(|word| word & (1 << (cpu % BITS)) != 0)
+	}
+
+	#[must_use]
+	pub fn count(&self) -> usize {
+		self.bits.iter().map(|word| word.count_ones() as usize).sum()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.bits.is_empty()
+	}
+
+	pub fn clear(&mut self) {
+		self.bits.clear();
+	}
+
+	/// Iterate the CPUs in the set, in increasing order
+	pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+		(0..self.bits.len().saturating_mul(BITS)).filter(|cpu| self.contains(*cpu))
+	}
+
+	#[must_use]
+	pub fn union(&self, other: &Self) -> Self {
+		let (mut set, other) = if self.bits.len() >= other.bits.len() {
+			(self.clone(), other)
+		} else {
+			(other.clone(), self)
+		};
+
+		for (word, other) in set.bits.iter_mut().zip(&other.bits) {
+			*word |= other;
+		}
+
+		set
+	}
+
+	#[must_use]
+	pub fn intersection(&self, other: &Self) -> Self {
+		let mut set = self.clone();
+
+		set.bits.truncate(other.bits.len());
+
+		for (word, other) in set.bits.iter_mut().zip(&other.bits) {
+			*word &= other;
+		}
+
+		set.trim();
+		set
+	}
+
+	/// Parse the kernel's cpu list format, such as `0-3,8,10-11`
+	#[must_use]
+	pub fn parse_list(list: &str) -> Option<Self> {
+		let mut set = Self::new();
+
+		for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+			let (start, end) = match range.split_once('-') {
+				Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+				None => {
+					let cpu = range.parse().ok()?;
+
+					(cpu, cpu)
+				}
+			};
+
+			if start > end {
+				return None;
+			}
+
+			for cpu in start..=end {
+				set.insert(cpu);
+			}
+		}
+
+		Some(set)
+	}
+}
+
+impl Default for CpuSet {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl FromIterator<usize> for CpuSet {
+	fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+		let mut set = Self::new();
+
+		for cpu in iter {
+			set.insert(cpu);
+		}
+
+		set
+	}
+}
+
+impl fmt::Debug for CpuSet {
+	fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt.debug_set().entries(self.iter()).finish()
+	}
+}
+
+define_enum! {
+	#[repr(u32)]
+	pub enum Policy {
+		Other      = 0,
+		Fifo       = 1,
+		RoundRobin = 2,
+		Batch      = 3,
+		Idle       = 5
+	}
+}
+
+/// Children created by fork do not inherit privileged scheduling policies
+pub const SCHED_RESET_ON_FORK: u32 = 0x4000_0000;
+
+define_struct! {
+	pub struct SchedParam {
+		pub priority: i32
+	}
+}
+
+pub mod raw {
+	use super::*;
+
+	#[syscall_define(SchedSetaffinity)]
+	pub fn sched_setaffinity(pid: i32, size: usize, mask: Ptr<()>) -> OsResult<()>;
+
+	/// Returns the number of bytes of the mask that were written
+	#[syscall_define(SchedGetaffinity)]
+	pub fn sched_getaffinity(pid: i32, size: usize, mask: MutPtr<()>) -> OsResult<usize>;
+
+	#[syscall_define(SchedSetscheduler)]
+	pub fn sched_setscheduler(pid: i32, policy: u32, param: &SchedParam) -> OsResult<()>;
+
+	#[syscall_define(SchedGetscheduler)]
+	pub fn sched_getscheduler(pid: i32) -> OsResult<u32>;
+
+	#[syscall_define(SchedGetPriorityMax)]
+	pub fn sched_get_priority_max(policy: Policy) -> OsResult<i32>;
+
+	#[syscall_define(SchedGetPriorityMin)]
+	pub fn sched_get_priority_min(policy: Policy) -> OsResult<i32>;
+
+	#[syscall_define(SchedYield)]
+	pub fn sched_yield() -> OsResult<()>;
+}
+
+/// Set the CPUs that thread `pid` may run on. A `pid` of `None` is the
+/// calling thread
+pub fn sched_setaffinity(pid: Option<i32>, set: &CpuSet) -> OsResult<()> {
+	raw::sched_setaffinity(
+		pid.unwrap_or(0),
+		size_of_val(set.bits.as_slice()),
+		ptr!(set.bits.as_ptr()).cast()
+	)
+}
+
+/// Get the CPUs that thread `pid` may run on. A `pid` of `None` is the
+/// calling thread
+///
+/// This reflects the `cpuset` cgroup controller as well as any affinity
+/// set by the parent
+///
+/// The mask starts at [`CPU_SETSIZE`] CPUs, and grows until it holds every
+/// CPU the kernel knows of
+pub fn sched_getaffinity(pid: Option<i32>) -> OsResult<CpuSet> {
+	let mut bits = vec![0; CPU_SETSIZE / BITS];
+
+	loop {
+		let size = size_of_val(bits.as_slice());
+
+		match raw::sched_getaffinity(pid.unwrap_or(0), size, ptr!(bits.as_mut_ptr()).cast()) {
+			Ok(_) => break,
+
+			/* the mask is smaller than the kernel's. give up once it's far
+			 * larger than any kernel supports
+			 */
+			Err(OsError::Inval) if size < (1 << 20) => {
+				let len = bits.len().saturating_mul(2);
+
+				bits.resize(len, 0);
+			}
+
+			Err(err) => return Err(err)
+		}
+	}
+
+	let mut set = CpuSet { bits };
+
+	set.trim();
+
+	Ok(set)
+}
+
+/// Pin the calling thread to `cpu`
+pub fn pin_current_thread(cpu: usize) -> OsResult<()> {
+	sched_setaffinity(None, &[cpu].into_iter().collect())
+}
+
+pub fn sched_setscheduler(
+	pid: Option<i32>, policy: Policy, reset_on_fork: bool, priority: i32
+) -> OsResult<()> {
+	let mut policy = policy as u32;
+
+	if reset_on_fork {
+		policy |= SCHED_RESET_ON_FORK;
+	}
+
+	raw::sched_setscheduler(pid.unwrap_or(0), policy, &SchedParam { priority })
+}
+
+/// Returns `None` for a policy that isn't a [`Policy`], such as
+/// `SCHED_DEADLINE`, which can only be set with `sched_setattr`
+pub fn sched_getscheduler(pid: Option<i32>) -> OsResult<Option<Policy>> {
+	let policy = raw::sched_getscheduler(pid.unwrap_or(0))?;
+
+	Ok(Policy::from_u32(policy & !SCHED_RESET_ON_FORK))
+}
+
+pub use raw::{sched_get_priority_max, sched_get_priority_min, sched_yield};
+
+/// The number of CPUs this thread is allowed to run on, falling back to the
+/// number of online CPUs
+#[must_use]
+pub fn available_parallelism() -> Option<usize> {
+	if let Ok(set) = sched_getaffinity(None) {
+		if !set.is_empty() {
+			return Some(set.count());
+		}
+	}
+
+	get_system_configuration(SystemConfiguration::NprocessorsOnln)
+		.ok()
+		.flatten()
+		.and_then(|count| count.try_into().ok())
+}
@@ -0,0 +1,460 @@
+//! Seccomp-BPF system call filters
+//!
+//! A [`Filter`] is a list of rules, each matching a system call number and
+//! optionally some of its arguments, compiled into a classic BPF program
+//! that the kernel runs on every system call made by the thread.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::error::OsError;
+use super::prctl::set_no_new_privs;
+use super::*;
+
+/// The maximum number of instructions in a BPF program
+pub const BPF_MAXINSNS: usize = 4096;
+
+#[cfg(target_arch = "x86_64")]
+pub const AUDIT_ARCH: u32 = 0xc000_003e;
+
+#[cfg(target_arch = "aarch64")]
+pub const AUDIT_ARCH: u32 = 0xc000_00b7;
+
+/// System calls made through the x32 ABI have this bit set in their number
+#[cfg(target_arch = "x86_64")]
+pub const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+pub mod bpf {
+	pub const LD: u16 = 0x00;
+	pub const ALU: u16 = 0x04;
+	pub const JMP: u16 = 0x05;
+	pub const RET: u16 = 0x06;
+
+	pub const W: u16 = 0x00;
+	pub const ABS: u16 = 0x20;
+
+	pub const AND: u16 = 0x50;
+
+	pub const JEQ: u16 = 0x10;
+	pub const JGT: u16 = 0x20;
+	pub const JGE: u16 = 0x30;
+
+	pub const K: u16 = 0x00;
+}
+
+/// Offsets into `struct seccomp_data`
+pub mod data {
+	pub const NR: u32 = 0;
+	pub const ARCH: u32 = 4;
+	pub const INSTRUCTION_POINTER: u32 = 8;
+	pub const ARGS: u32 = 16;
+}
+
+define_struct! {
+	pub struct SockFilter {
+		pub code: u16,
+		pub jt: u8,
+		pub jf: u8,
+		pub k: u32
+	}
+}
+
+impl SockFilter {
+	#[must_use]
+	pub const fn stmt(code: u16, k: u32) -> Self {
+		Self { code, jt: 0, jf: 0, k }
+	}
+
+	#[must_use]
+	pub const fn jump(code: u16, k: u32, jt: u8, jf: u8) -> Self {
+		Self { code, jt, jf, k }
+	}
+}
+
+define_struct! {
+	pub struct SockFprog {
+		pub len: u16,
+		pub filter: Ptr<SockFilter>
+	}
+}
+
+define_enum! {
+	#[repr(u32)]
+	pub enum SeccompOperation {
+		SetModeStrict = 0,
+		SetModeFilter = 1,
+		GetActionAvail = 2,
+		GetNotifSizes = 3
+	}
+}
+
+define_enum! {
+	#[bitflags]
+	#[repr(u32)]
+	pub enum SeccompFlag {
+		/// Synchronize the filter to all threads of the process
+		Tsync        = 1 << 0,
+
+		/// Log all actions except `Allow`
+		Log          = 1 << 1,
+
+		/// Disable speculative store bypass mitigations
+		SpecAllow    = 1 << 2,
+		NewListener  = 1 << 3,
+		TsyncEsrch   = 1 << 4,
+		WaitKillable = 1 << 5
+	}
+}
+
+/// # Safety
+/// `args` must be valid for `operation`
+#[syscall_define(Seccomp)]
+pub unsafe fn seccomp(
+	operation: SeccompOperation, flags: BitFlags<SeccompFlag>, args: Ptr<()>
+) -> OsResult<i32>;
+
+/// What happens when a rule matches
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+	KillProcess,
+	KillThread,
+
+	/// Send `SIGSYS` to the thread
+	Trap,
+
+	/// Fail the system call with the error, without executing it
+	Errno(OsError),
+
+	/// Notify a ptrace tracer, passing the value in `PTRACE_GETEVENTMSG`
+	Trace(u16),
+
+	/// Log and allow the system call
+	Log,
+	Allow
+}
+
+impl Action {
+	#[must_use]
+	#[allow(clippy::cast_sign_loss)]
+	pub fn into_raw(self) -> u32 {
+		match self {
+			Self::KillProcess => 0x8000_0000,
+			Self::KillThread => 0x0000_0000,
+			Self::Trap => 0x0003_0000,
+			Self::Errno(err) => 0x0005_0000 | (i32::from(err) as u32 & 0xffff),
+			Self::Trace(data) => 0x7ff0_0000 | u32::from(data),
+			Self::Log => 0x7ffc_0000,
+			Self::Allow => 0x7fff_0000
+		}
+	}
+}
+
+/// An unsigned comparison of a 64 bit system call argument
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Comparison {
+	Equal(u64),
+	NotEqual(u64),
+	Less(u64),
+	LessEqual(u64),
+	Greater(u64),
+	GreaterEqual(u64),
+
+	/// `arg & mask == value`
+	MaskedEqual { mask: u64, value: u64 }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Condition {
+	pub arg: u8,
+	pub comparison: Comparison
+}
+
+/// A rule applies its action when the system call number and all of the
+/// conditions match
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Rule {
+	pub syscall: SyscallNumber,
+	pub conditions: Vec<Condition>,
+	pub action: Action
+}
+
+impl Rule {
+	#[must_use]
+	pub const fn new(syscall: SyscallNumber, action: Action) -> Self {
+		Self { syscall, conditions: Vec::new(), action }
+	}
+
+	/// Add a condition on argument `arg`
+	///
+	/// # Panics
+	/// If `arg` is not less than 6
+	#[must_use]
+	pub fn arg(mut self, arg: u8, comparison: Comparison) -> Self {
+		assert!(arg < 6, "System calls only have six arguments");
+
+		self.conditions.push(Condition { arg, comparison });
+		self
+	}
+}
+
+/// A jump target that is resolved once the length of the rule is known
+#[derive(Clone, Copy)]
+enum Jump {
+	Offset(u8),
+
+	/// The start of the next rule
+	NextRule
+}
+
+struct Instruction {
+	code: u16,
+	k: u32,
+	jt: Jump,
+	jf: Jump
+}
+
+impl Instruction {
+	const fn stmt(code: u16, k: u32) -> Self {
+		Self { code, k, jt: Jump::Offset(0), jf: Jump::Offset(0) }
+	}
+
+	const fn jump(op: u16, k: u32, jt: Jump, jf: Jump) -> Self {
+		Self { code: bpf::JMP | op | bpf::K, k, jt, jf }
+	}
+
+	const fn load(offset: u32) -> Self {
+		Self::stmt(bpf::LD | bpf::W | bpf::ABS, offset)
+	}
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::arithmetic_side_effects)]
+fn compile_condition(condition: &Condition, out: &mut Vec<Instruction>) {
+	use Jump::*;
+
+	/* arguments are little endian on all supported architectures */
+	let offset = data::ARGS + u32::from(condition.arg) * 8;
+	let (low, high) = (Instruction::load(offset), Instruction::load(offset + 4));
+	let split = |value: u64| ((value >> 32) as u32, value as u32);
+
+	match condition.comparison {
+		Comparison::Equal(value) => {
+			let (hi, lo) = split(value);
+
+			out.extend([
+				high,
+				Instruction::jump(bpf::JEQ, hi, Offset(0), NextRule),
+				low,
+				Instruction::jump(bpf::JEQ, lo, Offset(0), NextRule)
+			]);
+		}
+
+		Comparison::NotEqual(value) => {
+			let (hi, lo) = split(value);
+
+			out.extend([
+				high,
+				Instruction::jump(bpf::JEQ, hi, Offset(0), Offset(2)),
+				low,
+				Instruction::jump(bpf::JEQ, lo, NextRule, Offset(0))
+			]);
+		}
+
+		Comparison::MaskedEqual { mask, value } => {
+			let ((mask_hi, mask_lo), (hi, lo)) = (split(mask), split(value));
+
+			out.extend([
+				high,
+				Instruction::stmt(bpf::ALU | bpf::AND | bpf::K, mask_hi),
+				Instruction::jump(bpf::JEQ, hi, Offset(0), NextRule),
+				low,
+				Instruction::stmt(bpf::ALU | bpf::AND | bpf::K, mask_lo),
+				Instruction::jump(bpf::JEQ, lo, Offset(0), NextRule)
+			]);
+		}
+
+		Comparison::Greater(value) | Comparison::GreaterEqual(value) => {
+			let (hi, lo) = split(value);
+			let op = if matches!(condition.comparison, Comparison::Greater(_)) {
+				bpf::JGT
+			} else {
+				bpf::JGE
+			};
+
+			/* high > hi passes, high < hi fails, otherwise compare low */
+			out.extend([
+				high,
+				Instruction::jump(bpf::JGT, hi, Offset(3), Offset(0)),
+				Instruction::jump(bpf::JEQ, hi, Offset(0), NextRule),
+				low,
+				Instruction::jump(op, lo, Offset(0), NextRule)
+			]);
+		}
+
+		Comparison::Less(value) | Comparison::LessEqual(value) => {
+			let (hi, lo) = split(value);
+			let op = if matches!(condition.comparison, Comparison::Less(_)) {
+				bpf::JGE
+			} else {
+				bpf::JGT
+			};
+
+			/* high < hi passes, high > hi fails, otherwise compare low */
+			out.extend([
+				high,
+				Instruction::jump(bpf::JGE, hi, Offset(0), Offset(3)),
+				Instruction::jump(bpf::JGT, hi, NextRule, Offset(0)),
+				low,
+				Instruction::jump(op, lo, NextRule, Offset(0))
+			]);
+		}
+	}
+}
+
+/// A seccomp filter, built from rules that are checked in order. The first
+/// matching rule decides the action, and system calls that match no rule get
+/// the default action
+///
+/// System calls from a foreign architecture (or the x32 ABI) always kill the
+/// process, as their numbers would be misinterpreted
+#[derive(Clone, Debug)]
+pub struct Filter {
+	default: Action,
+	rules: Vec<Rule>,
+	flags: BitFlags<SeccompFlag>
+}
+
+impl Filter {
+	#[must_use]
+	pub fn new(default: Action) -> Self {
+		Self { default, rules: Vec::new(), flags: BitFlags::default() }
+	}
+
+	#[must_use]
+	pub fn rule(mut self, rule: Rule) -> Self {
+		self.rules.push(rule);
+		self
+	}
+
+	#[must_use]
+	pub fn allow(self, syscall: SyscallNumber) -> Self {
+		self.rule(Rule::new(syscall, Action::Allow))
+	}
+
+	/// Kill the process if `syscall` is called
+	#[must_use]
+	pub fn deny(self, syscall: SyscallNumber) -> Self {
+		self.rule(Rule::new(syscall, Action::KillProcess))
+	}
+
+	/// Fail `syscall` with `err`
+	#[must_use]
+	pub fn errno(self, syscall: SyscallNumber, err: OsError) -> Self {
+		self.rule(Rule::new(syscall, Action::Errno(err)))
+	}
+
+	#[must_use]
+	pub fn flag(mut self, flag: SeccompFlag) -> Self {
+		self.flags |= flag;
+		self
+	}
+
+	/// Compile the filter into a BPF program
+	///
+	/// Returns `OsError::TooBig` if the program exceeds [`BPF_MAXINSNS`]
+	/// instructions, or a rule has too many conditions to jump over
+	#[allow(clippy::cast_possible_truncation, clippy::arithmetic_side_effects)]
+	pub fn compile(&self) -> OsResult<Vec<SockFilter>> {
+		let kill = Action::KillProcess.into_raw();
+		let mut program = vec![
+			SockFilter::stmt(bpf::LD | bpf::W | bpf::ABS, data::ARCH),
+			SockFilter::jump(bpf::JMP | bpf::JEQ | bpf::K, AUDIT_ARCH, 1, 0),
+			SockFilter::stmt(bpf::RET | bpf::K, kill)
+		];
+
+		#[cfg(target_arch = "x86_64")]
+		program.extend([
+			SockFilter::stmt(bpf::LD | bpf::W | bpf::ABS, data::NR),
+			SockFilter::jump(bpf::JMP | bpf::JGE | bpf::K, X32_SYSCALL_BIT, 0, 1),
+			SockFilter::stmt(bpf::RET | bpf::K, kill)
+		]);
+
+		let mut block = Vec::new();
+
+		for rule in &self.rules {
+			block.clear();
+			block.extend([
+				Instruction::load(data::NR),
+				Instruction::jump(bpf::JEQ, rule.syscall as u32, Jump::Offset(0), Jump::NextRule)
+			]);
+
+			for condition in &rule.conditions {
+				compile_condition(condition, &mut block);
+			}
+
+			block.push(Instruction::stmt(bpf::RET | bpf::K, rule.action.into_raw()));
+
+			let len = block.len();
+			let resolve = |index: usize, jump: Jump| match jump {
+				Jump::Offset(offset) => Ok(offset),
+				Jump::NextRule => u8::try_from(len - index - 1).map_err(|_| OsError::TooBig)
+			};
+
+			for (index, insn) in block.iter().enumerate() {
+				program.push(SockFilter {
+					code: insn.code,
+					jt: resolve(index, insn.jt)?,
+					jf: resolve(index, insn.jf)?,
+					k: insn.k
+				});
+			}
+		}
+
+		program.push(SockFilter::stmt(bpf::RET | bpf::K, self.default.into_raw()));
+
+		if program.len() > BPF_MAXINSNS {
+			return Err(OsError::TooBig);
+		}
+
+		Ok(program)
+	}
+
+	/// Compile and install the filter for the current thread (or all threads,
+	/// with [`SeccompFlag::Tsync`])
+	///
+	/// Sets `no_new_privs` first, so that no capabilities are required.
+	/// Installed filters cannot be removed
+	#[allow(clippy::cast_possible_truncation)]
+	pub fn install(&self) -> OsResult<()> {
+		let program = self.compile()?;
+		let prog = SockFprog { len: program.len() as u16, filter: ptr!(program.as_ptr()) };
+
+		set_no_new_privs()?;
+
+		/* Safety: `prog` points to a valid program */
+		unsafe {
+			seccomp(
+				SeccompOperation::SetModeFilter,
+				self.flags,
+				ptr!(&prog).cast()
+			)
+		}?;
+
+		Ok(())
+	}
+}
+
+/// Returns `true` if the kernel supports `action`
+#[must_use]
+pub fn action_available(action: Action) -> bool {
+	let action = action.into_raw() & 0xffff_0000;
+
+	/* Safety: the argument is a pointer to a u32 */
+	unsafe {
+		seccomp(
+			SeccompOperation::GetActionAvail,
+			BitFlags::default(),
+			ptr!(&action).cast()
+		)
+	}
+	.is_ok()
+}
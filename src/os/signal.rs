@@ -1,8 +1,12 @@
+#[cfg(feature = "std")]
 use std::os::unix::thread::RawPthread;
 
 use super::error::result_from_libc;
 use super::*;
 
+#[cfg(not(feature = "std"))]
+pub type RawPthread = core::ffi::c_ulong;
+
 pub type SignalSet = u64;
 
 define_enum! {
@@ -278,11 +282,32 @@ define_union! {
 }
 
 define_struct! {
+	/// The C library's `struct sigaction`, as taken by [`sig_action`]
 	pub struct SigAction {
 		pub handler: SigHandler,
+		pub mask: [SignalSet; 16],
 		pub flags: u32,
-		pub restorer: Option<unsafe extern "C" fn() -> !>,
-		pub mask: [SignalSet; 16]
+		pub restorer: Option<unsafe extern "C" fn() -> !>
+	}
+}
+
+define_enum! {
+	#[repr(u32)]
+	#[bitflags]
+	pub enum SignalStackFlag {
+		/// The thread is running on the alternate signal stack
+		OnStack    = 1 << 0,
+		Disable    = 1 << 1,
+		AutoDisarm = 1 << 31
+	}
+}
+
+define_struct! {
+	/// The kernel's `stack_t`, as taken by [`signal_stack`]
+	pub struct SignalStack {
+		pub sp: MutPtr<()>,
+		pub flags: u32,
+		pub size: usize
 	}
 }
 
@@ -331,3 +356,13 @@ pub fn pthread_set_sigmask(
 
 	result_from_libc(result as isize).map(|_| ())
 }
+
+/// Set or get the alternate stack that signal handlers installed with
+/// [`SignalFlags::OnStack`] run on
+///
+/// # Safety
+/// The new stack must stay mapped until it is replaced or disabled
+#[syscall_define(Sigaltstack)]
+pub unsafe fn signal_stack(
+	stack: Option<&SignalStack>, old: Option<&mut SignalStack>
+) -> OsResult<()>;
@@ -0,0 +1,332 @@
+//! A process-wide registry of signal handlers
+//!
+//! Several components can register callbacks for the same signal. The first
+//! registration for a signal installs a handler that runs every registered
+//! callback, then chains to the action that was installed before it
+//!
+//! Callbacks run in signal handler context, and must only do
+//! async-signal-safe work, such as setting an atomic flag or writing to an
+//! eventfd
+//!
+//! ```
+//! let flag = Arc::new(AtomicBool::new(false));
+//! let _registration = register_flag(Signal::Interrupt as i32, flag.clone())?;
+//!
+//! while !flag.load(Ordering::Relaxed) {
+//! 	/* ... */
+//! }
+//! ```
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use core::cell::Cell;
+use core::cell::UnsafeCell;
+use core::hint::spin_loop;
+use core::mem::{forget, zeroed};
+use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicU8, Ordering};
+
+use super::error::OsError;
+use super::eventfd::EventFd;
+use super::signal::*;
+use super::*;
+
+type Callback = dyn Fn(&SigInfo) + Send + Sync;
+
+struct Entry {
+	callback: Box<Callback>,
+	active: AtomicBool,
+	next: *mut Entry
+}
+
+/* Safety: `next` is only written before the entry is shared */
+unsafe impl Send for Entry {}
+
+/* Safety: see above */
+unsafe impl Sync for Entry {}
+
+const UNINSTALLED: u8 = 0;
+const INSTALLING: u8 = 1;
+const INSTALLED: u8 = 2;
+
+struct Slot {
+	state: AtomicU8,
+
+	/// Written once while `INSTALLING`, and only read once `INSTALLED`
+	previous: UnsafeCell<SigAction>,
+
+	/// Set once `previous` has run, if it was installed with
+	/// [`SignalFlags::ResetHand`]
+	reset: AtomicBool,
+
+	/// Entries are never removed or freed, so that the handler never sees
+	/// one freed while it runs
+	entries: AtomicPtr<Entry>
+}
+
+/* Safety: access to `previous` is synchronized by `state` */
+unsafe impl Sync for Slot {}
+
+impl Slot {
+	const fn new() -> Self {
+		Self {
+			state: AtomicU8::new(UNINSTALLED),
+			/* Safety: SigAction is repr(C) */
+			previous: UnsafeCell::new(unsafe { zeroed() }),
+			reset: AtomicBool::new(false),
+			entries: AtomicPtr::new(core::ptr::null_mut())
+		}
+	}
+}
+
+static SLOTS: [Slot; SIGRTMAX as usize] = [const { Slot::new() }; SIGRTMAX as usize];
+
+fn slot(signal: i32) -> Option<&'static Slot> {
+	let index = usize::try_from(signal).ok().filter(|index| *index != 0)?;
+
+	SLOTS.get(index)
+}
+
+/// Whether `signal` is raised by a fault, which happens again once the
+/// handler returns
+fn is_fault(signal: i32) -> bool {
+	[
+		Signal::SegmentationViolation,
+		Signal::Bus,
+		Signal::IllegalInstruction,
+		Signal::FloatingPointException
+	]
+	.iter()
+	.any(|fault| *fault as i32 == signal)
+}
+
+/// Calls the previously installed action, unless it was the default action
+/// or ignored. The signals in its mask are blocked while it runs, as the
+/// kernel would have done
+///
+/// # Safety
+/// The arguments must be those of the current signal
+unsafe fn chain(previous: &SigAction, signal: i32, info: MutPtr<SigInfo>, context: MutPtr<()>) {
+	/* Safety: all the fields of the union are pointer sized */
+	let raw = unsafe { transmute::<SigHandler, usize>(previous.handler) };
+
+	if raw == SigHandlers::Default as usize || raw == SigHandlers::Ignore as usize {
+		/* the fault happens again once we return, and then takes the default
+		 * action
+		 */
+		if is_fault(signal) {
+			let _ = sig_action(signal, Some(&SigAction::default()), None);
+		}
+
+		return;
+	}
+
+	let mut mask = [0; 16];
+	let masked = pthread_set_sigmask(SignalHow::Block, Some(&previous.mask), Some(&mut mask));
+
+	if previous.flags & SignalFlags::SigInfo as u32 != 0 {
+		/* Safety: SA_SIGINFO was set, so this is the active field */
+		if let Some(action) = unsafe { previous.handler.action } {
+			/* Safety: guaranteed by caller */
+			unsafe { action(signal, info, context) };
+		}
+	} else {
+		/* Safety: SA_SIGINFO was not set, so this is the active field */
+		if let Some(handler) = unsafe { previous.handler.handler } {
+			/* Safety: guaranteed by caller */
+			unsafe { handler(signal) };
+		}
+	}
+
+	if masked.is_ok() {
+		let _ = pthread_set_sigmask(SignalHow::SetMask, Some(&mask), None);
+	}
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+	/// The number of signal handlers running on this thread
+	static DEPTH: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Whether the current thread is running the handler installed by this
+/// module
+#[must_use]
+pub fn in_handler() -> bool {
+	#[cfg(feature = "std")]
+	return DEPTH.try_with(Cell::get).unwrap_or(0) != 0;
+
+	#[cfg(not(feature = "std"))]
+	false
+}
+
+unsafe extern "C" fn handle(signal: i32, info: MutPtr<SigInfo>, context: MutPtr<()>) {
+	#[cfg(feature = "std")]
+	let _ = DEPTH.try_with(|depth| depth.set(depth.get().wrapping_add(1)));
+
+	/* Safety: guaranteed by caller */
+	unsafe { dispatch(signal, info, context) };
+
+	#[cfg(feature = "std")]
+	let _ = DEPTH.try_with(|depth| depth.set(depth.get().wrapping_sub(1)));
+}
+
+/// # Safety
+/// The arguments must be those of the current signal
+unsafe fn dispatch(signal: i32, info: MutPtr<SigInfo>, context: MutPtr<()>) {
+	let Some(slot) = slot(signal) else {
+		return;
+	};
+
+	let mut entry = slot.entries.load(Ordering::Acquire);
+
+	while !entry.is_null() {
+		/* Safety: entries are never freed */
+		let current = unsafe { &*entry };
+
+		if current.active.load(Ordering::Acquire) {
+			/* Safety: the kernel passes a valid siginfo */
+			(current.callback)(unsafe { info.as_ref() });
+		}
+
+		entry = current.next;
+	}
+
+	if slot.state.load(Ordering::Acquire) == INSTALLED {
+		/* Safety: `previous` is never written again once installed */
+		let previous = unsafe { &*slot.previous.get() };
+
+		/* an action installed with SA_RESETHAND only runs once, and is then
+		 * treated like the default action
+		 */
+		if previous.flags & SignalFlags::ResetHand as u32 != 0 &&
+			slot.reset.swap(true, Ordering::AcqRel)
+		{
+			return;
+		}
+
+		/* Safety: these are the arguments of the current signal */
+		unsafe { chain(previous, signal, info, context) };
+	}
+}
+
+fn install(slot: &Slot, signal: i32) -> OsResult<()> {
+	loop {
+		match slot.state.compare_exchange_weak(
+			UNINSTALLED,
+			INSTALLING,
+			Ordering::Acquire,
+			Ordering::Acquire
+		) {
+			Ok(_) => break,
+			Err(INSTALLED) => return Ok(()),
+			Err(_) => spin_loop()
+		}
+	}
+
+	let mut action = SigAction::default();
+
+	action.handler.action = Some(handle);
+	action.flags = SignalFlags::SigInfo as u32 | SignalFlags::OnStack as u32;
+
+	/* Safety: exclusive access while `INSTALLING`. the old action is swapped
+	 * out in the same call, so that none installed concurrently is lost
+	 */
+	let result = sig_action(
+		signal,
+		Some(&action),
+		Some(unsafe { &mut *slot.previous.get() })
+	);
+
+	slot.state.store(
+		if result.is_ok() {
+			INSTALLED
+		} else {
+			UNINSTALLED
+		},
+		Ordering::Release
+	);
+
+	result
+}
+
+/// Removes the callback when dropped. The handler itself stays installed
+#[must_use = "the callback is removed when the registration is dropped"]
+pub struct Registration {
+	entry: &'static Entry
+}
+
+impl Registration {
+	/// Keep the callback registered for the lifetime of the process
+	pub fn forget(self) {
+		forget(self);
+	}
+}
+
+impl Drop for Registration {
+	fn drop(&mut self) {
+		self.entry.active.store(false, Ordering::Release);
+	}
+}
+
+/// Run `callback` whenever `signal` is delivered, before chaining to the
+/// action that was installed when the signal was first registered
+///
+/// System calls interrupted by the signal fail with [`OsError::Intr`]
+///
+/// The memory used by a registration is never freed
+///
+/// # Safety
+/// `callback` must only do async-signal-safe work
+#[allow(clippy::impl_trait_in_params)]
+pub unsafe fn register(
+	signal: i32, callback: impl Fn(&SigInfo) + Send + Sync + 'static
+) -> OsResult<Registration> {
+	let Some(slot) = slot(signal) else {
+		return Err(OsError::Inval);
+	};
+
+	let entry = Box::into_raw(Box::new(Entry {
+		callback: Box::new(callback),
+		active: AtomicBool::new(true),
+		next: core::ptr::null_mut()
+	}));
+
+	let mut head = slot.entries.load(Ordering::Relaxed);
+
+	loop {
+		/* Safety: the entry is not shared until the exchange succeeds */
+		unsafe { (*entry).next = head };
+
+		match slot
+			.entries
+			.compare_exchange_weak(head, entry, Ordering::Release, Ordering::Relaxed)
+		{
+			Ok(_) => break,
+			Err(current) => head = current
+		}
+	}
+
+	/* Safety: entries are never freed */
+	let registration = Registration { entry: unsafe { &*entry } };
+
+	install(slot, signal)?;
+
+	Ok(registration)
+}
+
+/// Set `flag` whenever `signal` is delivered
+pub fn register_flag(signal: i32, flag: Arc<AtomicBool>) -> OsResult<Registration> {
+	/* Safety: storing to an atomic is async-signal-safe */
+	unsafe { register(signal, move |_| flag.store(true, Ordering::Release)) }
+}
+
+/// Add one to `event` whenever `signal` is delivered
+pub fn register_eventfd(signal: i32, event: Arc<EventFd>) -> OsResult<Registration> {
+	/* Safety: the write system call is async-signal-safe */
+	unsafe {
+		register(signal, move |_| {
+			let _ = event.write(1);
+		})
+	}
+}
@@ -1,6 +1,7 @@
 use super::dirent::FileType;
 use super::fcntl::AtFlag;
 use super::openat::into_raw_dirfd;
+use super::time::TimeSpec;
 use super::*;
 
 define_enum! {
@@ -30,6 +31,43 @@ impl StatxMask {
 	pub const BasicStats: u32 = 0x07ff;
 }
 
+define_enum! {
+	/// The permission bits of a file's mode
+	#[bitflags]
+	#[repr(u32)]
+	pub enum Mode {
+		OtherExecute = 1 << 0,
+		OtherWrite   = 1 << 1,
+		OtherRead    = 1 << 2,
+		GroupExecute = 1 << 3,
+		GroupWrite   = 1 << 4,
+		GroupRead    = 1 << 5,
+		UserExecute  = 1 << 6,
+		UserWrite    = 1 << 7,
+		UserRead     = 1 << 8,
+		Sticky       = 1 << 9,
+		SetGroupId   = 1 << 10,
+		SetUserId    = 1 << 11
+	}
+}
+
+pub type Permissions = BitFlags<Mode>;
+
+#[allow(non_upper_case_globals)]
+impl Mode {
+	pub const UserAll: u32 = 0o700;
+	pub const GroupAll: u32 = 0o070;
+	pub const OtherAll: u32 = 0o007;
+}
+
+/// Set the timestamp to the current time, when used as the nanoseconds of a
+/// [`TimeSpec`] passed to [`utimensat`]
+pub const UTIME_NOW: i64 = (1 << 30) - 1;
+
+/// Leave the timestamp unchanged, when used as the nanoseconds of a
+/// [`TimeSpec`] passed to [`utimensat`]
+pub const UTIME_OMIT: i64 = (1 << 30) - 2;
+
 define_struct! {
 	pub struct StatxTimestamp {
 		pub sec: i64,
@@ -85,6 +123,15 @@ impl Statx {
 
 		FileType::from_u16(self.mode >> 12)
 	}
+
+	#[must_use]
+	pub fn permissions(&self) -> Option<Permissions> {
+		if !self.mask().intersects(StatxMask::Mode) {
+			return None;
+		}
+
+		Some(BitFlags::from_bits_truncate(self.mode.into()))
+	}
 }
 
 pub mod internal {
@@ -94,6 +141,22 @@ pub mod internal {
 	pub fn statx(
 		dirfd: RawFd, pathname: &CStr, flags: u32, mask: u32, statx: &mut Statx
 	) -> OsResult<()>;
+
+	#[syscall_define(Fchmodat)]
+	pub fn fchmodat(dirfd: RawFd, pathname: &CStr, mode: Permissions) -> OsResult<()>;
+
+	#[syscall_define(Fchownat)]
+	pub fn fchownat(
+		dirfd: RawFd, pathname: &CStr, owner: u32, group: u32, flags: BitFlags<AtFlag>
+	) -> OsResult<()>;
+
+	#[syscall_define(Fchown)]
+	pub fn fchown(fd: BorrowedFd<'_>, owner: u32, group: u32) -> OsResult<()>;
+
+	#[syscall_define(Utimensat)]
+	pub fn utimensat(
+		dirfd: RawFd, pathname: Ptr<()>, times: Option<&[TimeSpec; 2]>, flags: BitFlags<AtFlag>
+	) -> OsResult<()>;
 }
 
 pub fn statx(
@@ -113,3 +176,51 @@ pub fn statx_fd(fd: BorrowedFd<'_>, flags: u32, mask: u32, statx: &mut Statx) ->
 		statx
 	)
 }
+
+pub fn fchmodat(
+	dirfd: Option<BorrowedFd<'_>>, pathname: &CStr, mode: Permissions
+) -> OsResult<()> {
+	internal::fchmodat(into_raw_dirfd(dirfd), pathname, mode)
+}
+
+#[syscall_define(Fchmod)]
+pub fn fchmod(fd: BorrowedFd<'_>, mode: Permissions) -> OsResult<()>;
+
+/// Change the owner and group of a file. `None` leaves the id unchanged
+pub fn fchownat(
+	dirfd: Option<BorrowedFd<'_>>, pathname: &CStr, owner: Option<u32>, group: Option<u32>,
+	flags: BitFlags<AtFlag>
+) -> OsResult<()> {
+	internal::fchownat(
+		into_raw_dirfd(dirfd),
+		pathname,
+		owner.unwrap_or(u32::MAX),
+		group.unwrap_or(u32::MAX),
+		flags
+	)
+}
+
+/// Change the owner and group of an open file. `None` leaves the id unchanged
+pub fn fchown(fd: BorrowedFd<'_>, owner: Option<u32>, group: Option<u32>) -> OsResult<()> {
+	internal::fchown(fd, owner.unwrap_or(u32::MAX), group.unwrap_or(u32::MAX))
+}
+
+/// Set the access and modification times of a file, in that order. `None`
+/// sets both to the current time
+///
+/// See [`UTIME_NOW`] and [`UTIME_OMIT`]
+pub fn utimensat(
+	dirfd: Option<BorrowedFd<'_>>, pathname: &CStr, times: Option<&[TimeSpec; 2]>,
+	flags: BitFlags<AtFlag>
+) -> OsResult<()> {
+	internal::utimensat(
+		into_raw_dirfd(dirfd),
+		ptr!(pathname.as_ptr()).cast(),
+		times,
+		flags
+	)
+}
+
+pub fn futimens(fd: BorrowedFd<'_>, times: Option<&[TimeSpec; 2]>) -> OsResult<()> {
+	internal::utimensat(fd.as_raw_fd(), Ptr::null(), times, BitFlags::default())
+}
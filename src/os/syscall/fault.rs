@@ -0,0 +1,166 @@
+//! Per-thread syscall fault injection, for testing error paths
+//!
+//! ```
+//! let _guard = fault::inject(FaultRule::new(Read, Fault::Error(OsError::Intr)).nth(2));
+//!
+//! read(fd, buf)?; // succeeds
+//! read(fd, buf)?; // fails with `OsError::Intr`
+//! ```
+//!
+//! Only system calls made through this crate are affected
+
+use std::cell::{Cell, RefCell};
+
+use super::*;
+
+/// What happens to a matching system call
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Fault {
+	/// Fail without entering the kernel
+	Error(OsError),
+
+	/// Limit the length argument to at most this many bytes, causing a short
+	/// read or write. Only valid for `read`, `write`, `pread64`, `pwrite64`,
+	/// `sendto` and `recvfrom`
+	Short(usize)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FaultRule {
+	syscall: SyscallNumber,
+	fault: Fault,
+	skip: usize,
+	times: Option<usize>
+}
+
+impl FaultRule {
+	/// Inject `fault` into the next call to `syscall`
+	#[must_use]
+	pub const fn new(syscall: SyscallNumber, fault: Fault) -> Self {
+		Self { syscall, fault, skip: 0, times: Some(1) }
+	}
+
+	/// Inject into the `n`th call instead, counting from one
+	#[must_use]
+	pub const fn nth(mut self, n: usize) -> Self {
+		self.skip = n.saturating_sub(1);
+		self
+	}
+
+	/// Inject into `n` consecutive calls
+	#[must_use]
+	pub const fn times(mut self, n: usize) -> Self {
+		self.times = Some(n);
+		self
+	}
+
+	/// Inject into every call after the skipped ones
+	#[must_use]
+	pub const fn always(mut self) -> Self {
+		self.times = None;
+		self
+	}
+}
+
+/// Whether the third argument of `syscall` is a length in bytes
+const fn takes_length(syscall: SyscallNumber) -> bool {
+	matches!(
+		syscall,
+		Read | Write | Pread64 | Pwrite64 | Sendto | Recvfrom
+	)
+}
+
+thread_local! {
+	static RULES: RefCell<Vec<(u64, FaultRule)>> = const { RefCell::new(Vec::new()) };
+	static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Removes the rule it was returned for when dropped, if it hasn't been
+/// exhausted already
+#[must_use = "the fault is removed when the guard is dropped"]
+#[allow(missing_copy_implementations)]
+#[derive(Debug)]
+pub struct FaultGuard(u64);
+
+impl Drop for FaultGuard {
+	fn drop(&mut self) {
+		let _ = RULES.try_with(|rules| {
+			rules.borrow_mut().retain(|(id, _)| *id != self.0);
+		});
+	}
+}
+
+/// Add a rule for this thread. Earlier rules take priority when several
+/// match the same call
+///
+/// # Panics
+/// If the rule is a [`Fault::Short`] for a system call without a length
+/// argument
+pub fn inject(rule: FaultRule) -> FaultGuard {
+	assert!(
+		!matches!(rule.fault, Fault::Short(_)) || takes_length(rule.syscall),
+		"Short faults are not supported for {:?}",
+		rule.syscall
+	);
+
+	let id = NEXT_ID.replace(NEXT_ID.get().wrapping_add(1));
+
+	RULES.with_borrow_mut(|rules| rules.push((id, rule)));
+
+	FaultGuard(id)
+}
+
+/// Remove all rules for this thread
+pub fn clear() {
+	let _ = RULES.try_with(|rules| rules.borrow_mut().clear());
+}
+
+/// The number of rules on this thread that have yet to be exhausted
+#[must_use]
+pub fn pending() -> usize {
+	RULES.with_borrow(Vec::len)
+}
+
+/// Returns the result to use instead of making the system call, if any.
+/// May modify `args`
+#[allow(clippy::arithmetic_side_effects)]
+pub(super) fn intercept(num: i32, args: &mut [usize]) -> Option<isize> {
+	let fault = RULES
+		.try_with(|rules| {
+			let mut rules = rules.try_borrow_mut().ok()?;
+			let index = rules
+				.iter()
+				.position(|(_, rule)| rule.syscall as i32 == num)?;
+			let rule = &mut rules[index].1;
+
+			if rule.skip > 0 {
+				rule.skip -= 1;
+
+				return None;
+			}
+
+			let fault = rule.fault;
+
+			if let Some(times) = &mut rule.times {
+				*times = times.saturating_sub(1);
+
+				if *times == 0 {
+					rules.remove(index);
+				}
+			}
+
+			Some(fault)
+		})
+		.ok()??;
+
+	match fault {
+		Fault::Error(err) => Some(isize::from(i16::from(err)).wrapping_neg()),
+		Fault::Short(max) => {
+			if let Some(len) = args.get_mut(2) {
+				*len = (*len).min(max);
+			}
+
+			None
+		}
+	}
+}
@@ -0,0 +1,63 @@
+//! Replacements for the raw syscall stubs, which give the enabled hooks a
+//! chance to observe or alter each system call
+
+use super::*;
+
+/// # Safety
+/// the arguments must be valid for the system call
+unsafe fn call(num: i32, args: &[usize]) -> isize {
+	/* Safety: guaranteed by caller */
+	unsafe {
+		match *args {
+			[] => platform::syscall0(num),
+			[a] => platform::syscall1(num, a),
+			[a, b] => platform::syscall2(num, a, b),
+			[a, b, c] => platform::syscall3(num, a, b, c),
+			[a, b, c, d] => platform::syscall4(num, a, b, c, d),
+			[a, b, c, d, e] => platform::syscall5(num, a, b, c, d, e),
+			[a, b, c, d, e, f, ..] => platform::syscall6(num, a, b, c, d, e, f)
+		}
+	}
+}
+
+/// # Safety
+/// the arguments must be valid for the system call
+#[inline(never)]
+unsafe fn dispatch(num: i32, args: &mut [usize]) -> isize {
+	#[cfg(feature = "syscall-trace")]
+	let start = trace::start();
+
+	#[cfg(feature = "syscall-fault")]
+	let result = fault::intercept(num, args);
+
+	#[cfg(not(feature = "syscall-fault"))]
+	let result = None;
+
+	/* Safety: guaranteed by caller */
+	let result = result.unwrap_or_else(|| unsafe { call(num, args) });
+
+	#[cfg(feature = "syscall-trace")]
+	trace::finish(start, num, args, result);
+
+	result
+}
+
+macro_rules! define_stub {
+	($func:ident $(, $arg:ident)*) => {
+		/// # Safety
+		/// the arguments must be valid for the system call
+		#[inline(always)]
+		pub unsafe fn $func(num: i32 $(, $arg: usize)*) -> isize {
+			/* Safety: guaranteed by caller */
+			unsafe { dispatch(num, &mut [$($arg),*]) }
+		}
+	};
+}
+
+define_stub!(syscall0);
+define_stub!(syscall1, arg0);
+define_stub!(syscall2, arg0, arg1);
+define_stub!(syscall3, arg0, arg1, arg2);
+define_stub!(syscall4, arg0, arg1, arg2, arg3);
+define_stub!(syscall5, arg0, arg1, arg2, arg3, arg4);
+define_stub!(syscall6, arg0, arg1, arg2, arg3, arg4, arg5);
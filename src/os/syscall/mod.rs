@@ -6,6 +6,18 @@ import_sysdeps!();
 pub use platform::*;
 pub use SyscallNumber::*;
 
+#[cfg(feature = "syscall-fault")]
+pub mod fault;
+
+#[cfg(feature = "syscall-trace")]
+pub mod trace;
+
+#[cfg(any(feature = "syscall-fault", feature = "syscall-trace"))]
+mod interpose;
+
+#[cfg(any(feature = "syscall-fault", feature = "syscall-trace"))]
+pub use interpose::{syscall0, syscall1, syscall2, syscall3, syscall4, syscall5, syscall6};
+
 use super::error::*;
 use super::*;
 
@@ -0,0 +1,205 @@
+//! Per-thread recording of the system calls made through this crate
+//!
+//! Each call is kept in a ring buffer of the most recent [`CAPACITY`] calls,
+//! which can be logged at the `trace` level with [`dump`]. Calls made from
+//! signal handlers are not recorded
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::time::Instant;
+
+use super::*;
+use crate::os::signal_handler::in_handler;
+use crate::trace;
+
+pub const CAPACITY: usize = 256;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Record {
+	pub number: i32,
+	pub args: [usize; 6],
+	pub arg_count: u8,
+
+	/// The raw return value, negative on error
+	pub result: isize,
+	pub elapsed_nanos: u64
+}
+
+impl Record {
+	const EMPTY: Self = Self {
+		number: 0,
+		args: [0; 6],
+		arg_count: 0,
+		result: 0,
+		elapsed_nanos: 0
+	};
+
+	#[must_use]
+	pub fn syscall(&self) -> Option<SyscallNumber> {
+		SyscallNumber::from_i32(self.number)
+	}
+
+	#[must_use]
+	pub fn args(&self) -> &[usize] {
+		&self.args[0..self.arg_count as usize]
+	}
+
+	pub fn result(&self) -> OsResult<isize> {
+		result_from_int(self.result)
+	}
+}
+
+impl fmt::Debug for Record {
+	fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.syscall() {
+			Some(syscall) => write!(fmt, "{:?}(", syscall)?,
+			None => write!(fmt, "syscall_{}(", self.number)?
+		}
+
+		for (i, arg) in self.args().iter().enumerate() {
+			if i != 0 {
+				fmt.write_str(", ")?;
+			}
+
+			write!(fmt, "{:#x}", arg)?;
+		}
+
+		match self.result() {
+			Ok(result) => write!(fmt, ") = {}", result)?,
+			Err(err) => write!(fmt, ") = {:?}", err)?
+		}
+
+		write!(fmt, " <{} ns>", self.elapsed_nanos)
+	}
+}
+
+/// A fixed ring of records, so that recording never allocates
+struct Ring {
+	records: [Record; CAPACITY],
+
+	/// The index of the oldest record
+	head: usize,
+	len: usize,
+	capacity: usize
+}
+
+impl Ring {
+	fn iter(&self) -> impl Iterator<Item = &Record> {
+		(0..self.len).map(|i| &self.records[self.head.wrapping_add(i) % CAPACITY])
+	}
+
+	fn pop_front(&mut self) {
+		self.head = self.head.wrapping_add(1) % CAPACITY;
+		self.len = self.len.saturating_sub(1);
+	}
+
+	fn push_back(&mut self, record: Record) {
+		if self.capacity == 0 {
+			return;
+		}
+
+		if self.len >= self.capacity {
+			self.pop_front();
+		}
+
+		self.records[self.head.wrapping_add(self.len) % CAPACITY] = record;
+		self.len = self.len.wrapping_add(1);
+	}
+
+	fn clear(&mut self) {
+		self.head = 0;
+		self.len = 0;
+	}
+}
+
+thread_local! {
+	static RING: RefCell<Ring> = const {
+		RefCell::new(Ring {
+			records: [Record::EMPTY; CAPACITY],
+			head: 0,
+			len: 0,
+			capacity: CAPACITY
+		})
+	};
+
+	static ENABLED: Cell<bool> = const { Cell::new(true) };
+}
+
+/// Enable or disable recording for this thread. Enabled by default
+pub fn set_enabled(enabled: bool) {
+	ENABLED.set(enabled);
+}
+
+/// Set the number of records kept for this thread, up to [`CAPACITY`],
+/// discarding the oldest records if there are too many
+pub fn set_capacity(capacity: usize) {
+	RING.with_borrow_mut(|ring| {
+		ring.capacity = capacity.min(CAPACITY);
+
+		while ring.len > ring.capacity {
+			ring.pop_front();
+		}
+	});
+}
+
+/// The records for this thread, from oldest to newest
+#[must_use]
+pub fn records() -> Vec<Record> {
+	RING.with_borrow(|ring| ring.iter().copied().collect())
+}
+
+/// Remove and return the records for this thread, from oldest to newest
+#[must_use]
+pub fn take() -> Vec<Record> {
+	RING.with_borrow_mut(|ring| {
+		let records = ring.iter().copied().collect();
+
+		ring.clear();
+		records
+	})
+}
+
+/// Log the records for this thread at the `trace` level
+pub fn dump() {
+	for record in records() {
+		trace!("{:?}", record);
+	}
+}
+
+pub fn clear() {
+	RING.with_borrow_mut(Ring::clear);
+}
+
+pub(super) fn start() -> Option<Instant> {
+	/* system calls made by signal handlers are not recorded, as the
+	 * interrupted code may be in the middle of recording
+	 */
+	let active = ENABLED.try_with(Cell::get).unwrap_or(false) && !in_handler();
+
+	active.then(Instant::now)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+pub(super) fn finish(start: Option<Instant>, num: i32, args: &[usize], result: isize) {
+	let Some(start) = start else {
+		return;
+	};
+
+	let mut record = Record {
+		number: num,
+		args: [0; 6],
+		arg_count: args.len().min(6) as u8,
+		result,
+		elapsed_nanos: start.elapsed().as_nanos().try_into().unwrap_or(u64::MAX)
+	};
+
+	for (dest, arg) in record.args.iter_mut().zip(args) {
+		*dest = *arg;
+	}
+
+	let _ = RING.try_with(|ring| {
+		if let Ok(mut ring) = ring.try_borrow_mut() {
+			ring.push_back(record);
+		}
+	});
+}
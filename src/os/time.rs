@@ -1,4 +1,4 @@
-use super::error::result_from_libc;
+use super::error::result_from_int;
 use super::*;
 use crate::impls::OptionExt;
 
@@ -9,6 +9,33 @@ define_struct! {
 	}
 }
 
+impl TimeVal {
+	/// Converts to a `Duration`, treating negative values as zero
+	#[must_use]
+	#[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+	pub fn as_duration(&self) -> Duration {
+		if self.sec < 0 || self.micros < 0 {
+			return Duration::ZERO;
+		}
+
+		Duration::new(self.sec as u64, (self.micros as u32).saturating_mul(1000))
+	}
+
+	#[must_use]
+	#[allow(clippy::arithmetic_side_effects)]
+	pub fn saturating_sub(&self, rhs: &Self) -> Self {
+		let mut sec = self.sec.saturating_sub(rhs.sec);
+		let mut micros = self.micros.saturating_sub(rhs.micros);
+
+		if micros < 0 {
+			sec = sec.saturating_sub(1);
+			micros += 1_000_000;
+		}
+
+		Self { sec, micros }
+	}
+}
+
 define_struct! {
 	pub struct TimeSpec {
 		pub sec: i64,
@@ -95,19 +122,58 @@ define_enum! {
 	}
 }
 
-extern "C" {
-	fn clock_gettime(clock: ClockId, spec: &mut TimeSpec) -> i32;
-}
+#[syscall_define(ClockGettime)]
+pub fn clock_gettime(clock: ClockId, spec: &mut TimeSpec) -> OsResult<()>;
+
+#[syscall_define(Gettimeofday)]
+pub fn gettimeofday(tv: &mut TimeVal, tz: MutPtr<()>) -> OsResult<()>;
 
+/// Get the time of `clock`, using the vDSO if available and falling back to
+/// the system call. The vDSO is only used with the `std` feature
 pub fn time(clock: ClockId) -> Result<TimeSpec> {
 	let mut ts = TimeSpec { sec: 0, nanos: 0 };
 
-	/* Safety: &mut ts is a valid pointer */
-	result_from_libc(unsafe { clock_gettime(clock, &mut ts) } as isize)?;
+	#[cfg(feature = "std")]
+	let func = vdso::functions().clock_gettime;
+
+	#[cfg(not(feature = "std"))]
+	let func: Option<vdso::ClockGettimeFn> = None;
+
+	match func {
+		Some(func) => {
+			/* Safety: &mut ts is a valid pointer */
+			result_from_int(unsafe { func(clock, ptr!(&mut ts)) } as isize)?;
+		}
+
+		None => clock_gettime(clock, &mut ts)?
+	}
 
 	Ok(ts)
 }
 
+/// Get the wall clock time, using the vDSO if available and falling back to
+/// the system call. The vDSO is only used with the `std` feature
+pub fn time_of_day() -> OsResult<TimeVal> {
+	let mut tv = TimeVal { sec: 0, micros: 0 };
+
+	#[cfg(feature = "std")]
+	let func = vdso::functions().gettimeofday;
+
+	#[cfg(not(feature = "std"))]
+	let func: Option<vdso::GettimeofdayFn> = None;
+
+	match func {
+		Some(func) => {
+			/* Safety: &mut tv is a valid pointer, and the timezone is optional */
+			result_from_int(unsafe { func(ptr!(&mut tv), MutPtr::null()) } as isize)?;
+		}
+
+		None => gettimeofday(&mut tv, MutPtr::null())?
+	}
+
+	Ok(tv)
+}
+
 pub fn nanotime(clock: ClockId) -> Result<u64> {
 	let ts = time(clock)?;
 	let nanos = ts
@@ -0,0 +1,183 @@
+//! CPU topology discovery from `/sys/devices/system`
+
+use std::fs;
+use std::str::FromStr;
+
+use super::error::OsError;
+use super::sched::CpuSet;
+use super::*;
+
+const CPU_PATH: &str = "/sys/devices/system/cpu";
+const NODE_PATH: &str = "/sys/devices/system/node";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CacheType {
+	Data,
+	Instruction,
+	Unified
+}
+
+#[derive(Clone, Debug)]
+pub struct Cache {
+	pub level: u32,
+	pub ty: CacheType,
+
+	/// The size in bytes
+	pub size: Option<u64>,
+
+	/// The CPUs sharing this cache
+	pub shared_cpus: CpuSet
+}
+
+#[derive(Clone, Debug)]
+pub struct Cpu {
+	pub id: usize,
+	pub core_id: Option<u32>,
+	pub package_id: Option<u32>,
+	pub node: Option<u32>,
+
+	/// The SMT siblings of this CPU, including itself
+	pub siblings: CpuSet,
+	pub caches: Vec<Cache>
+}
+
+#[derive(Clone, Debug)]
+pub struct Node {
+	pub id: u32,
+	pub cpus: CpuSet
+}
+
+#[derive(Clone, Debug)]
+pub struct Topology {
+	pub online: CpuSet,
+	pub cpus: Vec<Cpu>,
+	pub nodes: Vec<Node>
+}
+
+fn read_string(path: &str) -> Option<String> {
+	fs::read_to_string(path).ok()
+}
+
+fn read_value<T: FromStr>(path: &str) -> Option<T> {
+	read_string(path)?.trim().parse().ok()
+}
+
+fn read_list(path: &str) -> Option<CpuSet> {
+	CpuSet::parse_list(&read_string(path)?)
+}
+
+/// Parse a size such as `32K` or `8M`
+#[allow(clippy::arithmetic_side_effects)]
+fn parse_size(size: &str) -> Option<u64> {
+	let size = size.trim();
+	let (digits, shift) = match size.as_bytes().last()? {
+		b'K' => (&size[..size.len() - 1], 10),
+		b'M' => (&size[..size.len() - 1], 20),
+		b'G' => (&size[..size.len() - 1], 30),
+		_ => (size, 0)
+	};
+
+	digits.parse::<u64>().ok()?.checked_shl(shift)
+}
+
+fn read_caches(cpu: usize) -> Vec<Cache> {
+	let mut caches = Vec::new();
+
+	for index in 0.. {
+		let path = format!("{}/cpu{}/cache/index{}", CPU_PATH, cpu, index);
+
+		let Some(level) = read_value(&format!("{}/level", path)) else {
+			break;
+		};
+
+		let ty = match read_string(&format!("{}/type", path)).as_deref().map(str::trim) {
+			Some("Data") => CacheType::Data,
+			Some("Instruction") => CacheType::Instruction,
+			_ => CacheType::Unified
+		};
+
+		caches.push(Cache {
+			level,
+			ty,
+			size: read_string(&format!("{}/size", path)).and_then(|size| parse_size(&size)),
+			shared_cpus: read_list(&format!("{}/shared_cpu_list", path)).unwrap_or_default()
+		});
+	}
+
+	caches
+}
+
+impl Topology {
+	/// Read the topology of the online CPUs
+	///
+	/// Information that the kernel does not expose is left empty
+	pub fn read() -> Result<Self> {
+		let online = read_list(&format!("{}/online", CPU_PATH)).ok_or(OsError::NoEnt)?;
+
+		let nodes: Vec<_> = read_list(&format!("{}/online", NODE_PATH))
+			.unwrap_or_default()
+			.iter()
+			.filter_map(|id| {
+				let cpus = read_list(&format!("{}/node{}/cpulist", NODE_PATH, id))?;
+
+				Some(Node { id: id.try_into().ok()?, cpus })
+			})
+			.collect();
+
+		let cpus = online
+			.iter()
+			.map(|id| {
+				let topology = format!("{}/cpu{}/topology", CPU_PATH, id);
+				let siblings = read_list(&format!("{}/thread_siblings_list", topology))
+					.unwrap_or_else(|| [id].into_iter().collect());
+
+				Cpu {
+					id,
+					core_id: read_value(&format!("{}/core_id", topology)),
+					package_id: read_value(&format!("{}/physical_package_id", topology)),
+					node: nodes
+						.iter()
+						.find(|node| node.cpus.contains(id))
+						.map(|node| node.id),
+					siblings,
+					caches: read_caches(id)
+				}
+			})
+			.collect();
+
+		Ok(Self { online, cpus, nodes })
+	}
+
+	#[must_use]
+	pub fn cpu(&self, id: usize) -> Option<&Cpu> {
+		self.cpus.iter().find(|cpu| cpu.id == id)
+	}
+
+	/// One CPU from each physical core in `allowed`, choosing the lowest
+	/// numbered SMT sibling
+	#[must_use]
+	pub fn physical_cores(&self, allowed: &CpuSet) -> CpuSet {
+		self.cpus
+			.iter()
+			.filter(|cpu| allowed.contains(cpu.id))
+			.filter(|cpu| {
+				cpu.siblings
+					.intersection(allowed)
+					.iter()
+					.next()
+					.map_or(true, |first| first == cpu.id)
+			})
+			.map(|cpu| cpu.id)
+			.collect()
+	}
+
+	/// The CPUs sharing the largest cache level with `cpu`
+	#[must_use]
+	pub fn last_level_cache_peers(&self, cpu: usize) -> Option<CpuSet> {
+		self.cpu(cpu)?
+			.caches
+			.iter()
+			.max_by_key(|cache| cache.level)
+			.map(|cache| cache.shared_cpus.clone())
+	}
+}
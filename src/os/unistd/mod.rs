@@ -42,3 +42,6 @@ pub fn read(fd: BorrowedFd<'_>, #[array] buf: MutRawBuf<'_>) -> OsResult<()>;
 
 #[syscall_define(Write)]
 pub fn write(fd: BorrowedFd<'_>, #[array] buf: RawBuf<'_>) -> OsResult<()>;
+
+#[syscall_define(Ftruncate)]
+pub fn ftruncate(fd: BorrowedFd<'_>, length: i64) -> OsResult<()>;
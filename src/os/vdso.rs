@@ -0,0 +1,407 @@
+//! Symbol lookup in the virtual dynamic shared object that the kernel maps
+//! into every process
+//!
+//! The vDSO exports functions such as `clock_gettime` that can be called
+//! without entering the kernel. Its location is found in the auxiliary vector,
+//! and its symbols are resolved by parsing the ELF image in memory, so that no
+//! dynamic loader or libc is needed.
+
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
+use super::prctl::{prctl, PrctlOption};
+use super::time::{ClockId, TimeSpec, TimeVal};
+use super::*;
+use crate::sync::atomic::{AtomicUsize, Ordering};
+
+/// The end of the auxiliary vector
+const AT_NULL: u64 = 0;
+
+/// Address of the vDSO's ELF header
+pub const AT_SYSINFO_EHDR: u64 = 33;
+
+const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+const ELF_CLASS_64: u8 = 2;
+
+const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+
+const DT_NULL: i64 = 0;
+const DT_HASH: i64 = 4;
+const DT_STRTAB: i64 = 5;
+const DT_SYMTAB: i64 = 6;
+const DT_GNU_HASH: i64 = 0x6fff_fef5;
+
+const STT_FUNC: u8 = 2;
+const STB_GLOBAL: u8 = 1;
+const STB_WEAK: u8 = 2;
+const SHN_UNDEF: u16 = 0;
+
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
+mod names {
+	pub const CLOCK_GETTIME: &[u8] = b"__vdso_clock_gettime";
+	pub const GETTIMEOFDAY: &[u8] = b"__vdso_gettimeofday";
+}
+
+#[cfg(all(feature = "std", target_arch = "aarch64"))]
+mod names {
+	pub const CLOCK_GETTIME: &[u8] = b"__kernel_clock_gettime";
+	pub const GETTIMEOFDAY: &[u8] = b"__kernel_gettimeofday";
+}
+
+define_struct! {
+	pub struct ElfHeader {
+		pub ident: [u8; 16],
+		pub ty: u16,
+		pub machine: u16,
+		pub version: u32,
+		pub entry: u64,
+		pub program_header_offset: u64,
+		pub section_header_offset: u64,
+		pub flags: u32,
+		pub header_size: u16,
+		pub program_header_entry_size: u16,
+		pub program_header_count: u16,
+		pub section_header_entry_size: u16,
+		pub section_header_count: u16,
+		pub section_name_index: u16
+	}
+}
+
+define_struct! {
+	pub struct ProgramHeader {
+		pub ty: u32,
+		pub flags: u32,
+		pub offset: u64,
+		pub vaddr: u64,
+		pub paddr: u64,
+		pub file_size: u64,
+		pub mem_size: u64,
+		pub align: u64
+	}
+}
+
+define_struct! {
+	pub struct Dynamic {
+		pub tag: i64,
+		pub value: u64
+	}
+}
+
+define_struct! {
+	pub struct Symbol {
+		pub name: u32,
+		pub info: u8,
+		pub other: u8,
+		pub section_index: u16,
+		pub value: u64,
+		pub size: u64
+	}
+}
+
+pub type ClockGettimeFn = unsafe extern "C" fn(ClockId, MutPtr<TimeSpec>) -> i32;
+pub type GettimeofdayFn = unsafe extern "C" fn(MutPtr<TimeVal>, MutPtr<()>) -> i32;
+
+/// A parsed vDSO image
+pub struct Vdso {
+	load_bias: usize,
+	string_table: Ptr<u8>,
+	symbol_table: Ptr<Symbol>,
+	symbol_count: usize
+}
+
+impl Vdso {
+	/// Parse the vDSO image whose ELF header is at `base`
+	///
+	/// # Safety
+	/// `base` must point to a valid, mapped ELF image that lives for the
+	/// rest of the program
+	#[allow(
+		clippy::arithmetic_side_effects,
+		clippy::cast_possible_truncation,
+		clippy::multiple_unsafe_ops_per_block
+	)]
+	pub unsafe fn parse(base: Ptr<()>) -> Option<Self> {
+		/* Safety: guaranteed by caller */
+		let header = unsafe { base.cast::<ElfHeader>().as_ref() };
+
+		if header.ident[0..4] != ELF_MAGIC || header.ident[4] != ELF_CLASS_64 {
+			return None;
+		}
+
+		/* Safety: the program headers are in the image */
+		let program_headers = unsafe {
+			base.cast::<u8>()
+				.add(header.program_header_offset as usize)
+				.cast::<ProgramHeader>()
+		};
+
+		let mut load_bias = None;
+		let mut dynamic = None;
+
+		for i in 0..header.program_header_count as usize {
+			/* Safety: `i` is in range */
+			let program_header = unsafe { program_headers.add(i).as_ref() };
+
+			match program_header.ty {
+				PT_LOAD if load_bias.is_none() => {
+					load_bias = Some(
+						base.addr()
+							.wrapping_add(program_header.offset as usize)
+							.wrapping_sub(program_header.vaddr as usize)
+					);
+				}
+
+				PT_DYNAMIC => dynamic = Some(program_header),
+				_ => ()
+			}
+		}
+
+		let (load_bias, dynamic) = (load_bias?, dynamic?);
+		let mut entry = Ptr::<Dynamic>::from_addr(load_bias.wrapping_add(dynamic.vaddr as usize));
+		let (mut string_table, mut symbol_table) = (None, None);
+		let (mut hash, mut gnu_hash) = (None, None);
+
+		loop {
+			/* Safety: the dynamic table is terminated by DT_NULL */
+			let dynamic = unsafe { entry.as_ref() };
+			let addr = load_bias.wrapping_add(dynamic.value as usize);
+
+			match dynamic.tag {
+				DT_NULL => break,
+				DT_STRTAB => string_table = Some(Ptr::from_addr(addr)),
+				DT_SYMTAB => symbol_table = Some(Ptr::from_addr(addr)),
+				DT_HASH => hash = Some(Ptr::<u32>::from_addr(addr)),
+				DT_GNU_HASH => gnu_hash = Some(Ptr::<u32>::from_addr(addr)),
+				_ => ()
+			}
+
+			/* Safety: not yet at the end of the table */
+			entry = unsafe { entry.add(1) };
+		}
+
+		let symbol_count = match (hash, gnu_hash) {
+			/* Safety: the second word of the hash table is the number of symbols */
+			(Some(hash), _) => (unsafe { hash.add(1).read() }) as usize,
+
+			/* Safety: the gnu hash table is valid */
+			(None, Some(gnu_hash)) => unsafe { gnu_hash_symbol_count(gnu_hash) },
+			(None, None) => return None
+		};
+
+		Some(Self {
+			load_bias,
+			string_table: string_table?,
+			symbol_table: symbol_table?,
+			symbol_count
+		})
+	}
+
+	/// Find the address of the exported function `name`
+	#[must_use]
+	#[allow(
+		clippy::cast_possible_truncation,
+		clippy::multiple_unsafe_ops_per_block
+	)]
+	pub fn lookup(&self, name: &[u8]) -> Option<Ptr<()>> {
+		for i in 0..self.symbol_count {
+			/* Safety: `i` is in range */
+			let symbol = unsafe { self.symbol_table.add(i).as_ref() };
+			let binding = symbol.info >> 4;
+
+			if symbol.info & 0xf != STT_FUNC ||
+				!matches!(binding, STB_GLOBAL | STB_WEAK) ||
+				symbol.section_index == SHN_UNDEF
+			{
+				continue;
+			}
+
+			/* Safety: names in the string table are nul terminated */
+			let symbol_name = unsafe {
+				CStr::from_ptr(self.string_table.add(symbol.name as usize).as_ptr().cast())
+			};
+
+			if symbol_name.to_bytes() == name {
+				return Some(Ptr::from_addr(
+					self.load_bias.wrapping_add(symbol.value as usize)
+				));
+			}
+		}
+
+		None
+	}
+}
+
+/* Safety: the vDSO is immutable and lives for the entire program */
+unsafe impl Send for Vdso {}
+
+/* Safety: see above */
+unsafe impl Sync for Vdso {}
+
+/// # Safety
+/// `table` must point to a valid gnu hash table
+#[allow(clippy::arithmetic_side_effects, clippy::multiple_unsafe_ops_per_block)]
+unsafe fn gnu_hash_symbol_count(table: Ptr<u32>) -> usize {
+	/* Safety: guaranteed by caller */
+	unsafe {
+		let bucket_count = table.read() as usize;
+		let symbol_offset = table.add(1).read() as usize;
+		let bloom_size = table.add(2).read() as usize;
+		let buckets = table.add(4).cast::<u64>().add(bloom_size).cast::<u32>();
+		let chains = buckets.add(bucket_count);
+
+		let Some(last) = (0..bucket_count)
+			.map(|i| buckets.add(i).read() as usize)
+			.max()
+			.filter(|last| *last >= symbol_offset)
+		else {
+			return symbol_offset;
+		};
+
+		/* walk the last chain until the end marker */
+		let mut index = last;
+
+		while chains.add(index - symbol_offset).read() & 1 == 0 {
+			index += 1;
+		}
+
+		index + 1
+	}
+}
+
+/// The most auxiliary vector entries read with `PR_GET_AUXV`. The kernel
+/// currently writes fewer than 32
+const AUXV_ENTRIES: usize = 64;
+
+/// The environment pointers on the initial stack, which the auxiliary vector
+/// follows. Zero if unknown
+static ENVP: AtomicUsize = AtomicUsize::new(0);
+
+/// glibc passes `argc`, `argv` and `envp` to the functions in `.init_array`,
+/// before `main` or anything else could change the environment
+#[cfg(target_env = "gnu")]
+#[used]
+#[link_section = ".init_array.00099"]
+static INIT_ENVP: extern "C" fn(i32, usize, usize) = {
+	extern "C" fn init(_: i32, _: usize, envp: usize) {
+		ENVP.store(envp, Ordering::Relaxed);
+	}
+
+	init
+};
+
+/// Read an entry from the auxiliary vector, as copied by the kernel with
+/// `PR_GET_AUXV`. Returns `None` if the kernel doesn't support it
+fn get_aux_value_prctl(key: u64) -> Option<u64> {
+	let mut auxv = [[0u64; 2]; AUXV_ENTRIES];
+
+	/* Safety: the buffer is valid for its size */
+	let size = unsafe {
+		prctl(
+			PrctlOption::GetAuxv,
+			ptr!(&mut auxv).addr(),
+			size_of_val(&auxv),
+			0,
+			0
+		)
+	}
+	.ok()?;
+
+	let len = usize::try_from(size)
+		.ok()?
+		.min(size_of_val(&auxv))
+		.checked_div(size_of::<[u64; 2]>())?;
+
+	auxv[..len]
+		.iter()
+		.find(|[ty, _]| *ty == key)
+		.map(|[_, value]| *value)
+}
+
+/// Read an entry from the auxiliary vector that follows the environment on
+/// the process's initial stack. Returns `None` if the environment pointers
+/// are unknown
+#[allow(clippy::multiple_unsafe_ops_per_block)]
+fn get_aux_value_stack(key: u64) -> Option<u64> {
+	let envp = ENVP.load(Ordering::Relaxed);
+
+	if envp == 0 {
+		return None;
+	}
+
+	/* Safety: the initial stack lives as long as the process. the environment
+	 * pointers end with a null pointer, and are followed by the auxiliary
+	 * vector, which ends with `AT_NULL`
+	 */
+	unsafe {
+		let mut env = Ptr::<usize>::from_addr(envp);
+
+		while env.read() != 0 {
+			env = env.add(1);
+		}
+
+		let mut entry = env.add(1).cast::<[u64; 2]>();
+
+		loop {
+			match entry.read() {
+				[AT_NULL, _] => return None,
+				[ty, value] if ty == key => return Some(value),
+				_ => entry = entry.add(1)
+			}
+		}
+	}
+}
+
+/// Read an entry from the auxiliary vector. Doesn't need `/proc` or the C
+/// library
+#[must_use]
+pub fn get_aux_value(key: u64) -> Option<u64> {
+	get_aux_value_prctl(key).or_else(|| get_aux_value_stack(key))
+}
+
+/// The vDSO functions used by the crate
+pub struct Functions {
+	pub clock_gettime: Option<ClockGettimeFn>,
+	pub gettimeofday: Option<GettimeofdayFn>
+}
+
+#[cfg(feature = "std")]
+#[allow(clippy::multiple_unsafe_ops_per_block)]
+fn load() -> Functions {
+	let mut functions = Functions { clock_gettime: None, gettimeofday: None };
+
+	let Some(base) = get_aux_value(AT_SYSINFO_EHDR).filter(|base| *base != 0) else {
+		return functions;
+	};
+
+	/* Safety: the kernel maps a valid vDSO at this address, for the lifetime of
+	 * the process
+	 */
+	#[allow(clippy::cast_possible_truncation)]
+	let Some(vdso) = (unsafe { Vdso::parse(Ptr::from_addr(base as usize)) }) else {
+		return functions;
+	};
+
+	/* Safety: the symbols have these signatures */
+	unsafe {
+		functions.clock_gettime = vdso
+			.lookup(names::CLOCK_GETTIME)
+			.map(|func| transmute::<Ptr<()>, ClockGettimeFn>(func));
+		functions.gettimeofday = vdso
+			.lookup(names::GETTIMEOFDAY)
+			.map(|func| transmute::<Ptr<()>, GettimeofdayFn>(func));
+	}
+
+	functions
+}
+
+/// Get the vDSO functions, resolving them on first use
+///
+/// Functions that could not be found are `None`, in which case the caller
+/// should fall back to the system call
+#[cfg(feature = "std")]
+pub fn functions() -> &'static Functions {
+	static FUNCTIONS: OnceLock<Functions> = OnceLock::new();
+
+	FUNCTIONS.get_or_init(load)
+}
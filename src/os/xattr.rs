@@ -0,0 +1,78 @@
+//! Extended attributes
+//!
+//! Each operation has a path variant, which follows symbolic links, an `l`
+//! variant, which operates on the link itself, and an `f` variant, which
+//! operates on an open file
+//!
+//! The get and list operations return the size of the value or list. If the
+//! buffer is empty, nothing is copied and the size needed is returned
+
+use super::*;
+
+define_enum! {
+	#[bitflags]
+	#[repr(u32)]
+	pub enum XattrFlag {
+		/// Fail with `OsError::Exist` if the attribute already exists
+		Create  = 1 << 0,
+
+		/// Fail with `OsError::NoData` if the attribute does not exist
+		Replace = 1 << 1
+	}
+}
+
+/// The largest size of an attribute value
+pub const XATTR_SIZE_MAX: usize = 65536;
+
+/// The largest size of an attribute name list
+pub const XATTR_LIST_MAX: usize = 65536;
+
+#[syscall_define(Getxattr)]
+pub fn getxattr(path: &CStr, name: &CStr, #[array] value: MutRawBuf<'_>) -> OsResult<usize>;
+
+#[syscall_define(Lgetxattr)]
+pub fn lgetxattr(path: &CStr, name: &CStr, #[array] value: MutRawBuf<'_>) -> OsResult<usize>;
+
+#[syscall_define(Fgetxattr)]
+pub fn fgetxattr(
+	fd: BorrowedFd<'_>, name: &CStr, #[array] value: MutRawBuf<'_>
+) -> OsResult<usize>;
+
+#[syscall_define(Setxattr)]
+pub fn setxattr(
+	path: &CStr, name: &CStr, #[array] value: RawBuf<'_>, flags: BitFlags<XattrFlag>
+) -> OsResult<()>;
+
+#[syscall_define(Lsetxattr)]
+pub fn lsetxattr(
+	path: &CStr, name: &CStr, #[array] value: RawBuf<'_>, flags: BitFlags<XattrFlag>
+) -> OsResult<()>;
+
+#[syscall_define(Fsetxattr)]
+pub fn fsetxattr(
+	fd: BorrowedFd<'_>, name: &CStr, #[array] value: RawBuf<'_>, flags: BitFlags<XattrFlag>
+) -> OsResult<()>;
+
+#[syscall_define(Listxattr)]
+pub fn listxattr(path: &CStr, #[array] list: MutRawBuf<'_>) -> OsResult<usize>;
+
+#[syscall_define(Llistxattr)]
+pub fn llistxattr(path: &CStr, #[array] list: MutRawBuf<'_>) -> OsResult<usize>;
+
+#[syscall_define(Flistxattr)]
+pub fn flistxattr(fd: BorrowedFd<'_>, #[array] list: MutRawBuf<'_>) -> OsResult<usize>;
+
+#[syscall_define(Removexattr)]
+pub fn removexattr(path: &CStr, name: &CStr) -> OsResult<()>;
+
+#[syscall_define(Lremovexattr)]
+pub fn lremovexattr(path: &CStr, name: &CStr) -> OsResult<()>;
+
+#[syscall_define(Fremovexattr)]
+pub fn fremovexattr(fd: BorrowedFd<'_>, name: &CStr) -> OsResult<()>;
+
+/// Split a list returned by [`listxattr`] into names
+pub fn names(list: &[u8]) -> impl Iterator<Item = &CStr> {
+	list.split_inclusive(|byte| *byte == 0)
+		.filter_map(|name| CStr::from_bytes_with_nul(name).ok())
+}
@@ -1,10 +1,11 @@
-use std::cmp;
-use std::fmt::{self, Debug, Formatter, Result};
-use std::num::NonZeroUsize;
-use std::ops::{Deref, DerefMut};
-use std::ptr::{self as pointer, null_mut, slice_from_raw_parts_mut};
-use std::rc::Rc;
-use std::sync::Arc;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use core::cmp;
+use core::fmt::{self, Debug, Formatter, Result};
+use core::num::NonZeroUsize;
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self as pointer, null_mut, slice_from_raw_parts_mut};
 
 use crate::macros::{assert_unsafe_precondition, sealed_trait, wrapper_functions};
 
@@ -14,7 +15,7 @@ pub mod non_null;
 pub mod pin;
 pub mod ptr;
 
-pub use std::mem::offset_of;
+pub use core::mem::offset_of;
 
 #[doc(inline)]
 pub use non_null::*;
@@ -27,7 +28,7 @@ pub use ptr::*;
 macro_rules! container_of {
 	($ptr:expr, $type:ty => $field:ident) => {
 		$crate::pointer::Pointer::cast::<u8>($ptr)
-			.sub(::std::mem::offset_of!($type, $field))
+			.sub(::core::mem::offset_of!($type, $field))
 			.cast::<$type>()
 	};
 }
@@ -45,7 +45,7 @@ impl<T: ?Sized, const MUT: bool> NonNullPtr<T, MUT> {
 	}
 
 	/// # Safety
-	/// Caller must enforce aliasing rules. See std::ptr::as_ref
+	/// Caller must enforce aliasing rules. See core::ptr::as_ref
 	#[must_use]
 	#[allow(clippy::missing_const_for_fn)]
 	pub unsafe fn as_ref<'a>(self) -> &'a T {
@@ -138,7 +138,7 @@ impl<T: ?Sized> MutNonNull<T> {
 	}
 
 	/// # Safety
-	/// Caller must enforce aliasing rules. See std::ptr::as_ref
+	/// Caller must enforce aliasing rules. See core::ptr::as_ref
 	#[must_use]
 	pub unsafe fn as_mut<'a>(self) -> &'a mut T {
 		/* Safety: guaranteed by caller */
@@ -35,7 +35,7 @@ impl<T: ?Sized, const MUT: bool> Pointer<T, MUT> {
 	}
 
 	/// # Safety
-	/// Caller must enforce aliasing rules. See std::ptr::as_ref
+	/// Caller must enforce aliasing rules. See core::ptr::as_ref
 	#[must_use]
 	#[allow(clippy::missing_const_for_fn)]
 	pub unsafe fn as_ref<'a>(self) -> &'a T {
@@ -126,7 +126,7 @@ impl<T: ?Sized> MutPtr<T> {
 	}
 
 	/// # Safety
-	/// Caller must enforce aliasing rules. See std::ptr::as_ref
+	/// Caller must enforce aliasing rules. See core::ptr::as_ref
 	#[must_use]
 	pub unsafe fn as_mut<'a>(self) -> &'a mut T {
 		/* Safety: guaranteed by caller */
@@ -146,14 +146,14 @@ impl<T> MutPtr<T> {
 	}
 
 	/// # Safety
-	/// See [`std::ptr::copy`]
+	/// See [`core::ptr::copy`]
 	pub unsafe fn copy_from(self, src: Ptr<T>, count: usize) {
 		/* Safety: guaranteed by caller */
 		unsafe { self.ptr().copy_from(src.ptr, count) }
 	}
 
 	/// # Safety
-	/// See [`std::ptr::copy`]
+	/// See [`core::ptr::copy`]
 	pub unsafe fn copy_from_nonoverlapping(self, src: Ptr<T>, count: usize) {
 		/* Safety: guaranteed by caller */
 		unsafe { self.ptr().copy_from_nonoverlapping(src.ptr, count) }
@@ -236,14 +236,14 @@ impl<T, const MUT: bool> Pointer<[T], MUT> {
 
 impl<T> MutPtr<[T]> {
 	/// # Safety
-	/// See [`std::ptr::copy`]
+	/// See [`core::ptr::copy`]
 	pub unsafe fn copy_from(self, src: Ptr<T>, count: usize) {
 		/* Safety: guaranteed by caller */
 		unsafe { self.cast::<T>().copy_from(src, count) }
 	}
 
 	/// # Safety
-	/// See [`std::ptr::copy`]
+	/// See [`core::ptr::copy`]
 	pub unsafe fn copy_from_nonoverlapping(self, src: Ptr<T>, count: usize) {
 		/* Safety: guaranteed by caller */
 		unsafe { self.cast::<T>().copy_from_nonoverlapping(src, count) }
@@ -336,11 +336,11 @@ macro_rules! ptr {
 	};
 
 	(&$value:expr) => {
-		$crate::pointer::Pointer::from(::std::ptr::addr_of!($value))
+		$crate::pointer::Pointer::from(::core::ptr::addr_of!($value))
 	};
 
 	(&mut $value:expr) => {
-		$crate::pointer::Pointer::from(::std::ptr::addr_of_mut!($value))
+		$crate::pointer::Pointer::from(::core::ptr::addr_of_mut!($value))
 	};
 
 	(!null &$value:expr) => {
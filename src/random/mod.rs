@@ -0,0 +1,208 @@
+//! Fast, non-cryptographic pseudo random number generation
+//!
+//! Suitable for hash seeds, backoff jitter, and identifiers that only need to
+//! be unique. Use [`crate::os::random`] when the output must be unpredictable
+
+use std::cell::Cell;
+use std::mem::size_of;
+use std::ops::Range;
+
+use crate::macros::{macro_each, sealed_trait};
+use crate::os::random::random_nonblocking;
+use crate::os::time::{nanotime, ClockId};
+use crate::pointer::*;
+
+sealed_trait!();
+
+/// An integer type that can be sampled uniformly from a range
+pub trait SampleUniform: Sealed + Copy {
+	#[doc(hidden)]
+	fn sample(rng: &mut WyRand, range: Range<Self>) -> Self;
+}
+
+macro_rules! sample_impl {
+	(($type:ty, $unsigned:ty)) => {
+		impl Sealed for $type {}
+
+		impl SampleUniform for $type {
+			#[allow(
+				clippy::cast_possible_truncation,
+				clippy::cast_possible_wrap,
+				clippy::cast_sign_loss,
+				clippy::unnecessary_cast
+			)]
+			fn sample(rng: &mut WyRand, range: Range<Self>) -> Self {
+				assert!(range.start < range.end, "Cannot sample an empty range");
+
+				let width = range.end.wrapping_sub(range.start) as $unsigned as u64;
+				let offset = rng.bounded(width) as $unsigned;
+
+				range.start.wrapping_add(offset as $type)
+			}
+		}
+	};
+}
+
+macro_each!(
+	sample_impl,
+	(u8, u8),
+	(u16, u16),
+	(u32, u32),
+	(u64, u64),
+	(usize, usize),
+	(i8, u8),
+	(i16, u16),
+	(i32, u32),
+	(i64, u64),
+	(isize, usize)
+);
+
+/// The wyrand generator. Passes BigCrush and PractRand, and produces a new
+/// value in a handful of instructions
+#[derive(Clone, Copy, Debug)]
+pub struct WyRand {
+	state: u64
+}
+
+impl WyRand {
+	#[must_use]
+	pub const fn new(seed: u64) -> Self {
+		Self { state: seed }
+	}
+
+	/// Creates a new generator seeded from the operating system's random
+	/// source
+	///
+	/// Never blocks. If no random bytes are available, the seed is derived
+	/// from the current time instead
+	#[must_use]
+	pub fn from_entropy() -> Self {
+		let mut seed = [0u8; size_of::<u64>()];
+
+		if random_nonblocking(&mut seed).is_ok() {
+			return Self::new(u64::from_ne_bytes(seed));
+		}
+
+		let time = nanotime(ClockId::Monotonic).unwrap_or_default();
+		let mut rng = Self::new(time ^ ptr!(&seed).addr() as u64);
+
+		Self::new(rng.next_u64())
+	}
+
+	#[must_use]
+	#[allow(clippy::cast_possible_truncation)]
+	pub fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0xa076_1d64_78bd_642f);
+
+		let value =
+			u128::from(self.state).wrapping_mul(u128::from(self.state ^ 0xe703_7ed1_a0b4_28db));
+
+		((value >> 64) as u64) ^ (value as u64)
+	}
+
+	#[must_use]
+	#[allow(clippy::cast_possible_truncation)]
+	pub fn next_u32(&mut self) -> u32 {
+		(self.next_u64() >> 32) as u32
+	}
+
+	/// Returns `true` with a probability of one half
+	#[must_use]
+	pub fn next_bool(&mut self) -> bool {
+		self.next_u64() >> 63 != 0
+	}
+
+	/// Returns a uniformly distributed value in `0..bound`, using Lemire's
+	/// nearly divisionless method
+	#[allow(clippy::cast_possible_truncation, clippy::arithmetic_side_effects)]
+	fn bounded(&mut self, bound: u64) -> u64 {
+		let mut product = u128::from(self.next_u64()) * u128::from(bound);
+
+		if (product as u64) < bound {
+			let threshold = bound.wrapping_neg() % bound;
+
+			while (product as u64) < threshold {
+				product = u128::from(self.next_u64()) * u128::from(bound);
+			}
+		}
+
+		(product >> 64) as u64
+	}
+
+	/// Returns a uniformly distributed value in `range`
+	///
+	/// # Panics
+	/// If the range is empty
+	#[must_use]
+	pub fn gen_range<T: SampleUniform>(&mut self, range: Range<T>) -> T {
+		T::sample(self, range)
+	}
+
+	pub fn fill_bytes(&mut self, buf: &mut [u8]) {
+		let mut chunks = buf.chunks_exact_mut(size_of::<u64>());
+
+		for chunk in &mut chunks {
+			chunk.copy_from_slice(&self.next_u64().to_ne_bytes());
+		}
+
+		let remainder = chunks.into_remainder();
+
+		if !remainder.is_empty() {
+			let bytes = self.next_u64().to_ne_bytes();
+
+			remainder.copy_from_slice(&bytes[0..remainder.len()]);
+		}
+	}
+
+	/// Shuffles the slice in place using the Fisher-Yates algorithm
+	#[allow(clippy::arithmetic_side_effects)]
+	pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+		for i in (1..slice.len()).rev() {
+			slice.swap(i, self.gen_range(0..i + 1));
+		}
+	}
+}
+
+thread_local! {
+	static THREAD_RNG: Cell<WyRand> = Cell::new(WyRand::from_entropy());
+}
+
+/// Runs `func` with this thread's generator, which is lazily seeded from the
+/// operating system's random source
+pub fn with_thread_rng<F, Output>(func: F) -> Output
+where
+	F: FnOnce(&mut WyRand) -> Output
+{
+	THREAD_RNG.with(|cell| {
+		let mut rng = cell.get();
+		let output = func(&mut rng);
+
+		cell.set(rng);
+		output
+	})
+}
+
+/// See [`WyRand::next_u64`]
+#[must_use]
+pub fn random_u64() -> u64 {
+	with_thread_rng(WyRand::next_u64)
+}
+
+/// See [`WyRand::gen_range`]
+///
+/// # Panics
+/// If the range is empty
+#[must_use]
+pub fn gen_range<T: SampleUniform>(range: Range<T>) -> T {
+	with_thread_rng(|rng| rng.gen_range(range))
+}
+
+/// See [`WyRand::fill_bytes`]
+pub fn fill_bytes(buf: &mut [u8]) {
+	with_thread_rng(|rng| rng.fill_bytes(buf));
+}
+
+/// See [`WyRand::shuffle`]
+pub fn shuffle<T>(slice: &mut [T]) {
+	with_thread_rng(|rng| rng.shuffle(slice));
+}
@@ -1,10 +1,14 @@
-use std::fmt::Arguments;
+use core::fmt::Arguments;
+#[cfg(feature = "std")]
 use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
 
+#[cfg(feature = "log")]
 use crate::log::*;
 
+#[cfg(feature = "std")]
 pub type MaybePanic<T> = std::thread::Result<T>;
 
+#[cfg(feature = "std")]
 pub fn catch_unwind_safe<F, Output>(func: F) -> MaybePanic<Output>
 where
 	F: FnOnce() -> Output
@@ -12,18 +16,44 @@ where
 	catch_unwind(AssertUnwindSafe(func))
 }
 
+/// Without the `std` feature, there is no `abort`, so this panics again
+/// while the first panic unwinds, which aborts
 #[track_caller]
 #[cold]
 #[inline(never)]
 pub fn panic_nounwind(fmt: Arguments<'_>) -> ! {
-	print_panic(None, fmt);
-	print_fatal(format_args!("Non unwinding panic, aborting"));
+	#[cfg(feature = "log")]
+	{
+		print_panic(None, fmt);
+		print_fatal(format_args!("Non unwinding panic, aborting"));
+	}
+
+	#[cfg(all(feature = "std", not(feature = "log")))]
+	let _ = fmt;
 
+	#[cfg(feature = "std")]
 	std::process::abort();
+
+	#[cfg(not(feature = "std"))]
+	#[allow(clippy::panic)]
+	{
+		struct Abort;
+
+		impl Drop for Abort {
+			fn drop(&mut self) {
+				panic!("Panicked during a non unwinding panic, aborting");
+			}
+		}
+
+		let _abort = Abort;
+
+		panic!("Non unwinding panic: {}", fmt);
+	}
 }
 
 /// # Panics
 /// resumes the panic if `result` is an `Err`
+#[cfg(feature = "std")]
 pub fn join<T>(result: MaybePanic<T>) -> T {
 	match result {
 		Ok(ok) => ok,
@@ -36,12 +66,12 @@ pub fn call_no_unwind<F, Output>(func: F) -> Output
 where
 	F: FnOnce() -> Output
 {
-	#[cfg(debug_assertions)]
+	#[cfg(all(debug_assertions, feature = "std"))]
 	match catch_unwind_safe(func) {
 		Ok(ok) => ok,
 		Err(_) => crate::macros::panic_nounwind!("Function that must never panic panicked")
 	}
 
-	#[cfg(not(debug_assertions))]
+	#[cfg(not(all(debug_assertions, feature = "std")))]
 	func()
 }
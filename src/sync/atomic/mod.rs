@@ -1,4 +1,9 @@
 pub mod ptr;
 
+#[doc(inline)]
+pub use core::sync::atomic::{
+	fence, AtomicBool, AtomicI64, AtomicU32, AtomicU64, AtomicUsize, Ordering
+};
+
 #[doc(inline)]
 pub use ptr::*;
@@ -1,5 +1,5 @@
-use std::mem::transmute;
-use std::sync::atomic;
+use core::mem::transmute;
+use core::sync::atomic;
 
 use crate::pointer::*;
 
@@ -1,5 +1,6 @@
-use std::hint::spin_loop;
-use std::thread::yield_now;
+use core::hint::spin_loop;
+
+use super::yield_now;
 
 const SPIN_LIMIT: u16 = 6;
 const YIELD_LIMIT: u16 = 10;
@@ -26,6 +27,16 @@ impl Backoff {
 		}
 	}
 
+	#[cfg(feature = "random")]
+	#[allow(clippy::arithmetic_side_effects)]
+	fn spin_jitter_internal(&self) {
+		let limit: u32 = 1 << self.step.min(SPIN_LIMIT);
+
+		for _ in 0..crate::random::gen_range(limit / 2..limit + 1) {
+			spin_loop();
+		}
+	}
+
 	#[allow(clippy::arithmetic_side_effects)]
 	pub fn spin(&mut self) {
 		self.spin_internal();
@@ -35,6 +46,18 @@ impl Backoff {
 		}
 	}
 
+	/// Same as [`Backoff::spin`], except the number of spins is randomized, so
+	/// that threads contending on the same value don't retry in lockstep
+	#[cfg(feature = "random")]
+	#[allow(clippy::arithmetic_side_effects)]
+	pub fn spin_jitter(&mut self) {
+		self.spin_jitter_internal();
+
+		if self.step <= SPIN_LIMIT {
+			self.step += 1;
+		}
+	}
+
 	#[allow(clippy::arithmetic_side_effects)]
 	pub fn snooze(&mut self) {
 		if self.step < SPIN_LIMIT {
@@ -48,6 +71,22 @@ impl Backoff {
 		}
 	}
 
+	/// Same as [`Backoff::snooze`], except the number of spins is randomized.
+	/// See [`Backoff::spin_jitter`]
+	#[cfg(feature = "random")]
+	#[allow(clippy::arithmetic_side_effects)]
+	pub fn snooze_jitter(&mut self) {
+		if self.step < SPIN_LIMIT {
+			self.spin_jitter_internal();
+		} else {
+			yield_now();
+		}
+
+		if self.step <= YIELD_LIMIT {
+			self.step += 1;
+		}
+	}
+
 	#[must_use]
 	pub const fn is_completed(&self) -> bool {
 		self.step > YIELD_LIMIT
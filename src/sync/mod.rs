@@ -1,9 +1,11 @@
 pub mod atomic;
 pub mod backoff;
 pub mod cache_padded;
+#[cfg(feature = "std")]
 pub mod poison;
 pub mod spin_lock;
 pub mod spin_mutex;
+pub mod work_queue;
 
 #[doc(inline)]
 pub use backoff::*;
@@ -13,3 +15,20 @@ pub use cache_padded::*;
 pub use spin_lock::*;
 #[doc(inline)]
 pub use spin_mutex::{SpinMutex, SpinMutexGuard};
+#[doc(inline)]
+pub use work_queue::{Stealer, WorkQueue};
+
+/// Give up the rest of this thread's time slice
+///
+/// Without the `std` feature, this uses `sched_yield` if the `os` feature
+/// is enabled, and spins once otherwise
+pub fn yield_now() {
+	#[cfg(feature = "std")]
+	std::thread::yield_now();
+
+	#[cfg(all(not(feature = "std"), feature = "os"))]
+	let _ = crate::os::sched::sched_yield();
+
+	#[cfg(not(any(feature = "std", feature = "os")))]
+	core::hint::spin_loop();
+}
@@ -1,6 +1,7 @@
-use std::hint::spin_loop;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread::yield_now;
+use core::hint::spin_loop;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use super::yield_now;
 
 /// A spin lock for when the critical section is short and predictable
 pub struct SpinLock(AtomicBool);
@@ -1,6 +1,6 @@
-use std::fmt::{Debug, Formatter, Result};
-use std::ops::{Deref, DerefMut};
-use std::result;
+use core::fmt::{Debug, Formatter, Result};
+use core::ops::{Deref, DerefMut};
+use core::result;
 
 use super::*;
 use crate::cell::UnsafeCell;
@@ -0,0 +1,234 @@
+//! A bounded lock-free queue for work stealing
+//!
+//! Values are only pushed by the owning [`WorkQueue`], and taken in the order
+//! that they were pushed by the owner or by any number of [`Stealer`]s on
+//! other threads
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+
+use super::atomic::{AtomicUsize, Ordering};
+use super::{Backoff, CachePadded};
+use crate::cell::UnsafeCell;
+use crate::pointer::*;
+
+struct Inner<T> {
+	/// The index of the next value to take. Advanced by the owner and the
+	/// stealers
+	front: CachePadded<AtomicUsize>,
+
+	/// The index of the next slot to push to. Only written by the owner
+	back: CachePadded<AtomicUsize>,
+
+	slots: Box<[UnsafeCell<MaybeUninit<T>>]>
+}
+
+impl<T> Inner<T> {
+	#[allow(clippy::expect_used, clippy::missing_panics_doc)]
+	fn new(mut capacity: usize) -> Self {
+		assert!(capacity != 0, "Cannot create a zero sized queue");
+
+		capacity = capacity.checked_next_power_of_two().expect("Queue too big");
+
+		let mut slots = Vec::with_capacity(capacity);
+
+		for _ in 0..capacity {
+			slots.push(UnsafeCell::new(MaybeUninit::uninit()));
+		}
+
+		Self {
+			front: CachePadded(AtomicUsize::new(0)),
+			back: CachePadded(AtomicUsize::new(0)),
+			slots: slots.into_boxed_slice()
+		}
+	}
+
+	fn slot(&self, index: usize) -> MutPtr<MaybeUninit<T>> {
+		#[allow(clippy::arithmetic_side_effects)]
+		let mask = self.slots.len() - 1;
+
+		/* Safety: masked */
+		unsafe { self.slots.get_unchecked(index & mask) }.get()
+	}
+
+	fn len(&self) -> usize {
+		/* front never passes back, so load it first */
+		let front = self.front.load(Ordering::SeqCst);
+		let back = self.back.load(Ordering::SeqCst);
+
+		back.wrapping_sub(front)
+	}
+
+	/// # Safety
+	/// Must only be called by the owner
+	unsafe fn push(&self, value: T) -> Result<(), T> {
+		let back = self.back.load(Ordering::Relaxed);
+		let front = self.front.load(Ordering::Acquire);
+
+		if back.wrapping_sub(front) >= self.slots.len() {
+			return Err(value);
+		}
+
+		/* Safety: the slot is empty. stealers only read it after the store
+		 * below
+		 */
+		unsafe { ptr!(self.slot(back)=>write(value)) };
+
+		self.back.store(back.wrapping_add(1), Ordering::SeqCst);
+
+		Ok(())
+	}
+
+	#[allow(clippy::multiple_unsafe_ops_per_block)]
+	fn take(&self) -> Option<T> {
+		let mut backoff = Backoff::new();
+
+		loop {
+			let front = self.front.load(Ordering::SeqCst);
+			let back = self.back.load(Ordering::Acquire);
+
+			/* capacity can't be greater than isize::MAX */
+			#[allow(clippy::cast_possible_wrap)]
+			if back.wrapping_sub(front) as isize <= 0 {
+				return None;
+			}
+
+			/* Safety: the slot was initialized before `back` was advanced. if
+			 * another thread takes this value first, the owner may overwrite
+			 * the slot while we read it, but then the exchange below fails and
+			 * the copy is discarded without being dropped
+			 */
+			let value = unsafe { self.slot(front).ptr().read_volatile() };
+
+			if self
+				.front
+				.compare_exchange(
+					front,
+					front.wrapping_add(1),
+					Ordering::SeqCst,
+					Ordering::Relaxed
+				)
+				.is_ok()
+			{
+				/* Safety: we own the value now */
+				return Some(unsafe { value.assume_init() });
+			}
+
+			backoff.spin();
+		}
+	}
+}
+
+impl<T> Drop for Inner<T> {
+	fn drop(&mut self) {
+		let mut front = *self.front.get_mut();
+		let back = *self.back.get_mut();
+
+		while front != back {
+			/* Safety: every slot between front and back is initialized */
+			unsafe { ptr!(self.slot(front)=>assume_init_drop()) };
+
+			front = front.wrapping_add(1);
+		}
+	}
+}
+
+/* Safety: values are moved between threads, and each is only accessed by the
+ * thread that took it
+ */
+unsafe impl<T: Send> Send for Inner<T> {}
+
+/* Safety: see above */
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+/// The owning end of a work stealing queue, which is the only one that can
+/// push values
+pub struct WorkQueue<T> {
+	inner: Arc<Inner<T>>,
+
+	/// Only one thread may push at a time
+	phantom: PhantomData<Cell<()>>
+}
+
+impl<T> WorkQueue<T> {
+	/// Creates a queue that holds up to `capacity` values, rounded up to the
+	/// next power of two
+	///
+	/// # Panics
+	/// If `capacity` is zero or too big
+	#[must_use]
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			inner: Arc::new(Inner::new(capacity)),
+			phantom: PhantomData
+		}
+	}
+
+	/// Push a value to the back of the queue, or return it if the queue is full
+	pub fn push(&self, value: T) -> Result<(), T> {
+		/* Safety: the queue is not shared, so we are the owner */
+		unsafe { self.inner.push(value) }
+	}
+
+	/// Take the value at the front of the queue
+	pub fn pop(&self) -> Option<T> {
+		self.inner.take()
+	}
+
+	/// Create a handle for taking values from another thread
+	#[must_use]
+	pub fn stealer(&self) -> Stealer<T> {
+		Stealer { inner: self.inner.clone() }
+	}
+
+	#[must_use]
+	pub fn capacity(&self) -> usize {
+		self.inner.slots.len()
+	}
+
+	/// Count the number of values in the queue. Under contention, this may
+	/// report a higher count than actual
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.inner.len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
+/// A handle for taking values from the front of a [`WorkQueue`], from any
+/// thread
+pub struct Stealer<T> {
+	inner: Arc<Inner<T>>
+}
+
+impl<T> Stealer<T> {
+	/// Take the value at the front of the queue
+	pub fn steal(&self) -> Option<T> {
+		self.inner.take()
+	}
+
+	/// See [`WorkQueue::len`]
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.inner.len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
+impl<T> Clone for Stealer<T> {
+	fn clone(&self) -> Self {
+		Self { inner: self.inner.clone() }
+	}
+}
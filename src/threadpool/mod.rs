@@ -1,7 +1,7 @@
 use std::mem::transmute;
 use std::os::unix::thread::JoinHandleExt;
 use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
-use std::sync::{Arc, Condvar, Mutex};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
 use std::thread;
 
 use crate::cell::UnsafeCell;
@@ -9,8 +9,9 @@ use crate::closure::DynFnOnce;
 use crate::container::intrusive::linked_list::*;
 use crate::error::*;
 use crate::future::*;
+use crate::os::sched::{available_parallelism, sched_setaffinity, CpuSet};
 use crate::os::signal::*;
-use crate::os::unistd::{get_system_configuration, SystemConfiguration};
+use crate::os::signal_handler::{register, Registration};
 use crate::pointer::*;
 use crate::runtime::call_no_unwind;
 use crate::{debug, error, trace, warn};
@@ -198,22 +199,25 @@ const INTERRUPT_SIGNAL: i32 = (SIGRTMIN + Signal::Interrupt as u32) as i32;
 pub struct ThreadPool {
 	workers: Box<[Arc<Worker>]>,
 	queue: Pinned<Arc<Queue>>,
-	interruptible: bool
+	interrupt: bool
 }
 
 impl ThreadPool {
+	/// Registers the interrupt handler once per process. Every thread pool
+	/// shares the registration
 	fn install_interrupt_handler() -> OsResult<()> {
-		const extern "C" fn handler(_: i32) {}
-
-		let mut action = SigAction::default();
-
-		action.handler.handler = Some(handler);
-
-		sig_action(INTERRUPT_SIGNAL, Some(&action), None)
+		static INSTALLED: OnceLock<OsResult<()>> = OnceLock::new();
+
+		*INSTALLED.get_or_init(|| {
+			/* Safety: the handler does nothing. the signal only interrupts a
+			 * blocking system call
+			 */
+			unsafe { register(INTERRUPT_SIGNAL, |_| ()) }.map(Registration::forget)
+		})
 	}
 
 	fn interrupt_worker(&self, worker: &Worker) {
-		if !self.interruptible {
+		if !self.interrupt {
 			return;
 		}
 
@@ -224,8 +228,17 @@ impl ThreadPool {
 		}
 	}
 
-	#[allow(clippy::missing_panics_doc, clippy::expect_used)]
 	pub fn new(max_workers: usize) -> Result<Self> {
+		Self::create(max_workers, None)
+	}
+
+	/// Create a thread pool whose workers only run on the CPUs in `cpus`
+	pub fn new_with_affinity(max_workers: usize, cpus: CpuSet) -> Result<Self> {
+		Self::create(max_workers, Some(cpus))
+	}
+
+	#[allow(clippy::missing_panics_doc, clippy::expect_used)]
+	fn create(max_workers: usize, affinity: Option<CpuSet>) -> Result<Self> {
 		let queue = Queue::new(max_workers).pin_arc();
 		let mut threads = Vec::with_capacity(max_workers);
 		let mut error = None;
@@ -233,10 +246,19 @@ impl ThreadPool {
 		for i in 0..max_workers {
 			let worker = Arc::new(Worker::new(queue.clone()));
 			let worker_clone = worker.clone();
+			let affinity = affinity.clone();
 
 			let result = thread::Builder::new()
 				.name(format!("xx-tp-wrk-{}", i))
-				.spawn(move || worker.run());
+				.spawn(move || {
+					if let Some(cpus) = &affinity {
+						if let Err(err) = sched_setaffinity(None, cpus) {
+							warn!("== Failed to set worker affinity {:?}", err);
+						}
+					}
+
+					worker.run();
+				});
 
 			let handle = match result {
 				Ok(handle) => handle,
@@ -256,17 +278,16 @@ impl ThreadPool {
 		let mut this = Self {
 			workers: threads.into_boxed_slice(),
 			queue,
-			interruptible: true
+			interrupt: false
 		};
 
-		if let Err(err) = Self::install_interrupt_handler() {
-			warn!(
+		match Self::install_interrupt_handler() {
+			Ok(()) => this.interrupt = true,
+			Err(err) => warn!(
 				target: &this,
 				"== Failed to set interrupt handler: {:?}\n== Cancel requests may not be possible",
 				err
-			);
-
-			this.interruptible = false;
+			)
 		}
 
 		if let Some(err) = error {
@@ -282,15 +303,13 @@ impl ThreadPool {
 		Ok(this)
 	}
 
+	/// Create a thread pool sized from the CPUs this thread is allowed to run
+	/// on, which respects the cgroup `cpuset` and any inherited affinity
 	#[allow(clippy::expect_used, clippy::missing_panics_doc)]
 	pub fn new_with_default_count() -> Result<Self> {
-		let count = get_system_configuration(SystemConfiguration::NprocessorsOnln)?
-			.expect("Falied to get cpu count");
-		let mut count = count.try_into().unwrap_or(usize::MAX);
-
-		count = count.checked_mul(2).unwrap_or(usize::MAX);
+		let count = available_parallelism().expect("Failed to get cpu count");
 
-		Self::new(count)
+		Self::new(count.checked_mul(2).unwrap_or(usize::MAX))
 	}
 
 	/// # Safety
@@ -359,7 +378,7 @@ impl ThreadPool {
 
 				drop(work_queue);
 
-				trace!(target: self, "## cancel_direct(work = {:?}) = AsyncCancel(interrupted = {})", cancel.0, self.interruptible);
+				trace!(target: self, "## cancel_direct(work = {:?}) = AsyncCancel(interrupted = {})", cancel.0, self.interrupt);
 
 				self.interrupt_worker(worker);
 			} else {
@@ -1,6 +1,7 @@
 use std::io::SeekFrom;
 
 use xx_core::coroutines::{get_context, scoped};
+use xx_core::os::mman::MirrorBuffer;
 
 use super::*;
 use crate::async_tests::util::read::*;
@@ -367,3 +368,75 @@ pub async fn test_buf_reader_from_parts_fail1() {
 pub async fn test_buf_reader_from_parts_fail2() {
 	BufReader::from_parts(Sequential::new(), vec![0; 20], 21);
 }
+
+#[main]
+#[test]
+pub async fn test_buf_reader_mirrored() -> Result<()> {
+	let buf = MirrorBuffer::new(4096)?;
+	let mut reader = BufReader::with_mirror_buffer(Sequential::new(), buf);
+	let cap = reader.capacity();
+	let mut stream_pos = 0u64;
+
+	for consume in [100, 4000, 1, cap - 1, 2048] {
+		reader.fill_amount(cap).await?;
+
+		assert_eq!(reader.buffer().len(), cap);
+		assert_eq!(reader.spare_capacity(), 0);
+
+		for (i, b) in reader.buffer().iter().enumerate() {
+			assert_eq!(*b, (stream_pos + i as u64) as u8);
+		}
+
+		reader.consume(consume);
+		stream_pos += consume as u64;
+
+		assert_eq!(reader.spare_capacity(), consume);
+		assert_eq!(reader.stream_position().await?, stream_pos);
+	}
+
+	Ok(())
+}
+
+#[main]
+#[test]
+pub async fn test_buf_reader_mirrored_seek_back() -> Result<()> {
+	let buf = MirrorBuffer::new(4096)?;
+	let mut reader = BufReader::with_mirror_buffer(Sequential::new(), buf);
+	let cap = reader.capacity();
+
+	reader.fill_amount(cap).await?;
+	reader.consume(cap - 1000);
+
+	/* nothing has been overwritten yet, so this is served from the buffer */
+	assert_eq!(
+		reader.seek(SeekFrom::Current(-1000)).await?,
+		(cap - 2000) as u64
+	);
+	assert_eq!(reader.buffer().len(), 2000);
+	assert_eq!(reader.inner_mut().stream_position().await?, cap as u64);
+
+	reader.consume(1000);
+	reader.fill_amount(cap).await?;
+
+	/* reads past one buffer length, overwriting the start of the stream */
+	assert_eq!(reader.stream_position().await?, (cap - 1000) as u64);
+	assert_eq!(
+		reader.inner_mut().stream_position().await?,
+		(cap * 2 - 1000) as u64
+	);
+
+	let rel = 500 - (cap - 1000) as i64;
+	let pos = reader.seek(SeekFrom::Current(rel)).await?;
+
+	assert_eq!(pos, 500);
+	assert_eq!(reader.buffer().len(), 0);
+	assert_eq!(reader.inner_mut().stream_position().await?, 500);
+
+	reader.fill_amount(cap).await?;
+
+	for (i, b) in reader.buffer().iter().enumerate() {
+		assert_eq!(*b, (500 + i) as u8);
+	}
+
+	Ok(())
+}
@@ -0,0 +1,174 @@
+use std::cell::RefCell;
+
+use xx_core::async_std::sync::channel::mpmc;
+use xx_core::async_std::sync::{broadcast, Mutex, RcNotify};
+use xx_core::coroutines::*;
+use xx_core::macros::asynchronous;
+
+use super::env::*;
+
+#[asynchronous]
+async fn push(order: &RefCell<Vec<u32>>, first: u32, second: Option<u32>) {
+	order.borrow_mut().push(first);
+
+	if let Some(second) = second {
+		yield_now().await;
+
+		order.borrow_mut().push(second);
+	}
+}
+
+#[asynchronous]
+async fn yield_order() {
+	let env = test_env().await;
+	let order = RefCell::new(Vec::new());
+
+	let first = unsafe { spawn(env, push(&order, 1, Some(3))) };
+	let second = unsafe { spawn(env, push(&order, 2, None)) };
+
+	second.await;
+	first.await;
+
+	assert_eq!(*order.borrow(), [1, 2, 3]);
+}
+
+#[test]
+fn test_yield_now() {
+	run(yield_order());
+}
+
+#[asynchronous]
+async fn refill() {
+	assert!(acquire_budget(10).await);
+	assert_eq!(current_budget().await, DEFAULT_BUDGET - 10);
+
+	yield_now().await;
+
+	assert_eq!(current_budget().await, DEFAULT_BUDGET);
+}
+
+#[test]
+fn test_yield_now_refills_budget() {
+	run(refill());
+}
+
+#[asynchronous]
+async fn exhaust(order: &RefCell<Vec<u32>>) {
+	assert!(acquire_budget(DEFAULT_BUDGET).await);
+
+	/* yields, as the budget ran out */
+	consume_budget().await;
+
+	order.borrow_mut().push(1);
+}
+
+#[asynchronous]
+async fn consume() {
+	let env = test_env().await;
+	let order = RefCell::new(Vec::new());
+
+	let first = unsafe { spawn(env, exhaust(&order)) };
+	let second = unsafe { spawn(env, push(&order, 2, None)) };
+
+	second.await;
+	first.await;
+
+	assert_eq!(*order.borrow(), [2, 1]);
+}
+
+#[test]
+fn test_consume_budget() {
+	run(consume());
+}
+
+#[asynchronous]
+async fn fast_paths() {
+	let mutex = Mutex::new(());
+	let budget = current_budget().await;
+
+	drop(mutex.lock().await.unwrap());
+
+	assert_eq!(current_budget().await, budget - 1);
+
+	let (tx, rx) = mpmc::bounded(1);
+
+	tx.try_send(()).unwrap();
+	rx.recv().await.unwrap();
+
+	assert_eq!(current_budget().await, budget - 2);
+}
+
+#[test]
+fn test_fast_paths_charge_budget() {
+	run(fast_paths());
+}
+
+#[asynchronous]
+async fn wait(notify: &RcNotify) {
+	assert!(acquire_budget(DEFAULT_BUDGET).await);
+
+	/* waits right away, instead of yielding first */
+	notify.wait().await.unwrap();
+}
+
+#[asynchronous]
+async fn wait_uncharged() {
+	let env = test_env().await;
+	let notify = RcNotify::new();
+
+	let waiter = unsafe { spawn(env, wait(&notify)) };
+
+	assert_eq!(notify.notify(()), 1);
+
+	waiter.await;
+}
+
+#[test]
+fn test_wait_not_charged() {
+	run(wait_uncharged());
+}
+
+#[asynchronous]
+async fn many_operations() {
+	let mutex = Mutex::new(0);
+	let (tx, rx) = mpmc::bounded(1);
+	let (btx, mut brx) = broadcast::channel(1);
+
+	/* several times the budget, without anything resuming yielded tasks */
+	for i in 0..DEFAULT_BUDGET * 4 {
+		*mutex.lock().await.unwrap() += 1;
+
+		assert!(tx.send(i).await.is_ok());
+		assert_eq!(rx.recv().await.unwrap(), i);
+
+		assert!(btx.send(i).is_ok());
+		assert_eq!(brx.recv().await.unwrap(), i);
+	}
+
+	assert_eq!(*mutex.lock().await.unwrap(), DEFAULT_BUDGET * 4);
+}
+
+#[test]
+fn test_sync_without_ready_queue() {
+	run_undriven(many_operations());
+}
+
+#[asynchronous]
+async fn yield_undriven() {
+	let env = test_env().await;
+	let order = RefCell::new(Vec::new());
+
+	let first = unsafe { spawn(env, push(&order, 1, Some(3))) };
+	let second = unsafe { spawn(env, push(&order, 2, None)) };
+
+	second.await;
+	first.await;
+
+	/* the yield doesn't suspend */
+	assert_eq!(*order.borrow(), [1, 3, 2]);
+}
+
+#[test]
+fn test_yield_now_without_ready_queue() {
+	run_undriven(yield_undriven());
+}
@@ -0,0 +1,122 @@
+//! A single threaded environment for running tasks without a runtime.
+//! At most, tasks that yield are resumed by the executor, so every other task
+//! that suspends must be resumed by another task
+
+use std::cell::Cell;
+use std::mem::forget;
+
+use xx_core::coroutines::*;
+use xx_core::pointer::*;
+use xx_core::runtime::{self, catch_unwind_safe, MaybePanic};
+
+#[repr(C)]
+pub struct TestEnv {
+	context: Context,
+	executor: Ptr<Executor>
+}
+
+impl TestEnv {
+	/// # Safety
+	/// `executor` must outlive the environment
+	unsafe fn new(executor: Ptr<Executor>) -> Self {
+		Self {
+			context: unsafe { Context::new::<Self>(None) },
+			executor
+		}
+	}
+}
+
+unsafe impl Environment for TestEnv {
+	fn context(&self) -> &Context {
+		&self.context
+	}
+
+	fn context_mut(&mut self) -> &mut Context {
+		&mut self.context
+	}
+
+	unsafe fn from_context(context: &Context) -> &Self {
+		unsafe { ptr!(context).cast::<Self>().as_ref() }
+	}
+
+	unsafe fn clone(&self) -> Self {
+		unsafe { Self::new(self.executor) }
+	}
+
+	fn executor(&self) -> Ptr<Executor> {
+		self.executor
+	}
+}
+
+/// The environment of the current task
+#[asynchronous]
+pub async fn test_env<#[cx] 'current>() -> &'current TestEnv {
+	get_context()
+		.await
+		.get_environment()
+		.expect("Not running in a `TestEnv`")
+}
+
+#[asynchronous]
+async fn store<T, Output>(task: T, output: &Cell<Option<MaybePanic<Output>>>)
+where
+	T: for<'ctx> Task<Output<'ctx> = Output>
+{
+	let context = get_context().await;
+	let result = catch_unwind_safe(|| unsafe { scoped(context, task) });
+
+	output.set(Some(result));
+}
+
+/// Run `task` on a new executor, resuming its panic if it panics. Tasks that
+/// yield are resumed once every other task is suspended
+///
+/// # Panics
+/// If the task is still suspended once every other task is
+pub fn run<T, Output>(task: T) -> Output
+where
+	T: for<'ctx> Task<Output<'ctx> = Output>
+{
+	run_on(task, true)
+}
+
+/// Same as [`run`], on an executor that never resumes tasks that yield, like
+/// a runtime that only resumes tasks when their operations complete
+///
+/// # Panics
+/// See [`run`]
+pub fn run_undriven<T, Output>(task: T) -> Output
+where
+	T: for<'ctx> Task<Output<'ctx> = Output>
+{
+	run_on(task, false)
+}
+
+fn run_on<T, Output>(task: T, drives_ready: bool) -> Output
+where
+	T: for<'ctx> Task<Output<'ctx> = Output>
+{
+	let mut executor = Executor::new();
+
+	executor.set_drives_ready(drives_ready);
+
+	let executor = executor.pin_box();
+	let env = unsafe { TestEnv::new(ptr!(&*executor)) };
+	let output = Cell::new(None);
+
+	drop(unsafe { spawn(&env, store(task, &output)) });
+
+	if drives_ready {
+		while unsafe { executor.run_ready() } != 0 {}
+	}
+
+	let Some(result) = output.take() else {
+		/* the suspended tasks still use the executor */
+		forget(executor);
+		forget(env);
+
+		panic!("Task did not complete");
+	};
+
+	runtime::join(result)
+}
@@ -0,0 +1,183 @@
+use std::cell::{Cell, RefCell};
+
+use xx_core::async_std::sync::RcNotify;
+use xx_core::coroutines::{join_all, select_all, try_join_all};
+use xx_core::error::*;
+use xx_core::macros::asynchronous;
+
+use super::env::*;
+
+/// Waits for `wait` if set, then notifies `notify` if set
+#[asynchronous]
+async fn child(
+	index: usize, wait: Option<&RcNotify>, notify: Option<&RcNotify>, order: &RefCell<Vec<usize>>
+) -> usize {
+	if let Some(wait) = wait {
+		wait.wait().await.unwrap();
+	}
+
+	if let Some(notify) = notify {
+		notify.notify(());
+	}
+
+	order.borrow_mut().push(index);
+	index
+}
+
+#[derive(Clone, Copy)]
+enum Step {
+	Wait,
+	Fail,
+	Return
+}
+
+/// Counts the times it is cancelled while waiting for `notify`
+#[asynchronous]
+async fn step(
+	index: usize, step: Step, notify: &RcNotify, cancelled: &Cell<usize>
+) -> Result<usize> {
+	match step {
+		Step::Wait => {
+			let result = notify.wait().await;
+
+			if result.is_err() {
+				cancelled.set(cancelled.get() + 1);
+			}
+
+			result.map(|()| index)
+		}
+
+		Step::Fail => Err(ErrorKind::InvalidData.into()),
+		Step::Return => Ok(index)
+	}
+}
+
+#[asynchronous]
+async fn empty() {
+	let env = test_env().await;
+	let order = RefCell::new(Vec::new());
+	let notify = RcNotify::new();
+	let cancelled = Cell::new(0);
+
+	let results = unsafe { join_all(env, (0..0).map(|i| child(i, None, None, &order))).await };
+
+	assert!(results.is_empty());
+
+	let results = unsafe {
+		try_join_all(
+			env,
+			(0..0).map(|i| step(i, Step::Fail, &notify, &cancelled))
+		)
+		.await
+	};
+
+	assert!(results.unwrap().is_empty());
+}
+
+#[test]
+fn test_join_all_empty() {
+	run(empty());
+}
+
+#[asynchronous]
+async fn select_empty() {
+	let env = test_env().await;
+	let notify = RcNotify::new();
+	let cancelled = Cell::new(0);
+
+	unsafe {
+		select_all(
+			env,
+			(0..0).map(|i| step(i, Step::Fail, &notify, &cancelled))
+		)
+		.await
+	};
+}
+
+#[test]
+#[should_panic(expected = "no tasks")]
+fn test_select_all_empty() {
+	run(select_empty());
+}
+
+#[asynchronous]
+async fn out_of_order() {
+	let env = test_env().await;
+	let notify = RcNotify::new();
+	let order = RefCell::new(Vec::new());
+
+	/* the second child completes first, then the third wakes the first */
+	let tasks = [
+		child(0, Some(&notify), None, &order),
+		child(1, None, None, &order),
+		child(2, None, Some(&notify), &order)
+	];
+
+	let results = unsafe { join_all(env, tasks).await };
+
+	assert_eq!(results, [0, 1, 2]);
+	assert_eq!(order.borrow()[0], 1);
+	assert_eq!(order.borrow().len(), 3);
+}
+
+#[test]
+fn test_join_all_order() {
+	run(out_of_order());
+}
+
+#[asynchronous]
+async fn try_cancel() {
+	let env = test_env().await;
+	let notify = RcNotify::new();
+	let cancelled = Cell::new(0);
+	let steps = [Step::Wait, Step::Wait, Step::Fail];
+
+	let results = unsafe {
+		try_join_all(
+			env,
+			steps
+				.iter()
+				.enumerate()
+				.map(|(i, kind)| step(i, *kind, &notify, &cancelled))
+		)
+		.await
+	};
+
+	assert_eq!(results.unwrap_err(), ErrorKind::InvalidData);
+	assert_eq!(cancelled.get(), 2);
+}
+
+#[test]
+fn test_try_join_all_cancels() {
+	run(try_cancel());
+}
+
+#[asynchronous]
+async fn select_index() {
+	let env = test_env().await;
+	let notify = RcNotify::new();
+	let cancelled = Cell::new(0);
+	let steps = [Step::Wait, Step::Wait, Step::Return, Step::Wait];
+
+	let (index, result) = unsafe {
+		select_all(
+			env,
+			steps
+				.iter()
+				.enumerate()
+				.map(|(i, kind)| step(i, *kind, &notify, &cancelled))
+		)
+		.await
+	};
+
+	assert_eq!(index, 2);
+	assert_eq!(result.unwrap(), 2);
+
+	/* the last task never starts, as the third completes while it's spawned */
+	assert_eq!(cancelled.get(), 2);
+}
+
+#[test]
+fn test_select_all_index() {
+	run(select_index());
+}
@@ -1,6 +1,14 @@
 use super::*;
 
+mod budget;
 mod concurrency;
+mod env;
 mod interrupt;
+mod join_all;
 mod join_panic;
+mod multi_thread;
+mod scope;
+mod task_local;
+mod task_set;
+mod wake;
 mod works;
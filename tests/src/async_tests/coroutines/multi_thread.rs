@@ -0,0 +1,156 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use xx_core::async_std::sync::channel::{mpmc, RecvError};
+use xx_core::coroutines::multi_thread::*;
+use xx_core::macros::asynchronous;
+use xx_core::runtime;
+
+fn new_runtime(threads: usize, placement: Placement) -> Runtime {
+	Runtime::new(Options {
+		threads: Some(threads),
+		placement,
+		..Default::default()
+	})
+	.unwrap()
+}
+
+#[asynchronous]
+async fn double(value: usize) -> usize {
+	value * 2
+}
+
+#[asynchronous]
+async fn thread_name() -> Option<String> {
+	thread::current().name().map(ToOwned::to_owned)
+}
+
+#[test]
+fn test_spawn_on() {
+	let runtime = new_runtime(2, Placement::RoundRobin);
+
+	for index in 0..runtime.threads() {
+		let handle = runtime.spawn_on(index, double(index));
+
+		assert_eq!(runtime::join(handle.join_blocking()), index * 2);
+	}
+
+	/* the other threads are idle, but must not take the tasks */
+	let handles: Vec<_> = (0..16)
+		.map(|_| runtime.spawn_on(1, thread_name()))
+		.collect();
+
+	for handle in handles {
+		let name = runtime::join(handle.join_blocking());
+
+		assert_eq!(name.as_deref(), Some("xx-executor-1"));
+	}
+}
+
+#[test]
+fn test_spawn_anywhere() {
+	for placement in [Placement::RoundRobin, Placement::LeastLoaded] {
+		let runtime = new_runtime(4, placement);
+		let handles: Vec<_> = (0..32)
+			.map(|value| runtime.spawn_anywhere(double(value)))
+			.collect();
+
+		let sum: usize = handles
+			.into_iter()
+			.map(|handle| runtime::join(handle.join_blocking()))
+			.sum();
+
+		assert_eq!(sum, (0..32).map(|value| value * 2).sum::<usize>());
+		assert!(runtime.loads().all(|load| load == 0));
+	}
+}
+
+#[asynchronous]
+async fn fail() {
+	panic!("remote panic");
+}
+
+#[asynchronous]
+async fn join_remote(handle: RemoteJoinHandle<usize>) -> usize {
+	handle.await
+}
+
+#[test]
+fn test_join_blocking() {
+	let runtime = new_runtime(2, Placement::RoundRobin);
+
+	let panic = runtime.spawn_on(0, fail()).join_blocking().unwrap_err();
+
+	assert_eq!(panic.downcast_ref::<&str>(), Some(&"remote panic"));
+
+	/* join a task on one thread from a task on another */
+	let handle = runtime.spawn_on(1, double(21));
+	let joined = runtime.spawn_on(0, join_remote(handle));
+
+	assert_eq!(runtime::join(joined.join_blocking()), 42);
+}
+
+#[asynchronous]
+async fn receive(rx: mpmc::Receiver<()>, started: Arc<AtomicBool>) -> Result<(), RecvError> {
+	started.store(true, Ordering::SeqCst);
+	rx.recv().await
+}
+
+#[test]
+fn test_cancel() {
+	let runtime = new_runtime(2, Placement::RoundRobin);
+	let started = Arc::new(AtomicBool::new(false));
+	let (tx, rx) = mpmc::bounded(1);
+
+	let handle = runtime.spawn_on(0, receive(rx, started.clone()));
+
+	while !started.load(Ordering::SeqCst) {
+		thread::yield_now();
+	}
+
+	/* cancel the running task from this thread */
+	handle.request_cancel().unwrap();
+
+	let result = runtime::join(handle.join_blocking());
+
+	assert!(matches!(result, Err(RecvError::Empty)));
+
+	drop(tx);
+}
+
+#[asynchronous]
+async fn count(rx: mpmc::Receiver<()>, done: Arc<AtomicUsize>) {
+	let _ = rx.recv().await;
+
+	done.fetch_add(1, Ordering::SeqCst);
+}
+
+#[asynchronous]
+async fn release(senders: Vec<mpmc::Sender<()>>) {
+	for tx in senders {
+		let _ = tx.send(()).await;
+	}
+}
+
+#[test]
+fn test_shutdown_on_drop() {
+	let runtime = new_runtime(4, Placement::RoundRobin);
+	let done = Arc::new(AtomicUsize::new(0));
+	let mut senders = Vec::new();
+
+	for _ in 0..8 {
+		let (tx, rx) = mpmc::bounded(1);
+
+		senders.push(tx);
+
+		drop(runtime.spawn_anywhere(count(rx, done.clone())));
+	}
+
+	drop(runtime.spawn_anywhere(release(senders)));
+
+	/* waits for the blocked tasks to be released and complete */
+	drop(runtime);
+
+	assert_eq!(done.load(Ordering::SeqCst), 8);
+}
@@ -0,0 +1,188 @@
+use std::cell::Cell;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use xx_core::async_std::sync::RcNotify;
+use xx_core::coroutines::{interrupt_guard, scope, yield_now, Scope, ScopedJoinHandle};
+use xx_core::macros::asynchronous;
+
+use super::env::*;
+
+#[asynchronous]
+async fn write(slot: &mut i32, value: i32, notify: Option<&RcNotify>) {
+	if let Some(notify) = notify {
+		notify.wait().await.unwrap();
+	}
+
+	*slot = value;
+}
+
+/// Waits for `notify`, counting the times it is interrupted
+#[asynchronous]
+async fn wait(notify: &RcNotify, interrupted: &Cell<usize>) {
+	if notify.wait().await.is_err() {
+		interrupted.set(interrupted.get() + 1);
+	}
+}
+
+#[asynchronous]
+async fn child_panic() {
+	panic!("child panic");
+}
+
+#[asynchronous]
+async fn borrow() {
+	let env = test_env().await;
+	let notify = &RcNotify::new();
+	let mut results = [0; 2];
+	let (first, second) = results.split_at_mut(1);
+
+	scope(env, |scope: &Scope<'_, '_, _>| async move {
+		let first = scope.spawn(write(&mut first[0], 1, Some(notify)));
+		let second = scope.spawn(write(&mut second[0], 2, None));
+
+		notify.notify(());
+
+		first.await;
+		second.await;
+	})
+	.await;
+
+	assert_eq!(results, [1, 2]);
+}
+
+#[test]
+fn test_scope_borrow() {
+	run(borrow());
+}
+
+#[asynchronous]
+async fn propagate(interrupted: &Cell<usize>, finished: &Cell<bool>) {
+	let env = test_env().await;
+	let notify = &RcNotify::new();
+
+	scope(env, |scope: &Scope<'_, '_, _>| async move {
+		scope.spawn(wait(notify, interrupted));
+		scope.spawn(wait(notify, interrupted));
+		scope.spawn(child_panic());
+
+		/* the body itself runs to completion */
+		finished.set(true);
+	})
+	.await;
+
+	unreachable!();
+}
+
+#[test]
+fn test_scope_panic() {
+	let interrupted = Cell::new(0);
+	let finished = Cell::new(false);
+	let panic =
+		catch_unwind(AssertUnwindSafe(|| run(propagate(&interrupted, &finished)))).unwrap_err();
+
+	assert_eq!(panic.downcast_ref::<&str>(), Some(&"child panic"));
+	assert_eq!(interrupted.get(), 2);
+	assert!(finished.get());
+}
+
+#[asynchronous]
+async fn joined_panic() -> bool {
+	let env = test_env().await;
+
+	scope(env, |scope: &Scope<'_, '_, _>| async move {
+		let handle = scope.spawn(child_panic());
+
+		handle.try_join().await.is_err()
+	})
+	.await
+}
+
+#[test]
+fn test_scope_joined_panic() {
+	assert!(run(joined_panic()));
+}
+
+/// Spawns another child once interrupted
+#[asynchronous]
+async fn spawner<'scope, 'env>(
+	scope: &'scope Scope<'scope, 'env, TestEnv>, notify: &'scope RcNotify,
+	interrupted: &'scope Cell<usize>
+) {
+	wait(notify, interrupted).await;
+
+	scope.spawn(wait(notify, interrupted));
+}
+
+#[asynchronous]
+async fn spawn_during_shutdown() {
+	let env = test_env().await;
+	let notify = &RcNotify::new();
+	let interrupted = &Cell::new(0);
+
+	scope(env, |scope: &Scope<'_, '_, _>| async move {
+		scope.spawn(spawner(scope, notify, interrupted));
+	})
+	.await;
+
+	/* both the spawner and the child it spawned were interrupted and joined */
+	assert_eq!(interrupted.get(), 2);
+}
+
+#[test]
+fn test_scope_spawn_during_shutdown() {
+	run(spawn_during_shutdown());
+}
+
+/// Waits for `notify`, ignoring interrupts
+#[asynchronous]
+async fn wait_uninterruptible(notify: &RcNotify) {
+	let _guard = interrupt_guard().await;
+
+	notify.wait().await.unwrap();
+}
+
+/// Once interrupted, joins `handle` after the scope has started joining it
+#[asynchronous]
+async fn join_sibling(handle: ScopedJoinHandle<'_, ()>, never: &RcNotify, joined: &Cell<bool>) {
+	let _ = never.wait().await;
+
+	yield_now().await;
+
+	handle.await;
+
+	joined.set(true);
+}
+
+/// Once interrupted, releases `notify` after `join_sibling` is waiting
+#[asynchronous]
+async fn release_late(notify: &RcNotify, never: &RcNotify) {
+	let _ = never.wait().await;
+
+	yield_now().await;
+	yield_now().await;
+
+	notify.notify(());
+}
+
+#[asynchronous]
+async fn join_during_shutdown() {
+	let env = test_env().await;
+	let notify = &RcNotify::new();
+	let never = &RcNotify::new();
+	let joined = &Cell::new(false);
+
+	scope(env, |scope: &Scope<'_, '_, _>| async move {
+		let handle = scope.spawn(wait_uninterruptible(notify));
+
+		scope.spawn(join_sibling(handle, never, joined));
+		scope.spawn(release_late(notify, never));
+	})
+	.await;
+
+	assert!(joined.get());
+}
+
+#[test]
+fn test_scope_join_during_shutdown() {
+	run(join_during_shutdown());
+}
@@ -0,0 +1,72 @@
+use xx_core::coroutines::{spawn, without_task_locals};
+use xx_core::macros::asynchronous;
+use xx_core::task_local;
+
+use super::env::*;
+
+task_local! {
+	static ID: u64;
+}
+
+#[asynchronous]
+async fn get_id() -> Option<u64> {
+	ID.try_with(|id| *id).await
+}
+
+#[asynchronous]
+async fn nested() -> (Option<u64>, Option<u64>) {
+	let inner = ID.scope(2, get_id()).await;
+
+	(inner, get_id().await)
+}
+
+#[asynchronous]
+async fn scope_get() {
+	assert_eq!(get_id().await, None);
+	assert_eq!(ID.scope(1, get_id()).await, Some(1));
+	assert_eq!(ID.scope(1, nested()).await, (Some(2), Some(1)));
+	assert_eq!(get_id().await, None);
+}
+
+#[test]
+fn test_task_local_scope() {
+	run(scope_get());
+}
+
+#[asynchronous]
+async fn spawned() -> Option<u64> {
+	let env = test_env().await;
+
+	unsafe { spawn(env, get_id()) }.await
+}
+
+#[asynchronous]
+async fn inherit() {
+	assert_eq!(ID.scope(3, spawned()).await, Some(3));
+	assert_eq!(spawned().await, None);
+}
+
+#[test]
+fn test_task_local_inherit() {
+	run(inherit());
+}
+
+#[asynchronous]
+async fn without() {
+	let env = test_env().await;
+
+	assert_eq!(without_task_locals(get_id()).await, None);
+
+	/* awaiting inline leaves the caller's values in place */
+	assert_eq!(get_id().await, Some(4));
+
+	let handle = unsafe { spawn(env, without_task_locals(get_id())) };
+
+	assert_eq!(handle.await, None);
+	assert_eq!(get_id().await, Some(4));
+}
+
+#[test]
+fn test_without_task_locals() {
+	run(ID.scope(4, without()));
+}
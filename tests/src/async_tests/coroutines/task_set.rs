@@ -0,0 +1,186 @@
+use xx_core::async_std::sync::RcNotify;
+use xx_core::coroutines::{is_interrupted, spawn, TaskSet};
+use xx_core::error::*;
+use xx_core::macros::asynchronous;
+
+use super::env::*;
+
+#[asynchronous]
+async fn value(value: usize, notify: Option<&RcNotify>) -> Result<usize> {
+	if let Some(notify) = notify {
+		notify.wait().await?;
+	}
+
+	Ok(value)
+}
+
+#[asynchronous]
+async fn join_order() {
+	let env = test_env().await;
+	let first = RcNotify::new();
+	let second = RcNotify::new();
+	let mut set = TaskSet::new();
+
+	unsafe {
+		set.spawn(env, value(0, Some(&first)));
+		set.spawn(env, value(1, Some(&second)));
+		set.spawn(env, value(2, None));
+	}
+
+	second.notify(());
+
+	assert_eq!(set.len(), 3);
+	assert_eq!(set.join_next().await.unwrap().unwrap(), 2);
+	assert_eq!(set.join_next().await.unwrap().unwrap(), 1);
+
+	first.notify(());
+
+	assert_eq!(set.join_next().await.unwrap().unwrap(), 0);
+	assert!(set.join_next().await.is_none());
+	assert!(set.is_empty());
+}
+
+#[test]
+fn test_task_set_join_order() {
+	run(join_order());
+}
+
+#[asynchronous]
+async fn reap() {
+	let env = test_env().await;
+	let mut set = TaskSet::new();
+
+	for i in 0..1000 {
+		unsafe { set.spawn(env, value(i, None)) };
+	}
+
+	/* completed tasks are kept until joined */
+	assert_eq!(set.len(), 1000);
+
+	let mut sum = 0;
+
+	while let Some(result) = set.join_next().await {
+		sum += result.unwrap();
+	}
+
+	assert_eq!(sum, 999 * 1000 / 2);
+	assert!(set.is_empty());
+}
+
+#[test]
+fn test_task_set_reap() {
+	run(reap());
+}
+
+#[asynchronous]
+async fn cancel_all() {
+	let env = test_env().await;
+	let notify = RcNotify::new();
+	let mut set = TaskSet::new();
+
+	for i in 0..3 {
+		unsafe { set.spawn(env, value(i, Some(&notify))) };
+	}
+
+	set.cancel_all().unwrap();
+
+	for _ in 0..3 {
+		assert!(set.join_next().await.unwrap().is_err());
+	}
+
+	assert!(set.is_empty());
+}
+
+#[test]
+fn test_task_set_cancel_all() {
+	run(cancel_all());
+}
+
+#[asynchronous]
+async fn deadline(notify: &RcNotify) {
+	let _ = notify.wait().await;
+}
+
+/// Lets the tasks complete, then waits for a deadline that never passes
+#[asynchronous]
+async fn release(tasks: &RcNotify, never: &RcNotify) {
+	tasks.notify(());
+
+	let _ = never.wait().await;
+}
+
+#[asynchronous]
+async fn shutdown_drained() {
+	let env = test_env().await;
+	let tasks = RcNotify::new();
+	let never = RcNotify::new();
+	let mut set = TaskSet::new();
+
+	for i in 0..3 {
+		unsafe { set.spawn(env, value(i, Some(&tasks))) };
+	}
+
+	/* every task completes before the deadline */
+	assert_eq!(set.shutdown(release(&tasks, &never)).await, 0);
+	assert!(!is_interrupted().await);
+	assert!(set.is_empty());
+}
+
+#[test]
+fn test_task_set_shutdown_drained() {
+	run(shutdown_drained());
+}
+
+#[asynchronous]
+async fn shutdown_expired() {
+	let env = test_env().await;
+	let notify = RcNotify::new();
+	let mut set = TaskSet::new();
+
+	for i in 0..3 {
+		unsafe { set.spawn(env, value(i, Some(&notify))) };
+	}
+
+	unsafe { set.spawn(env, value(3, None)) };
+
+	/* the deadline has already passed */
+	assert_eq!(set.shutdown(value(0, None)).await, 3);
+	assert!(!is_interrupted().await);
+	assert!(set.is_empty());
+}
+
+#[test]
+fn test_task_set_shutdown_expired() {
+	run(shutdown_expired());
+}
+
+#[asynchronous]
+async fn shutdown(notify: &RcNotify) -> (usize, bool) {
+	let env = test_env().await;
+	let mut set = TaskSet::new();
+
+	for i in 0..2 {
+		unsafe { set.spawn(env, value(i, Some(notify))) };
+	}
+
+	let cancelled = set.shutdown(deadline(notify)).await;
+
+	(cancelled, is_interrupted().await)
+}
+
+#[asynchronous]
+async fn shutdown_interrupted() {
+	let env = test_env().await;
+	let notify = RcNotify::new();
+	let handle = unsafe { spawn(env, shutdown(&notify)) };
+
+	handle.request_cancel().unwrap();
+
+	/* the caller's own interrupt is not cleared by the set */
+	assert_eq!(handle.await, (2, true));
+}
+
+#[test]
+fn test_task_set_shutdown_interrupted() {
+	run(shutdown_interrupted());
+}
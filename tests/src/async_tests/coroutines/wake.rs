@@ -0,0 +1,51 @@
+use std::cell::Cell;
+use std::thread;
+use std::time::Duration;
+
+use xx_core::coroutines::wake::EventFdWaker;
+use xx_core::future::{ReqPtr, Request};
+use xx_core::pointer::*;
+
+fn woken(_: ReqPtr<()>, arg: Ptr<()>, (): ()) {
+	/* Safety: the arg is the counter, which outlives the request */
+	let count = unsafe { arg.cast::<Cell<usize>>().as_ref() };
+
+	count.set(count.get() + 1);
+}
+
+#[test]
+fn test_eventfd_waker() {
+	let event_waker = EventFdWaker::new().unwrap();
+	let count = Cell::new(0usize);
+
+	/* Safety: `woken` does not unwind */
+	let requests: Vec<_> = (0..3)
+		.map(|_| unsafe { Request::new(ptr!(&count).cast(), woken) })
+		.collect();
+
+	thread::scope(|scope| {
+		scope.spawn(|| {
+			/* Safety: the waker outlives the thread */
+			let waker = unsafe { event_waker.waker() };
+
+			for request in &requests {
+				/* Safety: each request is woken once */
+				unsafe {
+					waker.prepare();
+					waker.wake(ptr!(request));
+				}
+			}
+		});
+	});
+
+	assert_eq!(count.get(), 0);
+
+	/* Safety: we are the executor thread */
+	let resumed = unsafe { event_waker.wait(Duration::from_secs(1)) }.unwrap();
+
+	assert_eq!(resumed, 3);
+	assert_eq!(count.get(), 3);
+
+	/* Safety: see above */
+	assert_eq!(unsafe { event_waker.drain() }, 0);
+}
@@ -1,4 +1,12 @@
-use xx_core::fiber::{Fiber, Start};
+use std::env::{current_exe, var};
+use std::hint::black_box;
+use std::mem::replace;
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, ExitStatus, Stdio};
+use std::ptr::null_mut;
+
+use xx_core::fiber::*;
+use xx_core::os::signal::Signal;
 use xx_core::pointer::*;
 
 unsafe extern "C" fn start(arg: Ptr<()>) {
@@ -40,3 +48,156 @@ fn test_fibers() {
 		assert_eq!(data.as_mut().2, 10);
 	}
 }
+
+#[test]
+fn test_pool_size_classes() {
+	let pool = Pool::with_options(PoolOptions {
+		size_classes: vec![64 * 1024, 256 * 1024],
+		max_retained: Some(4),
+		..Default::default()
+	});
+
+	assert_eq!(pool.prewarm(8, Some(1)), 4);
+
+	unsafe {
+		let start = Start::new(start, Ptr::null());
+		let fiber = pool.new_fiber_with_stack(start, Some(100 * 1024));
+
+		assert_eq!(fiber.stack_size(), round_stack_size(256 * 1024));
+
+		pool.exit_fiber(fiber);
+
+		let fiber = pool.new_fiber_with_stack(start, Some(1));
+
+		assert_eq!(fiber.stack_size(), round_stack_size(64 * 1024));
+
+		pool.exit_fiber(fiber);
+	}
+}
+
+unsafe extern "C" fn use_stack(arg: Ptr<()>) {
+	let data = arg.cast::<(Fiber, Fiber, Pool)>().cast_mut();
+	let buf = [1u8; 32 * 1024];
+
+	black_box(&buf);
+
+	Fiber::switch(ptr!(&mut data=>1), ptr!(&mut data=>0));
+
+	let fiber = replace(&mut data.as_mut().1, Fiber::main());
+
+	fiber.exit_to_pool(ptr!(&mut data=>0), ptr!(&data=>2));
+}
+
+#[test]
+fn test_stack_high_water_mark() {
+	let pool = Pool::with_options(PoolOptions {
+		stack: StackOptions {
+			size: Some(256 * 1024),
+			canary: true,
+			..StackOptions::new()
+		},
+		..Default::default()
+	});
+
+	unsafe {
+		let mut data = (Fiber::main(), Fiber::main(), pool);
+		let data = ptr!(&mut data);
+		let start = Start::new(use_stack, data.cast_const().cast());
+
+		data.as_mut().1 = data.as_ref().2.new_fiber(start);
+
+		assert!(data.as_ref().1.stack_high_water_mark().unwrap() < 4096);
+		assert_eq!(data.as_ref().0.stack_high_water_mark(), None);
+
+		Fiber::switch(ptr!(&mut data=>0), ptr!(&mut data=>1));
+
+		let used = data.as_ref().1.stack_high_water_mark().unwrap();
+
+		assert!(used >= 32 * 1024 && used < 64 * 1024);
+
+		/* the fiber exits to the pool, which measures it */
+		Fiber::switch(ptr!(&mut data=>0), ptr!(&mut data=>1));
+
+		let stats = data.as_ref().2.stats();
+
+		assert_eq!(stats.active, 0);
+		assert_eq!(stats.measured, 1);
+		assert!(stats.high_water_mark >= used && stats.high_water_mark < 64 * 1024);
+		assert_eq!(stats.usage.iter().sum::<u64>(), 1);
+	}
+}
+
+/// Set in a child process to the overflow scenario it runs
+const OVERFLOW_CHILD: &str = "XX_OVERFLOW_CHILD";
+
+/// Recurses until the stack overflows
+#[allow(unconditional_recursion)]
+fn recurse(depth: usize) -> usize {
+	let buf = [depth; 128];
+
+	recurse(black_box(&buf)[0] + 1) + black_box(&buf)[1]
+}
+
+unsafe extern "C" fn overflow_stack(_: Ptr<()>) {
+	recurse(0);
+}
+
+fn overflow_fiber() {
+	let options = StackOptions { size: Some(64 * 1024), ..StackOptions::new() };
+
+	/* fill the first chunk of records */
+	let fibers: Vec<_> = (0..300).map(|_| Fiber::with_stack(&options)).collect();
+
+	unsafe {
+		let mut main = Fiber::main();
+		let mut fiber =
+			Fiber::with_stack_and_start(&options, Start::new(overflow_stack, Ptr::null()));
+
+		Fiber::switch(ptr!(&mut main), ptr!(&mut fiber));
+	}
+
+	drop(fibers);
+}
+
+/// Runs a scenario when started by [`test_overflow_detection`], so that the
+/// `SIGSEGV` handler isn't installed in the shared test process
+#[test]
+fn test_overflow_child() {
+	let Ok(scenario) = var(OVERFLOW_CHILD) else {
+		return;
+	};
+
+	overflow::install().unwrap();
+	overflow::install().unwrap();
+
+	assert!(overflow::enabled());
+
+	match scenario.as_str() {
+		"overflow" => overflow_fiber(),
+		"fault" => unsafe { null_mut::<u8>().write_volatile(1) },
+		_ => ()
+	}
+}
+
+fn run_child(scenario: &str) -> ExitStatus {
+	Command::new(current_exe().unwrap())
+		.args(["--exact", "fiber::test_overflow_child", "--test-threads=1"])
+		.env(OVERFLOW_CHILD, scenario)
+		.stdout(Stdio::null())
+		.stderr(Stdio::null())
+		.status()
+		.unwrap()
+}
+
+#[test]
+fn test_overflow_detection() {
+	/* an overflow is reported, then aborts */
+	let status = run_child("overflow");
+
+	assert_eq!(status.signal(), Some(Signal::Abort as i32));
+
+	/* other faults take the default action */
+	let status = run_child("fault");
+
+	assert_eq!(status.signal(), Some(Signal::SegmentationViolation as i32));
+}
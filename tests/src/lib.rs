@@ -5,4 +5,5 @@ mod fiber;
 mod impls;
 mod macros;
 mod os;
+mod random;
 mod sync;
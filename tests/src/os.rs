@@ -1,13 +1,30 @@
 use std::mem::transmute;
-use std::os::fd::{FromRawFd, OwnedFd};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
+use xx_core::os::clock::*;
 use xx_core::os::error::{result_from_int, result_from_ptr, OsError};
+use xx_core::os::landlock::{self, AccessFs, AccessNet, RestrictionStatus, Ruleset};
+use xx_core::os::memfd::{MemFd, MemfdFlag, Seal};
 use xx_core::os::mman::{Advice, Flag, Flags, Map, Protection, Type};
 use xx_core::os::poll::{poll_timeout, PollFd, PollFlag};
-use xx_core::os::resource::{get_rlimit, Resource};
-use xx_core::os::time::{nanotime, ClockId};
-use xx_core::os::unistd::close;
+use xx_core::os::prctl;
+use xx_core::os::random::{fill_random, RandomFlag};
+use xx_core::os::resource::{get_rlimit, get_rusage, raise_limit_to_max, Resource, UsageWho};
+use xx_core::os::sched::{sched_getaffinity, sched_setaffinity, CpuSet};
+use xx_core::os::seccomp::{Action, Comparison, Filter, Rule};
+use xx_core::os::signal::*;
+use xx_core::os::signal_handler::{self, register_flag};
+use xx_core::os::stat::{fchmod, fchown, statx_fd, Mode, Statx, StatxMask};
+use xx_core::os::syscall::fault::{self, Fault, FaultRule};
+use xx_core::os::syscall::{trace, SyscallNumber};
+use xx_core::os::time::{clock_gettime, nanotime, time, time_of_day, ClockId, TimeSpec};
+use xx_core::os::topology::Topology;
+use xx_core::os::unistd::{close, ftruncate, write};
+use xx_core::os::vdso;
+use xx_core::os::xattr;
 use xx_core::pointer::{MutPtr, Ptr};
 
 #[test]
@@ -80,3 +97,435 @@ fn test_error() {
 	result_from_ptr(isize::MAX).unwrap();
 	assert_eq!(OsError::from(2), OsError::NoEnt);
 }
+
+#[test]
+fn test_memfd_seal() {
+	let memfd = MemFd::new(
+		c"test",
+		MemfdFlag::CloseOnExec | MemfdFlag::AllowSealing,
+		None
+	)
+	.unwrap();
+
+	memfd.set_len(4096).unwrap();
+
+	let map = memfd.seal_and_map().unwrap();
+
+	assert_eq!(map.len(), 4096);
+	assert!(memfd.seals().unwrap().contains(Seal::Write | Seal::Shrink));
+	assert_eq!(memfd.set_len(8192).unwrap_err(), OsError::Perm);
+}
+
+#[test]
+fn test_getrandom() {
+	let mut buf = [0u8; 4096];
+
+	fill_random(&mut buf, RandomFlag::NonBlock.into()).unwrap();
+
+	assert!(buf.iter().any(|b| *b != 0));
+}
+
+#[test]
+fn test_vdso_time() {
+	assert!(vdso::functions().clock_gettime.is_some());
+
+	let mut ts = TimeSpec::default();
+
+	clock_gettime(ClockId::Monotonic, &mut ts).unwrap();
+
+	let vdso_time = time(ClockId::Monotonic).unwrap();
+
+	assert!(vdso_time.as_nanos() >= ts.as_nanos());
+	assert!(time_of_day().unwrap().sec > 0);
+}
+
+#[test]
+fn test_aux_value() {
+	let page_size = vdso::get_aux_value(6).unwrap();
+
+	assert!(page_size >= 4096 && page_size.is_power_of_two());
+	assert!(vdso::get_aux_value(vdso::AT_SYSINFO_EHDR).is_some_and(|base| base != 0));
+	assert_eq!(vdso::get_aux_value(u64::MAX), None);
+}
+
+#[test]
+fn test_manual_clock() {
+	let clock = ManualClock::default();
+	let start = clock.now();
+	let deadline = start + Duration::from_secs(5);
+
+	clock.advance(Duration::from_secs(2));
+
+	assert_eq!(clock.now().duration_until(deadline), Duration::from_secs(3));
+	assert_eq!(clock.now() - start, Duration::from_secs(2));
+	assert_eq!(
+		clock.system_time().duration_since_epoch(),
+		Some(Duration::from_secs(2))
+	);
+
+	clock.advance(Duration::from_secs(4));
+
+	assert_eq!(clock.now().duration_until(deadline), Duration::ZERO);
+	assert!(start.checked_sub(Duration::from_nanos(1)).is_none());
+	assert!(Instant::<Monotonic>::now() >= Instant::from_nanos(1));
+}
+
+#[test]
+fn test_manual_clock_concurrent_advance() {
+	let clock = ManualClock::default();
+	let start = clock.now();
+
+	std::thread::scope(|scope| {
+		for _ in 0..4 {
+			scope.spawn(|| {
+				for _ in 0..1000 {
+					clock.advance(Duration::from_nanos(1));
+				}
+			});
+		}
+	});
+
+	assert_eq!(clock.now() - start, Duration::from_nanos(4000));
+}
+
+#[test]
+fn test_timer_slack() {
+	std::thread::spawn(|| {
+		prctl::set_timer_slack(123_456).unwrap();
+
+		assert_eq!(prctl::get_timer_slack().unwrap(), 123_456);
+	})
+	.join()
+	.unwrap();
+}
+
+#[test]
+fn test_landlock_abi() {
+	let version = landlock::abi_version();
+
+	assert!(landlock::supported_access_fs(0).is_empty());
+	assert!(landlock::supported_access_fs(1).contains(AccessFs::ReadFile | AccessFs::ReadDir));
+	assert!(!landlock::supported_access_fs(1).contains(AccessFs::Refer));
+	assert!(landlock::supported_access_fs(3).contains(AccessFs::Truncate));
+	assert!(landlock::supported_access_net(3).is_empty());
+	assert!(landlock::supported_access_net(4).contains(AccessNet::BindTcp));
+
+	if version > 0 {
+		assert!(!landlock::supported_access_fs(version).is_empty());
+	}
+}
+
+#[test]
+fn test_landlock_path_beneath() {
+	if landlock::abi_version() == 0 {
+		return;
+	}
+
+	std::thread::spawn(|| {
+		let allowed = std::env::temp_dir();
+		let access = AccessFs::ReadFile | AccessFs::ReadDir;
+		let status = Ruleset::new()
+			.handle_fs(access)
+			.path_beneath(&allowed, access)
+			.unwrap()
+			.restrict_self()
+			.unwrap();
+
+		assert_eq!(status, RestrictionStatus::FullyEnforced);
+
+		/* the directory rights survive, so the file type was read */
+		assert!(std::fs::read_dir(&allowed).is_ok());
+		assert!(std::fs::read_dir("/").is_err());
+	})
+	.join()
+	.unwrap();
+}
+
+#[test]
+fn test_seccomp_compile() {
+	let filter = Filter::new(Action::Allow)
+		.errno(SyscallNumber::Ptrace, OsError::Perm)
+		.rule(
+			Rule::new(SyscallNumber::Socket, Action::Errno(OsError::Acces))
+				.arg(0, Comparison::NotEqual(1))
+		)
+		.deny(SyscallNumber::Reboot);
+
+	let program = filter.compile().unwrap();
+	let last = program.last().unwrap();
+
+	assert_eq!(last.k, Action::Allow.into_raw());
+
+	let mut conditions = Rule::new(SyscallNumber::Read, Action::Allow);
+
+	for _ in 0..64 {
+		conditions = conditions.arg(0, Comparison::Greater(1));
+	}
+
+	assert_eq!(
+		Filter::new(Action::Allow).rule(conditions).compile().unwrap_err(),
+		OsError::TooBig
+	);
+}
+
+#[test]
+fn test_cpu_set() {
+	let set = CpuSet::parse_list("0-3,8,10-11\n").unwrap();
+
+	assert_eq!(set.count(), 7);
+	assert_eq!(set.iter().collect::<Vec<_>>(), [0, 1, 2, 3, 8, 10, 11]);
+	assert!(CpuSet::parse_list("3-1").is_none());
+
+	/* sets grow past the kernel's default size */
+	let mut large = CpuSet::parse_list("1,2047").unwrap();
+
+	assert!(large.contains(2047));
+	assert_eq!(large.iter().collect::<Vec<_>>(), [1, 2047]);
+
+	large.remove(2047);
+
+	assert_eq!(large, [1].into_iter().collect());
+
+	let allowed = sched_getaffinity(None).unwrap();
+
+	assert!(!allowed.is_empty());
+
+	sched_setaffinity(None, &allowed).unwrap();
+
+	let topology = Topology::read().unwrap();
+
+	for cpu in allowed.iter() {
+		assert!(topology.online.contains(cpu));
+	}
+
+	assert!(topology.physical_cores(&allowed).count() <= allowed.count());
+}
+
+#[test]
+fn test_rusage_delta() {
+	let before = get_rusage(UsageWho::Thread).unwrap();
+	let mut value = 0u64;
+
+	for i in 0..10_000_000u64 {
+		value = std::hint::black_box(value.wrapping_add(i));
+	}
+
+	let after = get_rusage(UsageWho::Thread).unwrap();
+	let delta = after - before;
+
+	assert!(delta.cpu_time() <= after.cpu_time());
+	assert_eq!(delta.max_rss, after.max_rss);
+
+	let limit = raise_limit_to_max(Resource::NoFile).unwrap();
+
+	assert_eq!(limit.current, limit.maximum);
+	assert_eq!(get_rlimit(Resource::NoFile).unwrap(), limit);
+}
+
+#[test]
+fn test_permissions() {
+	let memfd = MemFd::new(c"test", MemfdFlag::CloseOnExec.into(), None).unwrap();
+
+	fchmod(memfd.fd(), Mode::UserRead | Mode::UserWrite | Mode::GroupRead).unwrap();
+
+	let mut statx = Statx::default();
+
+	statx_fd(memfd.fd(), 0, StatxMask::Mode as u32, &mut statx).unwrap();
+
+	assert_eq!(statx.permissions().unwrap().bits(), 0o640);
+
+	fchown(memfd.fd(), None, None).unwrap();
+
+	let names: Vec<_> = xattr::names(b"user.a\0security.b\0").collect();
+
+	assert_eq!(names, [c"user.a", c"security.b"]);
+}
+
+#[test]
+fn test_fault_error() {
+	let memfd = MemFd::new(c"test", MemfdFlag::CloseOnExec.into(), None).unwrap();
+	let guard =
+		fault::inject(FaultRule::new(SyscallNumber::Write, Fault::Error(OsError::Intr)).nth(2));
+
+	write(memfd.fd(), b"a".as_slice().into()).unwrap();
+	assert_eq!(
+		write(memfd.fd(), b"b".as_slice().into()).unwrap_err(),
+		OsError::Intr
+	);
+	assert_eq!(fault::pending(), 0);
+	write(memfd.fd(), b"c".as_slice().into()).unwrap();
+
+	drop(guard);
+
+	let mut statx = Statx::default();
+
+	statx_fd(memfd.fd(), 0, StatxMask::Size as u32, &mut statx).unwrap();
+
+	assert_eq!(statx.size, 2);
+}
+
+#[test]
+fn test_fault_short() {
+	let memfd = MemFd::new(c"test", MemfdFlag::CloseOnExec.into(), None).unwrap();
+	let _guard = fault::inject(FaultRule::new(SyscallNumber::Write, Fault::Short(3)));
+
+	write(memfd.fd(), b"abcdefgh".as_slice().into()).unwrap();
+
+	let mut statx = Statx::default();
+
+	statx_fd(memfd.fd(), 0, StatxMask::Size as u32, &mut statx).unwrap();
+
+	assert_eq!(statx.size, 3);
+}
+
+#[test]
+#[should_panic(expected = "Short faults are not supported")]
+fn test_fault_short_unsupported() {
+	let _guard = fault::inject(FaultRule::new(SyscallNumber::Writev, Fault::Short(3)));
+}
+
+#[test]
+fn test_fault_guard() {
+	let first = fault::inject(FaultRule::new(
+		SyscallNumber::Read,
+		Fault::Error(OsError::Intr)
+	));
+	let second = fault::inject(FaultRule::new(
+		SyscallNumber::Write,
+		Fault::Error(OsError::Intr)
+	));
+
+	assert_eq!(fault::pending(), 2);
+
+	drop(first);
+
+	assert_eq!(fault::pending(), 1);
+
+	drop(second);
+
+	assert_eq!(fault::pending(), 0);
+}
+
+#[test]
+fn test_trace_records() {
+	let memfd = MemFd::new(c"test", MemfdFlag::CloseOnExec.into(), None).unwrap();
+	let fd = memfd.fd().as_raw_fd();
+
+	trace::clear();
+
+	write(memfd.fd(), b"abc".as_slice().into()).unwrap();
+	write(memfd.fd(), b"de".as_slice().into()).unwrap();
+	assert_eq!(ftruncate(memfd.fd(), -1).unwrap_err(), OsError::Inval);
+
+	let records = trace::take();
+
+	assert_eq!(records.len(), 3);
+	assert_eq!(records[0].syscall(), Some(SyscallNumber::Write));
+	assert_eq!(records[0].args()[0], fd as usize);
+	assert_eq!(records[0].args()[2], 3);
+	assert_eq!(records[0].result(), Ok(3));
+	assert_eq!(records[1].args()[2], 2);
+	assert_eq!(records[1].result(), Ok(2));
+	assert_eq!(records[2].syscall(), Some(SyscallNumber::Ftruncate));
+	assert_eq!(records[2].args(), [fd as usize, usize::MAX]);
+	assert_eq!(records[2].result(), Err(OsError::Inval));
+	assert!(trace::records().is_empty());
+}
+
+#[test]
+fn test_trace_capacity() {
+	let memfd = MemFd::new(c"test", MemfdFlag::CloseOnExec.into(), None).unwrap();
+
+	trace::set_capacity(2);
+	trace::clear();
+
+	for len in 1..=4 {
+		write(memfd.fd(), b"abcd"[..len].into()).unwrap();
+	}
+
+	let lens: Vec<_> = trace::take()
+		.iter()
+		.map(|record| record.args()[2])
+		.collect();
+
+	assert_eq!(lens, [3, 4]);
+
+	trace::set_capacity(trace::CAPACITY);
+}
+
+extern "C" {
+	fn raise(sig: i32) -> i32;
+}
+
+#[test]
+fn test_signal_register_flag() {
+	let signal = Signal::User1 as i32;
+	let flag = Arc::new(AtomicBool::new(false));
+	let registration = register_flag(signal, flag.clone()).unwrap();
+
+	/* Safety: the handler is installed */
+	unsafe { raise(signal) };
+
+	assert!(flag.swap(false, Ordering::Relaxed));
+
+	drop(registration);
+
+	/* Safety: see above */
+	unsafe { raise(signal) };
+
+	assert!(!flag.load(Ordering::Relaxed));
+}
+
+static PREVIOUS_CALLS: AtomicUsize = AtomicUsize::new(0);
+static PREVIOUS_MASKED: AtomicBool = AtomicBool::new(false);
+
+const fn signal_bit(signal: Signal) -> SignalSet {
+	1 << (signal as u32 - 1)
+}
+
+fn blocked(signal: Signal) -> bool {
+	let mut mask = [0; 16];
+
+	pthread_set_sigmask(SignalHow::Block, None, Some(&mut mask)).unwrap();
+
+	mask[0] & signal_bit(signal) != 0
+}
+
+extern "C" fn previous_handler(_: i32) {
+	PREVIOUS_CALLS.fetch_add(1, Ordering::Relaxed);
+	PREVIOUS_MASKED.store(blocked(Signal::Child), Ordering::Relaxed);
+}
+
+#[test]
+fn test_signal_chain() {
+	let signal = Signal::User2 as i32;
+	let mut action = SigAction::default();
+
+	action.handler.handler = Some(previous_handler);
+	action.mask[0] = signal_bit(Signal::Child);
+	action.flags = SignalFlags::ResetHand as u32;
+
+	sig_action(signal, Some(&action), None).unwrap();
+
+	let calls = Arc::new(AtomicUsize::new(0));
+	let counter = calls.clone();
+
+	/* Safety: incrementing an atomic is async-signal-safe */
+	let _registration = unsafe {
+		signal_handler::register(signal, move |_| {
+			counter.fetch_add(1, Ordering::Relaxed);
+		})
+	}
+	.unwrap();
+
+	for _ in 0..2 {
+		/* Safety: the handler is installed */
+		unsafe { raise(signal) };
+	}
+
+	assert_eq!(calls.load(Ordering::Relaxed), 2);
+	assert_eq!(PREVIOUS_CALLS.load(Ordering::Relaxed), 1);
+	assert!(PREVIOUS_MASKED.load(Ordering::Relaxed));
+	assert!(!blocked(Signal::Child));
+	assert!(!signal_handler::in_handler());
+}
@@ -0,0 +1,24 @@
+use xx_core::random::*;
+
+#[test]
+fn test_gen_range() {
+	let mut rng = WyRand::new(0x1234);
+
+	for _ in 0..10000 {
+		assert!((5..17).contains(&rng.gen_range(5u32..17)));
+		assert!((-8..3).contains(&rng.gen_range(-8i64..3)));
+		assert_eq!(rng.gen_range(u8::MAX - 1..u8::MAX), u8::MAX - 1);
+	}
+
+	assert!((0..100).contains(&gen_range(0usize..100)));
+}
+
+#[test]
+fn test_shuffle() {
+	let mut values: Vec<_> = (0..100).collect();
+
+	shuffle(&mut values);
+	values.sort();
+
+	assert!(values.iter().copied().eq(0..100));
+}
@@ -1 +1,2 @@
 mod spin_mutex;
+mod work_queue;
@@ -0,0 +1,72 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use xx_core::sync::work_queue::*;
+
+#[test]
+fn test_push_pop() {
+	let queue = WorkQueue::new(3);
+
+	assert_eq!(queue.capacity(), 4);
+
+	for i in 0..4 {
+		queue.push(i).unwrap();
+	}
+
+	assert_eq!(queue.push(4), Err(4));
+	assert_eq!(queue.stealer().steal(), Some(0));
+	assert_eq!(queue.pop(), Some(1));
+	assert_eq!(queue.len(), 2);
+}
+
+#[test]
+fn test_steal() {
+	const COUNT: usize = 100_000;
+
+	let queue = WorkQueue::new(64);
+	let done = Arc::new(AtomicBool::new(false));
+	let sum = Arc::new(AtomicUsize::new(0));
+	let mut handles = Vec::new();
+
+	for _ in 0..4 {
+		let stealer = queue.stealer();
+		let done = done.clone();
+		let sum = sum.clone();
+
+		handles.push(thread::spawn(move || loop {
+			match stealer.steal() {
+				Some(value) => {
+					sum.fetch_add(*value, Ordering::Relaxed);
+				}
+
+				None if done.load(Ordering::SeqCst) => break,
+				None => ()
+			}
+		}));
+	}
+
+	for i in 1..=COUNT {
+		let mut value = Box::new(i);
+
+		while let Err(rejected) = queue.push(value) {
+			value = rejected;
+
+			if let Some(value) = queue.pop() {
+				sum.fetch_add(*value, Ordering::Relaxed);
+			}
+		}
+	}
+
+	done.store(true, Ordering::SeqCst);
+
+	for handle in handles {
+		handle.join().unwrap();
+	}
+
+	while let Some(value) = queue.pop() {
+		sum.fetch_add(*value, Ordering::Relaxed);
+	}
+
+	assert_eq!(sum.load(Ordering::Relaxed), COUNT * (COUNT + 1) / 2);
+}